@@ -1,6 +1,6 @@
 use steel::*;
 
-#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive)]
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u32)]
 pub enum OreError {
     #[error("Amount too small")]
@@ -8,6 +8,42 @@ pub enum OreError {
 
     #[error("Not authorized")]
     NotAuthorized = 1,
+
+    #[error("Miner has not checkpointed the prior round")]
+    NotCheckpointed = 2,
+
+    #[error("Round is not active")]
+    RoundNotActive = 3,
+
+    #[error("Round has not ended")]
+    RoundNotEnded = 4,
+
+    #[error("Round has not expired")]
+    RoundNotExpired = 5,
+
+    #[error("Invalid round")]
+    InvalidRound = 6,
+
+    #[error("Automation balance is insufficient to deploy")]
+    InsufficientAutomationBalance = 7,
+
+    #[error("Invalid entropy var")]
+    InvalidVar = 8,
+
+    #[error("Invariant violation")]
+    InvariantViolation = 9,
+
+    #[error("Automation has spent its budget for this round")]
+    AutomationRoundBudgetExceeded = 10,
+
+    #[error("Deploy landed after its max slot")]
+    DeployDeadlinePassed = 11,
+
+    #[error("Square is more crowded than the deploy guard allows")]
+    DeployGuardFailed = 12,
+
+    #[error("Automation has already deployed this round")]
+    AutomationAlreadyDeployed = 13,
 }
 
 error!(OreError);
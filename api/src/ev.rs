@@ -0,0 +1,226 @@
+use crate::{
+    consts::{MAX_SUPPLY, ONE_ORE},
+    state::Round,
+};
+
+/// One in this many rounds pays out the motherlode, as sampled by `did_hit_motherlode`.
+pub const MOTHERLODE_ODDS: u64 = 625;
+
+/// What a deployment pays if a given square wins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SquarePayout {
+    /// The amount of SOL credited at checkpoint: the deployment minus the admin fee, plus a
+    /// pro-rata share of the winnings.
+    pub sol: u64,
+
+    /// The amount of ORE earned from the top miner reward. Split rounds pay exactly this, and
+    /// winner-take-all rounds pay the whole reward with the same odds.
+    pub top_miner_ore: u64,
+
+    /// The amount of ORE earned if the motherlode hits too.
+    pub motherlode_ore: u64,
+}
+
+/// The returns of an allocation for every possible winning square.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpectedReturns {
+    /// The amount of SOL deployed on each square.
+    pub amounts: [u64; 25],
+
+    /// The payout if each square wins.
+    pub payouts: [SquarePayout; 25],
+}
+
+impl ExpectedReturns {
+    /// The total amount of SOL deployed.
+    pub fn cost(&self) -> u64 {
+        self.amounts.iter().sum()
+    }
+
+    /// The number of squares out of 25 that pay anything.
+    pub fn winning_squares(&self) -> usize {
+        self.amounts.iter().filter(|&&a| a > 0).count()
+    }
+
+    /// The expected amount of SOL returned, with each square winning one round in 25.
+    pub fn expected_sol(&self) -> u64 {
+        let total: u128 = self.payouts.iter().map(|p| p.sol as u128).sum();
+        (total / 25) as u64
+    }
+
+    /// The expected amount of ORE earned, including the motherlode at its odds.
+    pub fn expected_ore(&self) -> u64 {
+        let top_miner: u128 = self.payouts.iter().map(|p| p.top_miner_ore as u128).sum();
+        let motherlode: u128 = self.payouts.iter().map(|p| p.motherlode_ore as u128).sum();
+        let odds = MOTHERLODE_ODDS as u128;
+        ((top_miner * odds + motherlode) / (25 * odds)) as u64
+    }
+
+    /// The expected SOL profit, negative if the allocation loses SOL on average.
+    pub fn expected_sol_profit(&self) -> i64 {
+        self.expected_sol() as i64 - self.cost() as i64
+    }
+
+    /// The expected amount of SOL returned, with ORE valued at `ore_price` lamports per ORE.
+    pub fn expected_value(&self, ore_price: u64) -> u64 {
+        let ore = (self.expected_ore() as u128 * ore_price as u128) / ONE_ORE as u128;
+        self.expected_sol() + ore as u64
+    }
+}
+
+/// Calculates the returns of deploying `amounts` into a round on top of what is already there,
+/// for a miner with nothing on those squares yet. Uses the same integer math and fee order as
+/// `reset` and `checkpoint`, given the current mint supply and motherlode pool.
+pub fn expected_returns(
+    round: &Round,
+    amounts: &[u64; 25],
+    mint_supply: u64,
+    motherlode_pool: u64,
+) -> ExpectedReturns {
+    let mut deployed = round.deployed;
+    for (d, &amount) in deployed.iter_mut().zip(amounts) {
+        *d += amount;
+    }
+    let total_deployed: u64 = deployed.iter().sum();
+    let top_miner_reward = MAX_SUPPLY.saturating_sub(mint_supply).min(ONE_ORE);
+
+    let mut payouts = [SquarePayout::default(); 25];
+    for (square, payout) in payouts.iter_mut().enumerate() {
+        let amount = amounts[square];
+        if amount == 0 {
+            continue;
+        }
+        let share = |pool: u64| ((pool as u128 * amount as u128) / deployed[square] as u128) as u64;
+
+        // Winnings are the SOL on every other square, minus the admin fee and vault.
+        let winnings = total_deployed - deployed[square];
+        let winnings = winnings - winnings / 100;
+        let winnings = winnings - winnings / 10;
+
+        *payout = SquarePayout {
+            sol: amount - (amount / 100).max(1) + share(winnings),
+            top_miner_ore: share(top_miner_reward),
+            motherlode_ore: share(motherlode_pool),
+        };
+    }
+
+    ExpectedReturns {
+        amounts: *amounts,
+        payouts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use steel::{Pubkey, Zeroable};
+
+    use super::*;
+    use crate::{sim, state::Miner};
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    /// Finds a var value whose rng satisfies the predicate.
+    fn find_value(predicate: impl Fn(&Round, u64) -> bool) -> [u8; 32] {
+        let mut round = Round::zeroed();
+        for i in 1..u32::MAX {
+            round.slot_hash = [0; 32];
+            round.slot_hash[0..4].copy_from_slice(&i.to_le_bytes());
+            if let Some(r) = round.rng() {
+                if predicate(&round, r) {
+                    return round.slot_hash;
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    /// A round with uneven deployments from other miners.
+    fn board() -> Round {
+        let mut round = Round::zeroed();
+        for square in 0..25 {
+            round.deployed[square] = (square as u64 * 37_000_001) % (3 * ONE_SOL);
+            round.total_deployed += round.deployed[square];
+        }
+        round
+    }
+
+    /// Adds our deployment to the round, as `deploy` would.
+    fn deploy(round: &Round, amounts: &[u64; 25]) -> (Round, Miner) {
+        let mut round = *round;
+        let mut miner = Miner::zeroed();
+        miner.authority = Pubkey::new_unique();
+        for (square, &amount) in amounts.iter().enumerate() {
+            miner.cumulative[square] = round.deployed[square];
+            miner.deployed[square] = amount;
+            round.deployed[square] += amount;
+            round.total_deployed += amount;
+        }
+        (round, miner)
+    }
+
+    #[test]
+    fn test_matches_settlement() {
+        let round = board();
+        let mut amounts = [0; 25];
+        amounts[0] = 12_345_678;
+        amounts[3] = ONE_SOL / 3;
+        amounts[24] = 99;
+        let mint_supply = 1_234 * ONE_ORE;
+        let motherlode_pool = 57 * ONE_ORE + 3;
+        let returns = expected_returns(&round, &amounts, mint_supply, motherlode_pool);
+        let (round, miner) = deploy(&round, &amounts);
+
+        // Every winning square pays what checkpoint credits in a split round.
+        for square in 0..25 {
+            let value =
+                find_value(|r, rng| r.winning_square(rng) == square && r.is_split_reward(rng));
+            let simulation = sim::simulate(&round, &[miner], value, mint_supply, motherlode_pool);
+            let rewards = simulation.rewards[0];
+            let payout = returns.payouts[square];
+            assert_eq!(payout.sol, rewards.rewards_sol);
+            assert_eq!(payout.top_miner_ore, rewards.top_miner_ore);
+        }
+
+        // The motherlode pays the same share.
+        let value =
+            find_value(|r, rng| r.did_hit_motherlode(rng) && amounts[r.winning_square(rng)] > 0);
+        let simulation = sim::simulate(&round, &[miner], value, mint_supply, motherlode_pool);
+        let square = simulation.outcome.winning_square.unwrap();
+        assert_eq!(
+            returns.payouts[square].motherlode_ore,
+            simulation.rewards[0].motherlode_ore
+        );
+    }
+
+    #[test]
+    fn test_expected_returns() {
+        let mut round = Round::zeroed();
+        round.deployed = [ONE_SOL; 25];
+        round.total_deployed = 25 * ONE_SOL;
+        let mut amounts = [0; 25];
+        amounts[7] = ONE_SOL;
+        let returns = expected_returns(&round, &amounts, 0, 625 * ONE_ORE);
+
+        // Half the square, and 24 SOL of winnings less 1% and then 10%.
+        let winnings = 24 * ONE_SOL - 24 * ONE_SOL / 100;
+        let winnings = winnings - winnings / 10;
+        let payout = returns.payouts[7];
+        assert_eq!(payout.sol, ONE_SOL - ONE_SOL / 100 + winnings / 2);
+        assert_eq!(payout.top_miner_ore, ONE_ORE / 2);
+        assert_eq!(payout.motherlode_ore, 625 * ONE_ORE / 2);
+        assert_eq!(returns.cost(), ONE_SOL);
+        assert_eq!(returns.winning_squares(), 1);
+        assert_eq!(returns.expected_sol(), payout.sol / 25);
+        assert_eq!(returns.expected_ore(), (ONE_ORE / 2 + ONE_ORE / 2) / 25);
+        assert_eq!(
+            returns.expected_value(2 * ONE_SOL),
+            payout.sol / 25 + 2 * ONE_SOL / 25
+        );
+        assert!(returns.expected_sol_profit() < 0);
+
+        // Nothing is minted past the max supply.
+        let returns = expected_returns(&round, &amounts, MAX_SUPPLY, 0);
+        assert_eq!(returns.payouts[7].top_miner_ore, 0);
+        assert_eq!(returns.expected_ore(), 0);
+    }
+}
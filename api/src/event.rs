@@ -1,9 +1,21 @@
 use serde::{Deserialize, Serialize};
 use steel::*;
+
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TryFromPrimitive)]
 pub enum OreEvent {
     Reset = 0,
     Bury = 1,
     Motherlode = 2,
+    Deploy = 3,
+    Checkpoint = 4,
+    ClaimSOL = 5,
+    ClaimORE = 6,
+    Deposit = 7,
+    Withdraw = 8,
+    ClaimYield = 9,
+    Automate = 10,
+    DeploySkipped = 11,
 }
 
 #[repr(C)]
@@ -90,5 +102,222 @@ pub struct MotherlodeEvent {
     pub ts: i64,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct DeployEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The signer of the deploy, either the authority or its automation executor.
+    pub signer: Pubkey,
+
+    /// The authority of the miner.
+    pub authority: Pubkey,
+
+    /// The round id.
+    pub round_id: u64,
+
+    /// The amount of SOL deployed to each square.
+    pub amounts: [u64; 25],
+
+    /// The total amount of SOL deployed.
+    pub total_amount: u64,
+
+    /// The number of squares deployed to.
+    pub num_squares: u64,
+
+    /// The total amount of SOL deployed in the round after this deploy.
+    pub round_total_deployed: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct CheckpointEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the miner.
+    pub authority: Pubkey,
+
+    /// The round id.
+    pub round_id: u64,
+
+    /// The amount of SOL rewarded (or refunded) to the miner.
+    pub rewards_sol: u64,
+
+    /// The amount of ORE rewarded for the top miner reward.
+    pub top_miner_ore: u64,
+
+    /// The amount of ORE rewarded from the motherlode.
+    pub motherlode_ore: u64,
+
+    /// Whether the miner was the top miner (1) or not (0).
+    pub is_top_miner: u64,
+
+    /// The checkpoint fee paid to the signer.
+    pub bot_fee: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct ClaimSOLEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the miner.
+    pub authority: Pubkey,
+
+    /// The amount of SOL claimed.
+    pub amount: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct ClaimOREEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the miner.
+    pub authority: Pubkey,
+
+    /// The amount of ORE claimed, net of fees.
+    pub amount: u64,
+
+    /// The amount of ORE charged as a fee and shared with unclaimed miners.
+    pub fee: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct DepositEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the stake account.
+    pub authority: Pubkey,
+
+    /// The amount of ORE deposited.
+    pub amount: u64,
+
+    /// The balance of the stake account after the deposit.
+    pub balance: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct WithdrawEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the stake account.
+    pub authority: Pubkey,
+
+    /// The amount of ORE withdrawn.
+    pub amount: u64,
+
+    /// The balance of the stake account after the withdrawal.
+    pub balance: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct ClaimYieldEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the stake account.
+    pub authority: Pubkey,
+
+    /// The amount of ORE claimed.
+    pub amount: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct AutomateEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the automation.
+    pub authority: Pubkey,
+
+    /// The executor of the automation, or the default pubkey if the automation was closed.
+    pub executor: Pubkey,
+
+    /// The amount of SOL to deploy per square.
+    pub amount: u64,
+
+    /// The amount of SOL deposited.
+    pub deposit: u64,
+
+    /// The fee paid to the executor per deploy.
+    pub fee: u64,
+
+    /// The square mask.
+    pub mask: u64,
+
+    /// The automation strategy.
+    pub strategy: u64,
+
+    /// The balance of the automation after this instruction.
+    pub balance: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable, Serialize, Deserialize)]
+pub struct DeploySkippedEvent {
+    /// The event discriminator.
+    pub disc: u64,
+
+    /// The authority of the miner.
+    pub authority: Pubkey,
+
+    /// The round id.
+    pub round_id: u64,
+
+    /// Mask of squares skipped because the miner already held them and others deployed since.
+    pub held: u64,
+
+    /// Mask of squares skipped by the deploy guard.
+    pub guarded: u64,
+
+    /// The timestamp of the event.
+    pub ts: i64,
+}
+
 event!(ResetEvent);
 event!(BuryEvent);
+event!(MotherlodeEvent);
+event!(DeployEvent);
+event!(CheckpointEvent);
+event!(ClaimSOLEvent);
+event!(ClaimOREEvent);
+event!(DepositEvent);
+event!(WithdrawEvent);
+event!(ClaimYieldEvent);
+event!(AutomateEvent);
+event!(DeploySkippedEvent);
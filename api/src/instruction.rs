@@ -10,6 +10,7 @@ pub enum OreInstruction {
     ClaimORE = 4,
     Close = 5,
     Deploy = 6,
+    DeployWeighted = 7,
     Log = 8,
     Reset = 9,
 
@@ -38,6 +39,21 @@ pub struct Automate {
     pub strategy: u8,
 }
 
+/// Optional data after `Automate` that limits how long and how much the executor may deploy.
+/// Automations set without it have no limits.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Pod, Zeroable)]
+pub struct AutomateLimits {
+    /// Most rounds to deploy in, 0 for no limit.
+    pub max_rounds: [u8; 8],
+
+    /// Slot the automation expires at, 0 for never.
+    pub expires_at: [u8; 8],
+
+    /// Most SOL, fees included, the executor may spend in one round, 0 for no limit.
+    pub max_per_round: [u8; 8],
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct ClaimSOL {}
@@ -53,6 +69,66 @@ pub struct Deploy {
     pub squares: [u8; 4],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct DeployWeighted {
+    pub amounts: [[u8; 8]; 25],
+}
+
+/// Optional data after `Deploy` or `DeployWeighted` that bounds how crowded each square may be
+/// when the deploy lands, like slippage protection. The round account already pins the round.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct DeployGuard {
+    /// Last slot the deploy may land in, 0 for no limit.
+    pub max_slot: [u8; 8],
+
+    /// Most SOL each square may already hold, u64::MAX for no limit.
+    pub max_deployed: [[u8; 8]; 25],
+
+    /// Least share of a square's pool the deploy must get, in basis points.
+    pub min_share: [u8; 8],
+
+    /// 1 fails the whole deploy when a square is out of bounds, 0 skips the square.
+    pub strict: u8,
+}
+
+impl DeployGuard {
+    pub fn new(max_slot: u64, max_deployed: [u64; 25], min_share: u64, strict: bool) -> Self {
+        DeployGuard {
+            max_slot: max_slot.to_le_bytes(),
+            max_deployed: max_deployed.map(u64::to_le_bytes),
+            min_share: min_share.to_le_bytes(),
+            strict: strict as u8,
+        }
+    }
+
+    /// Whether the deploy may still land at `slot`.
+    pub fn is_on_time(&self, slot: u64) -> bool {
+        let max_slot = u64::from_le_bytes(self.max_slot);
+        max_slot == 0 || slot <= max_slot
+    }
+
+    /// Whether `amount` may be deployed to a square that already holds `square_deployed`.
+    pub fn allows(&self, square_id: usize, square_deployed: u64, amount: u64) -> bool {
+        let max_deployed = u64::from_le_bytes(self.max_deployed[square_id]);
+        let min_share = u64::from_le_bytes(self.min_share) as u128;
+        square_deployed <= max_deployed
+            && amount as u128 * 10_000 >= min_share * (square_deployed as u128 + amount as u128)
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict != 0
+    }
+}
+
+impl Default for DeployGuard {
+    /// A guard that allows every deploy.
+    fn default() -> Self {
+        DeployGuard::new(0, [u64::MAX; 25], 0, false)
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Log {}
@@ -158,6 +234,7 @@ instruction!(OreInstruction, Checkpoint);
 instruction!(OreInstruction, ClaimSOL);
 instruction!(OreInstruction, ClaimORE);
 instruction!(OreInstruction, Deploy);
+instruction!(OreInstruction, DeployWeighted);
 instruction!(OreInstruction, Log);
 instruction!(OreInstruction, Wrap);
 instruction!(OreInstruction, Bury);
@@ -170,3 +247,28 @@ instruction!(OreInstruction, ClaimYield);
 instruction!(OreInstruction, NewVar);
 instruction!(OreInstruction, SetBuffer);
 instruction!(OreInstruction, SetSwapProgram);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deploy_guard() {
+        let guard = DeployGuard::default();
+        assert!(guard.is_on_time(u64::MAX));
+        assert!(guard.allows(0, u64::MAX, 1));
+
+        let mut max_deployed = [u64::MAX; 25];
+        max_deployed[3] = 1_000;
+        let guard = DeployGuard::new(100, max_deployed, 2_500, true);
+        assert!(guard.is_on_time(100));
+        assert!(!guard.is_on_time(101));
+        assert!(guard.allows(3, 1_000, 1_000));
+        assert!(!guard.allows(3, 1_001, 1_000));
+
+        // 25% of the pool after deploying.
+        assert!(guard.allows(4, 3_000, 1_000));
+        assert!(!guard.allows(4, 3_001, 1_000));
+        assert!(guard.is_strict());
+    }
+}
@@ -1,8 +1,10 @@
 pub mod consts;
 pub mod error;
+pub mod ev;
 pub mod event;
 pub mod instruction;
 pub mod sdk;
+pub mod sim;
 pub mod state;
 
 pub mod prelude {
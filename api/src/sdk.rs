@@ -13,7 +13,7 @@ pub fn log(signer: Pubkey, msg: &[u8]) -> Instruction {
     data.extend_from_slice(msg);
     Instruction {
         program_id: crate::ID,
-        accounts: vec![AccountMeta::new(signer, true)],
+        accounts: vec![AccountMeta::new_readonly(signer, true)],
         data: data,
     }
 }
@@ -22,8 +22,10 @@ pub fn program_log(accounts: &[AccountInfo], msg: &[u8]) -> Result<(), ProgramEr
     invoke_signed(&log(*accounts[0].key, msg), accounts, &crate::ID, &[BOARD])
 }
 
-// let [signer_info, automation_info, executor_info, miner_info, system_program] = accounts else {
+// let [signer_info, automation_info, executor_info, miner_info, system_program, board_info, ore_program] =
 
+/// Creates, updates or closes the signer's automation. Zero limits mean no limit.
+#[allow(clippy::too_many_arguments)]
 pub fn automate(
     signer: Pubkey,
     amount: u64,
@@ -32,9 +34,25 @@ pub fn automate(
     fee: u64,
     mask: u64,
     strategy: u8,
+    max_rounds: u64,
+    expires_at: u64,
+    max_per_round: u64,
 ) -> Instruction {
     let automation_address = automation_pda(signer).0;
     let miner_address = miner_pda(signer).0;
+    let mut data = Automate {
+        amount: amount.to_le_bytes(),
+        deposit: deposit.to_le_bytes(),
+        fee: fee.to_le_bytes(),
+        mask: mask.to_le_bytes(),
+        strategy: strategy as u8,
+    }
+    .to_bytes();
+    data.extend_from_slice(bytemuck::bytes_of(&AutomateLimits {
+        max_rounds: max_rounds.to_le_bytes(),
+        expires_at: expires_at.to_le_bytes(),
+        max_per_round: max_per_round.to_le_bytes(),
+    }));
     Instruction {
         program_id: crate::ID,
         accounts: vec![
@@ -43,15 +61,10 @@ pub fn automate(
             AccountMeta::new(executor, false),
             AccountMeta::new(miner_address, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
-        data: Automate {
-            amount: amount.to_le_bytes(),
-            deposit: deposit.to_le_bytes(),
-            fee: fee.to_le_bytes(),
-            mask: mask.to_le_bytes(),
-            strategy: strategy as u8,
-        }
-        .to_bytes(),
+        data,
     }
 }
 
@@ -63,12 +76,14 @@ pub fn claim_sol(signer: Pubkey) -> Instruction {
             AccountMeta::new(signer, true),
             AccountMeta::new(miner_address, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: ClaimSOL {}.to_bytes(),
     }
 }
 
-// let [signer_info, miner_info, mint_info, recipient_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program] =
+// let [signer_info, miner_info, mint_info, recipient_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program, board_info, ore_program] =
 
 pub fn claim_ore(signer: Pubkey) -> Instruction {
     let miner_address = miner_pda(signer).0;
@@ -87,6 +102,8 @@ pub fn claim_ore(signer: Pubkey) -> Instruction {
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: ClaimORE {}.to_bytes(),
     }
@@ -129,6 +146,8 @@ pub fn deploy(
             // Entropy accounts.
             AccountMeta::new(entropy_var_address, false),
             AccountMeta::new_readonly(entropy_api::ID, false),
+            // Event accounts.
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: Deploy {
             amount: amount.to_le_bytes(),
@@ -138,6 +157,58 @@ pub fn deploy(
     }
 }
 
+// let [signer_info, authority_info, automation_info, board_info, miner_info, round_info, system_program] =
+
+pub fn deploy_weighted(
+    signer: Pubkey,
+    authority: Pubkey,
+    round_id: u64,
+    amounts: [u64; 25],
+) -> Instruction {
+    let automation_address = automation_pda(authority).0;
+    let board_address = board_pda().0;
+    let miner_address = miner_pda(authority).0;
+    let round_address = round_pda(round_id).0;
+    let entropy_var_address = entropy_api::state::var_pda(board_address, 0).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(authority, false),
+            AccountMeta::new(automation_address, false),
+            AccountMeta::new(board_address, false),
+            AccountMeta::new(miner_address, false),
+            AccountMeta::new(round_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            // Entropy accounts.
+            AccountMeta::new(entropy_var_address, false),
+            AccountMeta::new_readonly(entropy_api::ID, false),
+            // Event accounts.
+            AccountMeta::new_readonly(crate::ID, false),
+        ],
+        data: DeployWeighted {
+            amounts: amounts.map(u64::to_le_bytes),
+        }
+        .to_bytes(),
+    }
+}
+
+/// Adds the accounts `deploy` and `deploy_weighted` need to checkpoint the miner's previous round
+/// in the same instruction, instead of failing with `NotCheckpointed`.
+pub fn with_checkpoint(mut ix: Instruction, previous_round_id: u64) -> Instruction {
+    ix.accounts
+        .push(AccountMeta::new(round_pda(previous_round_id).0, false));
+    ix.accounts.push(AccountMeta::new(TREASURY_ADDRESS, false));
+    ix
+}
+
+/// Appends a guard to the data of `deploy` or `deploy_weighted`, so squares that got more crowded
+/// than expected are skipped, or the deploy fails if the guard is strict.
+pub fn with_guard(mut ix: Instruction, guard: DeployGuard) -> Instruction {
+    ix.data.extend_from_slice(bytemuck::bytes_of(&guard));
+    ix
+}
+
 // let [pool, user_source_token, user_destination_token, a_vault, b_vault, a_token_vault, b_token_vault, a_vault_lp_mint, b_vault_lp_mint, a_vault_lp, b_vault_lp, protocol_token_fee, user_key, vault_program, token_program] =
 
 pub fn bury(signer: Pubkey, swap_accounts: &[AccountMeta], swap_data: &[u8]) -> Instruction {
@@ -251,7 +322,7 @@ pub fn close(signer: Pubkey, round_id: u64, rent_payer: Pubkey) -> Instruction {
     }
 }
 
-// let [signer_info, automation_info, board_info, miner_info, round_info, treasury_info, system_program] =
+// let [signer_info, board_info, miner_info, round_info, treasury_info, system_program, ore_program] =
 
 pub fn checkpoint(signer: Pubkey, authority: Pubkey, round_id: u64) -> Instruction {
     let miner_address = miner_pda(authority).0;
@@ -267,6 +338,7 @@ pub fn checkpoint(signer: Pubkey, authority: Pubkey, round_id: u64) -> Instructi
             AccountMeta::new(round_address, false),
             AccountMeta::new(treasury_address, false),
             AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: Checkpoint {}.to_bytes(),
     }
@@ -320,7 +392,7 @@ pub fn set_fee_collector(signer: Pubkey, fee_collector: Pubkey) -> Instruction {
     }
 }
 
-// let [signer_info, mint_info, sender_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program] =
+// let [signer_info, mint_info, sender_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program, board_info, ore_program] =
 
 pub fn deposit(signer: Pubkey, amount: u64) -> Instruction {
     let mint_address = MINT_ADDRESS;
@@ -340,6 +412,8 @@ pub fn deposit(signer: Pubkey, amount: u64) -> Instruction {
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: Deposit {
             amount: amount.to_le_bytes(),
@@ -348,7 +422,7 @@ pub fn deposit(signer: Pubkey, amount: u64) -> Instruction {
     }
 }
 
-// let [signer_info, mint_info, recipient_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program] =
+// let [signer_info, mint_info, recipient_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program, board_info, ore_program] =
 
 pub fn withdraw(signer: Pubkey, amount: u64) -> Instruction {
     let stake_address = stake_pda(signer).0;
@@ -368,6 +442,8 @@ pub fn withdraw(signer: Pubkey, amount: u64) -> Instruction {
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: Withdraw {
             amount: amount.to_le_bytes(),
@@ -376,7 +452,7 @@ pub fn withdraw(signer: Pubkey, amount: u64) -> Instruction {
     }
 }
 
-// let [signer_info, mint_info, recipient_info, stake_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program] =
+// let [signer_info, mint_info, recipient_info, stake_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program, board_info, ore_program] =
 
 pub fn claim_yield(signer: Pubkey, amount: u64) -> Instruction {
     let stake_address = stake_pda(signer).0;
@@ -396,6 +472,8 @@ pub fn claim_yield(signer: Pubkey, amount: u64) -> Instruction {
             AccountMeta::new_readonly(system_program::ID, false),
             AccountMeta::new_readonly(spl_token::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(board_pda().0, false),
+            AccountMeta::new_readonly(crate::ID, false),
         ],
         data: ClaimYield {
             amount: amount.to_le_bytes(),
@@ -0,0 +1,362 @@
+use crate::{
+    consts::{MAX_SUPPLY, ONE_ORE, SPLIT_ADDRESS},
+    state::{Miner, Round},
+};
+
+/// The settlement of a round, as computed by `reset`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RoundOutcome {
+    /// The winning square, or None if the round has no slot hash and all SOL is refunded.
+    pub winning_square: Option<usize>,
+
+    /// The amount of SOL sent to the fee collector.
+    pub admin_fee: u64,
+
+    /// The total amount of SOL deployed in the round, after refunds.
+    pub total_deployed: u64,
+
+    /// The total amount of SOL put in the ORE vault.
+    pub total_vaulted: u64,
+
+    /// The total amount of SOL won by miners on the winning square.
+    pub total_winnings: u64,
+
+    /// The amount of ORE minted for the top miner.
+    pub top_miner_reward: u64,
+
+    /// Whether the top miner reward is split between all miners on the winning square.
+    pub is_split: bool,
+
+    /// The amount of ORE paid out of the motherlode.
+    pub motherlode: u64,
+
+    /// The amount of ORE minted into the motherlode rewards pool.
+    pub motherlode_minted: u64,
+}
+
+impl RoundOutcome {
+    /// Writes the settlement into the round account.
+    pub fn apply(&self, round: &mut Round) {
+        round.total_deployed = self.total_deployed;
+        round.total_vaulted = self.total_vaulted;
+        round.total_winnings = self.total_winnings;
+        round.top_miner_reward = self.top_miner_reward;
+        round.motherlode = self.motherlode;
+        if self.is_split {
+            round.top_miner = SPLIT_ADDRESS;
+        }
+    }
+
+    /// The total amount of ORE minted by the round.
+    pub fn total_minted(&self) -> u64 {
+        self.top_miner_reward + self.motherlode_minted
+    }
+}
+
+/// The rewards of a single miner, as computed by `checkpoint`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MinerRewards {
+    /// The amount of SOL credited to the miner (winnings or refund).
+    pub rewards_sol: u64,
+
+    /// The amount of ORE earned from the top miner reward (whole or split).
+    pub top_miner_ore: u64,
+
+    /// The amount of ORE earned from the motherlode.
+    pub motherlode_ore: u64,
+
+    /// Whether the miner was sampled as the top miner.
+    pub is_top_miner: bool,
+}
+
+impl MinerRewards {
+    /// The total amount of ORE credited to the miner.
+    pub fn rewards_ore(&self) -> u64 {
+        self.top_miner_ore + self.motherlode_ore
+    }
+}
+
+/// The result of replaying a full round offline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+    /// The round account after reset and all checkpoints.
+    pub round: Round,
+
+    /// The settlement of the round.
+    pub outcome: RoundOutcome,
+
+    /// The rewards of each miner, in the order they were provided.
+    pub rewards: Vec<MinerRewards>,
+}
+
+/// Settles a round whose slot hash has been set, given the current mint supply and motherlode pool.
+pub fn settle_round(round: &Round, mint_supply: u64, motherlode_pool: u64) -> RoundOutcome {
+    // If no slot hash was found, refund all SOL.
+    let Some(r) = round.rng() else {
+        return RoundOutcome::default();
+    };
+
+    // Calculate admin fees.
+    let admin_fee = round.total_deployed / 100;
+    let winning_square = round.winning_square(r);
+
+    // If no one deployed on the winning square, vault all deployed.
+    if round.deployed[winning_square] == 0 {
+        return RoundOutcome {
+            winning_square: Some(winning_square),
+            admin_fee,
+            total_deployed: round.total_deployed,
+            total_vaulted: round.total_deployed - admin_fee,
+            ..Default::default()
+        };
+    }
+
+    // Get winnings amount (total deployed on all non-winning squares, minus admin fee and vault).
+    let winnings = round.calculate_total_winnings(winning_square);
+    let winnings = winnings - winnings / 100;
+    let vault_amount = winnings / 10;
+    let winnings = winnings - vault_amount;
+
+    // Mint +1 ORE for the winning miner(s), and +0.2 ORE to the motherlode.
+    let top_miner_reward = MAX_SUPPLY.saturating_sub(mint_supply).min(ONE_ORE);
+    let motherlode_minted = MAX_SUPPLY
+        .saturating_sub(mint_supply + top_miner_reward)
+        .min(ONE_ORE / 5);
+
+    // Payout the motherlode if it was activated.
+    let motherlode = if round.did_hit_motherlode(r) {
+        motherlode_pool
+    } else {
+        0
+    };
+
+    RoundOutcome {
+        winning_square: Some(winning_square),
+        admin_fee,
+        total_deployed: round.total_deployed,
+        total_vaulted: vault_amount,
+        total_winnings: winnings,
+        top_miner_reward,
+        is_split: round.is_split_reward(r),
+        motherlode,
+        motherlode_minted,
+    }
+}
+
+/// Calculates the rewards of a miner in a settled round.
+pub fn miner_rewards(round: &Round, miner: &Miner) -> MinerRewards {
+    // Round has no slot hash, refund all SOL.
+    let Some(r) = round.rng() else {
+        return MinerRewards {
+            rewards_sol: miner.deployed.iter().sum::<u64>(),
+            ..Default::default()
+        };
+    };
+
+    // If the miner did not deploy to the winning square, there are no rewards.
+    let winning_square = round.winning_square(r);
+    let deployed = miner.deployed[winning_square];
+    if deployed == 0 {
+        return MinerRewards::default();
+    }
+    let square_deployed = round.deployed[winning_square];
+
+    // Calculate SOL rewards.
+    let admin_fee = (deployed / 100).max(1);
+    let rewards_sol = deployed - admin_fee
+        + ((round.total_winnings as u128 * deployed as u128) / square_deployed as u128) as u64;
+
+    // Calculate ORE rewards.
+    let mut rewards = MinerRewards {
+        rewards_sol,
+        ..Default::default()
+    };
+    if round.top_miner == SPLIT_ADDRESS {
+        // If round is split, split the reward evenly among all miners.
+        rewards.top_miner_ore =
+            ((round.top_miner_reward as u128 * deployed as u128) / square_deployed as u128) as u64;
+    } else {
+        // If round is not split, payout to the top miner.
+        let top_miner_sample = round.top_miner_sample(r, winning_square);
+        let cumulative = miner.cumulative[winning_square];
+        if top_miner_sample >= cumulative && top_miner_sample < cumulative + deployed {
+            rewards.top_miner_ore = round.top_miner_reward;
+            rewards.is_top_miner = true;
+        }
+    }
+
+    // Calculate motherlode rewards.
+    if round.motherlode > 0 {
+        rewards.motherlode_ore =
+            ((round.motherlode as u128 * deployed as u128) / square_deployed as u128) as u64;
+    }
+
+    rewards
+}
+
+/// Replays reset and checkpoint for a round with the given var value, mint supply and motherlode pool.
+pub fn simulate(
+    round: &Round,
+    miners: &[Miner],
+    value: [u8; 32],
+    mint_supply: u64,
+    motherlode_pool: u64,
+) -> Simulation {
+    let mut round = *round;
+    round.slot_hash = value;
+    let outcome = settle_round(&round, mint_supply, motherlode_pool);
+    outcome.apply(&mut round);
+    let rewards = miners
+        .iter()
+        .map(|miner| {
+            let rewards = miner_rewards(&round, miner);
+            if rewards.is_top_miner {
+                round.top_miner = miner.authority;
+            }
+            rewards
+        })
+        .collect();
+    Simulation {
+        round,
+        outcome,
+        rewards,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use steel::{Pubkey, Zeroable};
+
+    use super::*;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    /// Builds a round and miners from a list of (authority, square, amount) deployments.
+    fn deploy(deployments: &[(u8, usize, u64)]) -> (Round, Vec<Miner>) {
+        let mut round = Round::zeroed();
+        let mut miners: Vec<Miner> = vec![];
+        for &(authority, square, amount) in deployments {
+            let authority = Pubkey::new_from_array([authority; 32]);
+            let index = match miners.iter().position(|m| m.authority == authority) {
+                Some(index) => index,
+                None => {
+                    let mut miner = Miner::zeroed();
+                    miner.authority = authority;
+                    miners.push(miner);
+                    miners.len() - 1
+                }
+            };
+            let miner = &mut miners[index];
+            miner.cumulative[square] = round.deployed[square];
+            miner.deployed[square] = amount;
+            round.deployed[square] += amount;
+            round.total_deployed += amount;
+            round.count[square] += 1;
+        }
+        (round, miners)
+    }
+
+    /// Finds a var value whose rng satisfies the predicate.
+    fn find_value(predicate: impl Fn(&Round, u64) -> bool) -> [u8; 32] {
+        let mut round = Round::zeroed();
+        for i in 1..u32::MAX {
+            round.slot_hash = [0; 32];
+            round.slot_hash[0..4].copy_from_slice(&i.to_le_bytes());
+            if let Some(r) = round.rng() {
+                if predicate(&round, r) {
+                    return round.slot_hash;
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_refund_without_slot_hash() {
+        let (round, miners) = deploy(&[(1, 0, ONE_SOL), (1, 3, ONE_SOL), (2, 4, ONE_SOL)]);
+        let sim = simulate(&round, &miners, [0; 32], 0, 10 * ONE_ORE);
+        assert_eq!(sim.outcome, RoundOutcome::default());
+        assert_eq!(sim.round.total_deployed, 0);
+        assert_eq!(sim.rewards[0].rewards_sol, 2 * ONE_SOL);
+        assert_eq!(sim.rewards[1].rewards_sol, ONE_SOL);
+        assert_eq!(sim.rewards[0].rewards_ore(), 0);
+    }
+
+    #[test]
+    fn test_empty_winning_square_vaults_all() {
+        let (round, miners) = deploy(&[(1, 0, ONE_SOL), (2, 1, ONE_SOL)]);
+        let value = find_value(|r, rng| r.winning_square(rng) > 1);
+        let sim = simulate(&round, &miners, value, 0, 10 * ONE_ORE);
+        assert_eq!(sim.outcome.admin_fee, 2 * ONE_SOL / 100);
+        assert_eq!(sim.outcome.total_vaulted, 2 * ONE_SOL - 2 * ONE_SOL / 100);
+        assert_eq!(sim.outcome.total_winnings, 0);
+        assert_eq!(sim.outcome.total_minted(), 0);
+        assert!(sim.rewards.iter().all(|r| *r == MinerRewards::default()));
+    }
+
+    #[test]
+    fn test_split_reward() {
+        let (round, miners) = deploy(&[(1, 0, ONE_SOL), (2, 0, 3 * ONE_SOL), (3, 1, 4 * ONE_SOL)]);
+        let value = find_value(|r, rng| {
+            r.winning_square(rng) == 0 && r.is_split_reward(rng) && !r.did_hit_motherlode(rng)
+        });
+        let sim = simulate(&round, &miners, value, 0, 10 * ONE_ORE);
+
+        // Losing SOL minus 1% admin fee, minus 10% vault.
+        let winnings = 4 * ONE_SOL - 4 * ONE_SOL / 100;
+        let vault = winnings / 10;
+        assert_eq!(sim.outcome.total_vaulted, vault);
+        assert_eq!(sim.outcome.total_winnings, winnings - vault);
+        assert_eq!(sim.round.top_miner, SPLIT_ADDRESS);
+
+        // Winners get their deployment back minus 1%, plus a pro-rata share of winnings and ORE.
+        let r1 = sim.rewards[0];
+        let r2 = sim.rewards[1];
+        assert_eq!(
+            r1.rewards_sol,
+            ONE_SOL - ONE_SOL / 100 + (winnings - vault) / 4
+        );
+        assert_eq!(
+            r2.rewards_sol,
+            3 * ONE_SOL - 3 * ONE_SOL / 100 + 3 * (winnings - vault) / 4
+        );
+        assert_eq!(r1.top_miner_ore, ONE_ORE / 4);
+        assert_eq!(r2.top_miner_ore, 3 * ONE_ORE / 4);
+        assert_eq!(sim.rewards[2], MinerRewards::default());
+    }
+
+    #[test]
+    fn test_top_miner_takes_all() {
+        let (round, miners) = deploy(&[(1, 7, ONE_SOL), (2, 7, ONE_SOL), (3, 8, ONE_SOL)]);
+        let value = find_value(|r, rng| r.winning_square(rng) == 7 && !r.is_split_reward(rng));
+        let sim = simulate(&round, &miners, value, 0, 0);
+        let top_miners = sim.rewards.iter().filter(|r| r.is_top_miner).count();
+        assert_eq!(top_miners, 1);
+        let total_ore: u64 = sim.rewards.iter().map(|r| r.top_miner_ore).sum();
+        assert_eq!(total_ore, ONE_ORE);
+        assert_ne!(sim.round.top_miner, SPLIT_ADDRESS);
+        assert_ne!(sim.round.top_miner, Pubkey::default());
+    }
+
+    #[test]
+    fn test_motherlode_hit() {
+        let value = find_value(|r, rng| r.did_hit_motherlode(rng));
+        let mut probe = Round::zeroed();
+        probe.slot_hash = value;
+        let winning_square = probe.winning_square(probe.rng().unwrap());
+        let (round, miners) = deploy(&[(1, winning_square, ONE_SOL), (2, winning_square, ONE_SOL)]);
+        let sim = simulate(&round, &miners, value, 0, 10 * ONE_ORE);
+        assert_eq!(sim.outcome.motherlode, 10 * ONE_ORE);
+        assert_eq!(sim.rewards[0].motherlode_ore, 5 * ONE_ORE);
+        assert_eq!(sim.rewards[1].motherlode_ore, 5 * ONE_ORE);
+    }
+
+    #[test]
+    fn test_mint_capped_at_max_supply() {
+        let (round, miners) = deploy(&[(1, 0, ONE_SOL)]);
+        let value = find_value(|r, rng| r.winning_square(rng) == 0);
+        let sim = simulate(&round, &miners, value, MAX_SUPPLY - ONE_ORE / 2, 0);
+        assert_eq!(sim.outcome.top_miner_reward, ONE_ORE / 2);
+        assert_eq!(sim.outcome.motherlode_minted, 0);
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use solana_program::keccak::hashv;
 use steel::*;
 
-use crate::state::miner_pda;
+use crate::state::{miner_pda, Round};
 
 use super::OreAccount;
 
@@ -27,8 +28,26 @@ pub struct Automation {
     pub strategy: u64,
 
     /// The mask of squares this automation should deploy to if preferred strategy.
-    /// If strategy is Random, first bit is used to determine how many squares to deploy to.
+    /// If strategy is Random or LeastCrowded, first byte is used to determine how many squares to deploy to.
     pub mask: u64,
+
+    /// The maximum number of rounds to deploy in, or 0 for no limit.
+    pub max_rounds: u64,
+
+    /// The number of rounds this automation has deployed in.
+    pub rounds: u64,
+
+    /// The slot at which this automation expires, or 0 if it never expires.
+    pub expires_at: u64,
+
+    /// The maximum amount of SOL, fees included, the executor can spend in one round, or 0 for no limit.
+    pub max_per_round: u64,
+
+    /// The last round this automation deployed in.
+    pub round_id: u64,
+
+    /// The amount of SOL, fees included, spent in the last round this automation deployed in.
+    pub round_spent: u64,
 }
 
 #[repr(u8)]
@@ -36,6 +55,7 @@ pub struct Automation {
 pub enum AutomationStrategy {
     Random = 0,
     Preferred = 1,
+    LeastCrowded = 2,
 }
 
 impl AutomationStrategy {
@@ -48,6 +68,137 @@ impl Automation {
     pub fn pda(&self) -> (Pubkey, u8) {
         miner_pda(self.authority)
     }
+
+    /// Whether this automation has expired or used up its rounds as of the given slot.
+    pub fn is_finished(&self, slot: u64) -> bool {
+        let expired = self.expires_at != 0 && slot >= self.expires_at;
+        let used_up = self.max_rounds != 0 && self.rounds >= self.max_rounds;
+        expired || used_up
+    }
+
+    /// The amount of SOL, fees included, the executor can still spend in the given round.
+    pub fn round_budget(&self, round_id: u64) -> u64 {
+        if self.max_per_round == 0 {
+            return u64::MAX;
+        }
+        let spent = if self.round_id == round_id {
+            self.round_spent
+        } else {
+            0
+        };
+        self.max_per_round.saturating_sub(spent)
+    }
+
+    /// The squares this automation deploys to in the given round, as of its current deployments.
+    /// Least crowded squares change as the round fills, so they are resolved by the automation's
+    /// one deploy of the round.
+    pub fn squares(&self, round: &Round) -> [bool; 25] {
+        let mut squares = [false; 25];
+        match AutomationStrategy::from_u64(self.strategy) {
+            AutomationStrategy::Preferred => {
+                // Preferred automation strategy. Use the miner authority's provided mask.
+                for i in 0..25 {
+                    squares[i] = (self.mask & (1 << i)) != 0;
+                }
+            }
+            AutomationStrategy::Random => {
+                // Random automation strategy. Generate a random mask based on number of squares user wants to deploy to.
+                let num_squares = (self.mask & 0xFF).min(25);
+                let r = hashv(&[&self.authority.to_bytes(), &round.id.to_le_bytes()]).0;
+                squares = generate_random_mask(num_squares, &r);
+            }
+            AutomationStrategy::LeastCrowded => {
+                // Least crowded automation strategy. Deploy to the squares with the least SOL deployed so far.
+                let num_squares = (self.mask & 0xFF).min(25);
+                squares = least_crowded_mask(num_squares, &round.deployed);
+            }
+        }
+        squares
+    }
+}
+
+fn generate_random_mask(num_squares: u64, r: &[u8]) -> [bool; 25] {
+    let mut new_mask = [false; 25];
+    let mut selected = 0;
+    for i in 0..25 {
+        let rand_byte = r[i];
+        let remaining_needed = num_squares as u64 - selected as u64;
+        let remaining_positions = 25 - i;
+        if remaining_needed > 0
+            && (rand_byte as u64) * (remaining_positions as u64) < (remaining_needed * 256)
+        {
+            new_mask[i] = true;
+            selected += 1;
+        }
+    }
+    new_mask
+}
+
+/// Selects the `num_squares` squares with the least SOL deployed. Ties go to the lower index.
+fn least_crowded_mask(num_squares: u64, deployed: &[u64; 25]) -> [bool; 25] {
+    let mut new_mask = [false; 25];
+    for _ in 0..num_squares {
+        let best = (0..25)
+            .filter(|&i| !new_mask[i])
+            .min_by_key(|&i| deployed[i]);
+        match best {
+            Some(i) => new_mask[i] = true,
+            None => break,
+        }
+    }
+    new_mask
 }
 
 account!(OreAccount, Automation);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let mut automation = Automation::zeroed();
+        automation.round_id = u64::MAX;
+        assert!(!automation.is_finished(100));
+        assert_eq!(automation.round_budget(1), u64::MAX);
+
+        // Expires at slot 100.
+        automation.expires_at = 100;
+        assert!(!automation.is_finished(99));
+        assert!(automation.is_finished(100));
+        automation.expires_at = 0;
+
+        // Finished after 2 rounds.
+        automation.max_rounds = 2;
+        automation.rounds = 1;
+        assert!(!automation.is_finished(0));
+        automation.rounds = 2;
+        assert!(automation.is_finished(0));
+
+        // The budget resets every round.
+        automation.round_id = 5;
+        automation.max_per_round = 1_000;
+        automation.round_spent = 600;
+        assert_eq!(automation.round_budget(5), 400);
+        assert_eq!(automation.round_budget(6), 1_000);
+    }
+
+    #[test]
+    fn test_least_crowded_mask() {
+        let mut deployed = [100; 25];
+        deployed[3] = 0;
+        deployed[7] = 50;
+        deployed[20] = 50;
+        let mask = least_crowded_mask(2, &deployed);
+        assert!(mask[3] && mask[7]);
+        assert_eq!(mask.iter().filter(|&&s| s).count(), 2);
+
+        // Ties beyond the cheapest squares go to the lowest index.
+        let mask = least_crowded_mask(4, &deployed);
+        assert!(mask[0] && mask[3] && mask[7] && mask[20]);
+        assert_eq!(mask.iter().filter(|&&s| s).count(), 4);
+
+        assert_eq!(least_crowded_mask(25, &deployed), [true; 25]);
+        assert_eq!(least_crowded_mask(0, &deployed), [false; 25]);
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use steel::*;
 
-use crate::state::{miner_pda, Treasury};
+use crate::{
+    error::OreError,
+    state::{miner_pda, Treasury},
+};
 
 use super::OreAccount;
 
@@ -56,8 +59,12 @@ impl Miner {
         miner_pda(self.authority)
     }
 
-    pub fn claim_ore(&mut self, clock: &Clock, treasury: &mut Treasury) -> u64 {
-        self.update_rewards(treasury);
+    pub fn claim_ore(
+        &mut self,
+        clock: &Clock,
+        treasury: &mut Treasury,
+    ) -> Result<u64, ProgramError> {
+        self.update_rewards(treasury)?;
         let refined_ore = self.refined_ore;
         let rewards_ore = self.rewards_ore;
         let mut amount = refined_ore + rewards_ore;
@@ -76,7 +83,7 @@ impl Miner {
             self.lifetime_rewards_ore -= fee;
         }
 
-        amount
+        Ok(amount)
     }
 
     pub fn claim_sol(&mut self, clock: &Clock) -> u64 {
@@ -86,12 +93,12 @@ impl Miner {
         amount
     }
 
-    pub fn update_rewards(&mut self, treasury: &Treasury) {
+    pub fn update_rewards(&mut self, treasury: &Treasury) -> Result<(), ProgramError> {
         // Accumulate rewards, weighted by stake balance.
         if treasury.miner_rewards_factor > self.rewards_factor {
             let accumulated_rewards = treasury.miner_rewards_factor - self.rewards_factor;
             if accumulated_rewards < Numeric::ZERO {
-                panic!("Accumulated rewards is negative");
+                return Err(OreError::InvariantViolation.into());
             }
             let personal_rewards = accumulated_rewards * Numeric::from_u64(self.rewards_ore);
             self.refined_ore += personal_rewards.to_u64();
@@ -100,6 +107,7 @@ impl Miner {
 
         // Update this miner account's last seen rewards factor.
         self.rewards_factor = treasury.miner_rewards_factor;
+        Ok(())
     }
 }
 
@@ -109,9 +109,10 @@ mod tests {
 
     #[test]
     fn test_rent() {
+        // Round accounts already on chain have this layout.
         let size_of_round = 8 + std::mem::size_of::<Round>();
+        assert_eq!(size_of_round, 560);
         let required_rent = Rent::default().minimum_balance(size_of_round);
-        println!("required_rent: {}", required_rent);
-        assert!(false);
+        assert_eq!(required_rent, 4_788_480);
     }
 }
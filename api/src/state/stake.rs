@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use steel::*;
 
-use crate::state::{stake_pda, Treasury};
+use crate::{
+    error::OreError,
+    state::{stake_pda, Treasury},
+};
 
 use super::OreAccount;
 
@@ -41,12 +44,17 @@ impl Stake {
         stake_pda(self.authority)
     }
 
-    pub fn claim(&mut self, amount: u64, clock: &Clock, treasury: &Treasury) -> u64 {
-        self.update_rewards(treasury);
+    pub fn claim(
+        &mut self,
+        amount: u64,
+        clock: &Clock,
+        treasury: &Treasury,
+    ) -> Result<u64, ProgramError> {
+        self.update_rewards(treasury)?;
         let amount = self.rewards.min(amount);
         self.rewards -= amount;
         self.last_claim_at = clock.unix_timestamp;
-        amount
+        Ok(amount)
     }
 
     pub fn deposit(
@@ -55,30 +63,35 @@ impl Stake {
         clock: &Clock,
         treasury: &mut Treasury,
         sender: &TokenAccount,
-    ) -> u64 {
-        self.update_rewards(treasury);
+    ) -> Result<u64, ProgramError> {
+        self.update_rewards(treasury)?;
         let amount = sender.amount().min(amount);
         self.balance += amount;
         self.last_deposit_at = clock.unix_timestamp;
         treasury.total_staked += amount;
-        amount
+        Ok(amount)
     }
 
-    pub fn withdraw(&mut self, amount: u64, clock: &Clock, treasury: &mut Treasury) -> u64 {
-        self.update_rewards(treasury);
+    pub fn withdraw(
+        &mut self,
+        amount: u64,
+        clock: &Clock,
+        treasury: &mut Treasury,
+    ) -> Result<u64, ProgramError> {
+        self.update_rewards(treasury)?;
         let amount = self.balance.min(amount);
         self.balance -= amount;
         self.last_withdraw_at = clock.unix_timestamp;
         treasury.total_staked -= amount;
-        amount
+        Ok(amount)
     }
 
-    pub fn update_rewards(&mut self, treasury: &Treasury) {
+    pub fn update_rewards(&mut self, treasury: &Treasury) -> Result<(), ProgramError> {
         // Accumulate rewards, weighted by stake balance.
         if treasury.stake_rewards_factor > self.rewards_factor {
             let accumulated_rewards = treasury.stake_rewards_factor - self.rewards_factor;
             if accumulated_rewards < Numeric::ZERO {
-                panic!("Accumulated rewards is negative");
+                return Err(OreError::InvariantViolation.into());
             }
             let personal_rewards = accumulated_rewards * Numeric::from_u64(self.balance);
             self.rewards += personal_rewards.to_u64();
@@ -87,6 +100,7 @@ impl Stake {
 
         // Update this stake account's last seen rewards factor.
         self.rewards_factor = treasury.stake_rewards_factor;
+        Ok(())
     }
 }
 
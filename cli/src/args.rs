@@ -0,0 +1,515 @@
+use clap::{Args, Parser, Subcommand};
+use solana_sdk::{keccak, pubkey::Pubkey};
+
+use crate::{
+    config::Settings,
+    crank::{SeedProvider, DEFAULT_SEED_PROVIDER},
+    fees::PriorityFee,
+    indexer::IndexerMode,
+    solver::Objective,
+    strategies::MiningStrategy,
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "ore-cli",
+    about = "Command line interface for ORE mining",
+    version
+)]
+pub struct Cli {
+    /// Path to the config file [default: ~/.config/ore/config.toml]
+    #[arg(long, global = true, env = "ORE_CONFIG")]
+    pub config: Option<String>,
+
+    /// Config profile to use, usually one per wallet
+    #[arg(long, short, global = true, env = "ORE_PROFILE")]
+    pub profile: Option<String>,
+
+    /// RPC URL, overrides the profile
+    #[arg(long, global = true, env = "RPC")]
+    pub rpc: Option<String>,
+
+    /// Path to the payer keypair, overrides the profile
+    #[arg(long, global = true, env = "KEYPAIR")]
+    pub keypair: Option<String>,
+
+    /// Jupiter swap API used by bury, overrides the profile
+    #[arg(long, global = true, env = "API_BASE_URL")]
+    pub jupiter_api_url: Option<String>,
+
+    /// Priority fee policy: `static:<price>`, `percentile:<p>` or `dynamic:<p>:<max>`, with prices
+    /// in micro-lamports per compute unit. Overrides the config file for every transaction
+    #[arg(long, global = true, env = "PRIORITY_FEE")]
+    pub priority_fee: Option<PriorityFee>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+impl Cli {
+    /// Settings passed as flags, the highest precedence config layer. The priority fee flag is
+    /// applied separately, since it must also override per-command fees.
+    pub fn settings(&self) -> Settings {
+        Settings {
+            rpc: self.rpc.clone(),
+            keypair: self.keypair.clone(),
+            jupiter_api_url: self.jupiter_api_url.clone(),
+            ..Settings::default()
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show the board
+    Board,
+
+    /// Show the clock sysvar
+    Clock,
+
+    /// Show the program config
+    Config,
+
+    /// Show the treasury and motherlode
+    Treasury,
+
+    /// List automation accounts
+    Automations {
+        /// Only automations run by this executor [default: Regolith]
+        #[arg(long)]
+        executor: Option<Pubkey>,
+    },
+
+    /// Show a round
+    Round {
+        /// Round id
+        id: u64,
+    },
+
+    /// Show a miner account
+    Miner {
+        /// Miner authority [default: payer]
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+
+    /// Show a stake account
+    Stake {
+        /// Stake authority [default: payer]
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+
+    /// List miners that deployed in a round
+    ParticipatingMiners {
+        /// Round id
+        id: u64,
+    },
+
+    /// Claim SOL and ORE rewards
+    Claim,
+
+    /// Checkpoint a miner's last round
+    Checkpoint {
+        /// Miner authority [default: payer]
+        #[arg(long)]
+        authority: Option<Pubkey>,
+    },
+
+    /// Checkpoint every miner whose round is in the bot fee window
+    CheckpointAll,
+
+    /// Checkpoint a stuck miner, claim its rewards and report forfeited rounds
+    Recover,
+
+    /// Close every expired round
+    CloseAll,
+
+    /// Deploy to a single square in the current round
+    Deploy {
+        /// Lamports to deploy
+        #[arg(long)]
+        amount: u64,
+
+        /// Square index, 0-24
+        #[arg(long, value_parser = clap::value_parser!(u64).range(0..25))]
+        square: u64,
+    },
+
+    /// Deploy to every square in the current round
+    DeployAll {
+        /// Lamports to deploy per square
+        #[arg(long)]
+        amount: u64,
+    },
+
+    /// Late snipe the 2 least crowded squares for one round
+    DeployOptimal {
+        /// Total lamports to deploy, split across both squares
+        #[arg(long, default_value_t = 20_000_000)]
+        amount: u64,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+    },
+
+    /// Late snipe the 2 least crowded squares every round
+    DeployContinuous {
+        /// Lamports to deploy per square
+        #[arg(long, default_value_t = 15_000_000)]
+        amount: u64,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+    },
+
+    /// Deploy to the 2 least crowded squares for one round and report the result
+    DeploySingle {
+        /// Lamports to deploy per square
+        #[arg(long, default_value_t = 5_000_000)]
+        amount: u64,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+    },
+
+    /// Deploy to the N least crowded squares every round, 5-10s before the end
+    DeployOptimalEv {
+        /// Number of squares to deploy to
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..=25))]
+        num_squares: u64,
+
+        /// Total lamports to deploy per round, split across squares
+        #[arg(long, default_value_t = 1_500_000)]
+        bet_amount: u64,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+
+        #[command(flatten)]
+        predict: PredictArgs,
+    },
+
+    /// Run a strategy from the strategies module every round
+    DeployStrategy {
+        /// Strategy spec, e.g. `contrarian:5:0.7`, `late_snipe:3:5`, `kelly:0.1`
+        #[arg(long)]
+        strategy: MiningStrategy,
+
+        /// Lamports to deploy per square
+        #[arg(long)]
+        amount: u64,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+
+        #[command(flatten)]
+        predict: PredictArgs,
+    },
+
+    /// Solve for the allocation of a budget across squares in the current round
+    Solve {
+        /// Most lamports to deploy
+        #[arg(long)]
+        budget: u64,
+
+        /// Value of one ORE in lamports
+        #[arg(long, default_value_t = 0)]
+        ore_price: u64,
+
+        /// What to maximize
+        #[arg(long, value_enum, default_value_t = Objective::Ev)]
+        objective: Objective,
+
+        /// Lamports held, for the log objective [default: payer balance]
+        #[arg(long)]
+        bankroll: Option<u64>,
+
+        /// Least lamports to deploy on a square
+        #[arg(long, default_value_t = 10_000)]
+        min_per_square: u64,
+
+        /// Lamports a deploy transaction costs
+        #[arg(long, default_value_t = 5_000)]
+        tx_fee: u64,
+    },
+
+    /// Create, fund, modify or close the payer's automation
+    Automate {
+        #[command(subcommand)]
+        command: AutomateCommand,
+    },
+
+    /// Deploy every round for automations that name the payer as executor, earning their fees
+    Executor {
+        /// Automations deployed per transaction
+        #[arg(long, default_value_t = 3)]
+        batch_size: usize,
+
+        /// Seconds before the round ends to deploy least crowded automations
+        #[arg(long, default_value_t = 5)]
+        late_seconds: u64,
+    },
+
+    /// Collect checkpoint fees and close expired rounds
+    Keeper {
+        /// File the keeper's progress is saved to
+        #[arg(long, default_value = "keeper_state.json")]
+        state_file: String,
+
+        /// Least profit in lamports a transaction must make to be sent
+        #[arg(long, default_value_t = 0)]
+        min_profit: i64,
+
+        /// Also close expired rounds whose rent goes to someone else
+        #[arg(long)]
+        close_others: bool,
+
+        /// Seconds between passes
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+
+    /// Mine with many wallets from one process, spread across different squares
+    Fleet {
+        /// Fleet file with a strategy and budget per wallet
+        #[arg(long)]
+        file: Option<String>,
+
+        /// Keypair files, or directories of them, comma separated
+        #[arg(long, value_delimiter = ',')]
+        keypairs: Vec<String>,
+
+        /// Lamports to deploy per square, for wallets from --keypairs
+        #[arg(long, default_value_t = 5_000_000)]
+        amount: u64,
+
+        /// Strategy spec for wallets from --keypairs [default: least crowded]
+        #[arg(long)]
+        strategy: Option<MiningStrategy>,
+
+        /// Number of least crowded squares per wallet from --keypairs
+        #[arg(long, default_value_t = 2)]
+        squares: usize,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+    },
+
+    /// Index program events to a JSONL file
+    Indexer {
+        /// Follow logs live, or backfill past transactions
+        #[arg(long, value_enum, default_value_t = IndexerMode::Live)]
+        mode: IndexerMode,
+
+        /// Output file
+        #[arg(long, default_value = "ore_events.jsonl")]
+        file: String,
+
+        /// Number of past transactions to fetch in backfill mode
+        #[arg(long, default_value_t = 1000)]
+        backfill_limit: usize,
+    },
+
+    /// Report how well the crowd inflow model predicts final pools on held-out rounds
+    EvalInflow {
+        /// Indexer file to train and evaluate on
+        #[arg(long, default_value = "ore_events.jsonl")]
+        index_file: String,
+
+        /// Slots before the end of the round to predict from
+        #[arg(long, default_value_t = 25)]
+        slots_remaining: u64,
+
+        /// Past rounds each prediction is fit on
+        #[arg(long, default_value_t = 50)]
+        history_rounds: usize,
+
+        /// Number of most recent rounds to predict
+        #[arg(long, default_value_t = 20)]
+        holdout: usize,
+    },
+
+    /// Reveal the entropy var and reset the board at the end of every round
+    Reset {
+        /// Seed providers tried in order: entropy API urls with {var}, or mock:<seed>
+        #[arg(
+            long,
+            env = "SEED_PROVIDER",
+            value_delimiter = ',',
+            default_value = DEFAULT_SEED_PROVIDER
+        )]
+        seed_provider: Vec<SeedProvider>,
+
+        /// Reset one round and exit
+        #[arg(long)]
+        once: bool,
+    },
+
+    /// Swap vaulted SOL to ORE and bury it
+    Bury,
+
+    /// Set the admin to the payer
+    SetAdmin,
+
+    /// Set the fee collector
+    SetFeeCollector { fee_collector: Pubkey },
+
+    /// Set the swap program used by bury
+    SetSwapProgram { swap_program: Pubkey },
+
+    /// Set the entropy var buffer
+    SetBuffer { buffer: u64 },
+
+    /// Open a new entropy var
+    NewVar {
+        /// Entropy provider
+        #[arg(long)]
+        provider: Pubkey,
+
+        /// Provider commit hash
+        #[arg(long)]
+        commit: keccak::Hash,
+
+        /// Number of samples
+        #[arg(long)]
+        samples: u64,
+    },
+
+    /// Create a hardcoded associated token account
+    Ata,
+
+    /// Print program addresses
+    Keys,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AutomateCommand {
+    /// Create an automation, or replace the payer's existing one
+    Create {
+        /// Executor that deploys for you
+        #[arg(long)]
+        executor: Pubkey,
+
+        /// Lamports to deploy per square
+        #[arg(long)]
+        amount: u64,
+
+        /// Lamports to deposit
+        #[arg(long)]
+        deposit: u64,
+
+        /// Lamports paid to the executor per round
+        #[arg(long)]
+        fee: u64,
+
+        #[command(flatten)]
+        squares: SquareArgs,
+
+        #[command(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Deposit more lamports
+    Topup {
+        #[arg(long)]
+        deposit: u64,
+    },
+
+    /// Change the squares, keeping the strategy
+    SetMask {
+        #[command(flatten)]
+        squares: SquareArgs,
+    },
+
+    /// Switch between random, preferred and least crowded squares
+    SetStrategy {
+        #[command(flatten)]
+        squares: SquareArgs,
+    },
+
+    /// Change the round, expiry and per-round spending limits. Limits left out are kept
+    SetLimits {
+        #[command(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Close the automation and return its balance
+    Close,
+}
+
+/// Spending limits for an automation. The automation closes once it expires or runs its last round.
+#[derive(Args, Debug)]
+pub struct LimitArgs {
+    /// Most rounds to deploy in, 0 for no limit
+    #[arg(long)]
+    pub max_rounds: Option<u64>,
+
+    /// Slot at which the automation expires, 0 for never
+    #[arg(long)]
+    pub expires_at: Option<u64>,
+
+    /// Most lamports, fees included, the executor can spend in one round, 0 for no limit
+    #[arg(long)]
+    pub max_per_round: Option<u64>,
+}
+
+/// Squares for an automation. Lists and grids pick preferred squares, counts pick random or least
+/// crowded ones.
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct SquareArgs {
+    /// Preferred squares, 0-24, comma separated
+    #[arg(long, value_delimiter = ',', value_parser = clap::value_parser!(u64).range(0..25))]
+    pub squares: Option<Vec<u64>>,
+
+    /// Preferred squares as a 5x5 grid, rows separated by `/`, e.g. `10000/01000/00100/00010/00001`
+    #[arg(long)]
+    pub grid: Option<String>,
+
+    /// Number of random squares, redrawn every round
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=25))]
+    pub random: Option<u64>,
+
+    /// Number of squares with the least SOL deployed when the executor deploys
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..=25))]
+    pub least_crowded: Option<u64>,
+}
+
+/// Slippage protection for deploys, relative to the round the squares were chosen from. Every
+/// limit is off by default.
+#[derive(Args, Clone, Copy, Debug, Default)]
+pub struct GuardArgs {
+    /// Skip squares whose pool grew by more than this many basis points before the deploy landed
+    #[arg(long)]
+    pub max_slippage: Option<u64>,
+
+    /// Skip squares where the deploy would get less than this share of the pool, in basis points
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u64).range(0..=10_000))]
+    pub min_share: u64,
+
+    /// Fail the deploy if it lands more than this many slots after the round was fetched
+    #[arg(long)]
+    pub max_delay_slots: Option<u64>,
+
+    /// Fail the whole deploy instead of skipping squares
+    #[arg(long)]
+    pub strict_guard: bool,
+}
+
+/// Choosing squares on the pools predicted for the end of the round, rather than the current
+/// ones. Off by default.
+#[derive(Args, Clone, Debug, Default)]
+pub struct PredictArgs {
+    /// Let the strategy see the final pools predicted by the crowd inflow model
+    #[arg(long)]
+    pub predict_crowd: bool,
+
+    /// Indexer file the model is fit on
+    #[arg(long, default_value = "ore_events.jsonl")]
+    pub index_file: String,
+
+    /// Past rounds the model is fit on
+    #[arg(long, default_value_t = 50)]
+    pub history_rounds: usize,
+}
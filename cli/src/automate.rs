@@ -0,0 +1,475 @@
+/// Commands to manage the payer's Automation account
+///
+/// The automate instruction always writes every field, so each command reads the current
+/// automation and resends it with one thing changed.
+
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    native_token::lamports_to_sol, pubkey::Pubkey, signature::Signer, signer::keypair::Keypair,
+};
+
+use ore_api::prelude::*;
+
+use crate::args::{AutomateCommand, LimitArgs, SquareArgs};
+use crate::fees::PriorityFee;
+use crate::{get_automation, get_miner, submit_transaction};
+
+/// An automation's settings, as sent to the automate instruction.
+#[derive(Clone, Copy, Debug)]
+struct Settings {
+    executor: Pubkey,
+    amount: u64,
+    fee: u64,
+    mask: u64,
+    strategy: AutomationStrategy,
+    max_rounds: u64,
+    expires_at: u64,
+    max_per_round: u64,
+}
+
+impl Settings {
+    fn from_automation(automation: &Automation) -> Result<Settings> {
+        Ok(Settings {
+            executor: automation.executor,
+            amount: automation.amount,
+            fee: automation.fee,
+            mask: automation.mask,
+            strategy: AutomationStrategy::try_from(automation.strategy as u8)
+                .map_err(|_| anyhow!("Unknown automation strategy {}", automation.strategy))?,
+            max_rounds: automation.max_rounds,
+            expires_at: automation.expires_at,
+            max_per_round: automation.max_per_round,
+        })
+    }
+
+    /// Overwrites the limits that were passed, keeping the others.
+    fn set_limits(&mut self, limits: &LimitArgs) {
+        self.max_rounds = limits.max_rounds.unwrap_or(self.max_rounds);
+        self.expires_at = limits.expires_at.unwrap_or(self.expires_at);
+        self.max_per_round = limits.max_per_round.unwrap_or(self.max_per_round);
+    }
+}
+
+pub async fn automate(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    fee: PriorityFee,
+    command: AutomateCommand,
+) -> Result<()> {
+    let existing = get_automation(rpc, payer.pubkey()).await.ok();
+    let current = || {
+        existing
+            .as_ref()
+            .ok_or_else(|| anyhow!("No automation for {}, create one first", payer.pubkey()))
+    };
+
+    let (settings, deposit) = match command {
+        AutomateCommand::Create {
+            executor,
+            amount,
+            deposit,
+            fee,
+            squares,
+            limits,
+        } => {
+            if existing.is_some() {
+                println!("Replacing the existing automation. Its balance is kept.");
+            }
+            let (strategy, mask) = selection(&squares)?;
+            let mut settings = Settings {
+                executor,
+                amount,
+                fee,
+                mask,
+                strategy,
+                max_rounds: 0,
+                expires_at: 0,
+                max_per_round: 0,
+            };
+            settings.set_limits(&limits);
+            (settings, deposit)
+        }
+        AutomateCommand::Topup { deposit } => (Settings::from_automation(current()?)?, deposit),
+        AutomateCommand::SetMask { squares } => {
+            let mut settings = Settings::from_automation(current()?)?;
+            let (strategy, mask) = selection(&squares)?;
+            if strategy != settings.strategy {
+                return Err(anyhow!(
+                    "The automation uses the {:?} strategy, use set-strategy to change it",
+                    settings.strategy
+                ));
+            }
+            settings.mask = mask;
+            (settings, 0)
+        }
+        AutomateCommand::SetStrategy { squares } => {
+            let mut settings = Settings::from_automation(current()?)?;
+            (settings.strategy, settings.mask) = selection(&squares)?;
+            (settings, 0)
+        }
+        AutomateCommand::SetLimits { limits } => {
+            let mut settings = Settings::from_automation(current()?)?;
+            settings.set_limits(&limits);
+            (settings, 0)
+        }
+        AutomateCommand::Close => {
+            let automation = current()?;
+            println!(
+                "Closing automation, returning {} SOL",
+                lamports_to_sol(automation.balance)
+            );
+            let ix =
+                ore_api::sdk::automate(payer.pubkey(), 0, 0, Pubkey::default(), 0, 0, 0, 0, 0, 0);
+            submit_transaction(rpc, payer, &[ix], fee).await?;
+            return Ok(());
+        }
+    };
+
+    // Check the automation can deploy at least once.
+    let balance = existing.map_or(0, |a| a.balance) + deposit;
+    let rounds = existing.map_or(0, |a| a.rounds);
+    let slot = rpc.get_slot().await?;
+    validate(&settings, balance, rounds, slot)?;
+    preview(&settings, balance, rounds, slot);
+
+    // The first automate also funds the miner's checkpoint fee.
+    if get_miner(rpc, payer.pubkey())
+        .await
+        .map_or(true, |m| m.checkpoint_fee == 0)
+    {
+        println!("  checkpoint fee: {} SOL", lamports_to_sol(CHECKPOINT_FEE));
+    }
+
+    let ix = ore_api::sdk::automate(
+        payer.pubkey(),
+        settings.amount,
+        deposit,
+        settings.executor,
+        settings.fee,
+        settings.mask,
+        settings.strategy as u8,
+        settings.max_rounds,
+        settings.expires_at,
+        settings.max_per_round,
+    );
+    submit_transaction(rpc, payer, &[ix], fee).await?;
+    Ok(())
+}
+
+/// Rejects settings the program would accept but that could never deploy. `rounds` is the number
+/// of rounds the automation has already deployed in.
+fn validate(settings: &Settings, balance: u64, rounds: u64, slot: u64) -> Result<()> {
+    if settings.executor == Pubkey::default() {
+        return Err(anyhow!(
+            "Executor cannot be the default pubkey, use close instead"
+        ));
+    }
+    if settings.amount == 0 {
+        return Err(anyhow!("Amount must be greater than zero"));
+    }
+    if num_squares(settings.strategy, settings.mask) == 0 {
+        return Err(anyhow!("No squares selected"));
+    }
+
+    // Executors pay for their own checkpoint and deploy transactions out of the fee.
+    if settings.fee < CHECKPOINT_FEE {
+        return Err(anyhow!(
+            "Fee must be at least the checkpoint fee ({} lamports)",
+            CHECKPOINT_FEE
+        ));
+    }
+
+    // The program closes an automation that cannot fund one square plus the fee.
+    if balance < settings.amount + settings.fee {
+        return Err(anyhow!(
+            "Balance of {} lamports cannot fund one square plus the fee ({} lamports)",
+            balance,
+            settings.amount + settings.fee
+        ));
+    }
+
+    // The program closes an automation once it expires or runs its last round.
+    if settings.max_per_round != 0 && settings.max_per_round < settings.amount + settings.fee {
+        return Err(anyhow!(
+            "Per-round limit cannot fund one square plus the fee ({} lamports)",
+            settings.amount + settings.fee
+        ));
+    }
+    if settings.max_rounds != 0 && rounds >= settings.max_rounds {
+        return Err(anyhow!(
+            "The automation already deployed in {} rounds, raise --max-rounds",
+            rounds
+        ));
+    }
+    if settings.expires_at != 0 && settings.expires_at <= slot {
+        return Err(anyhow!(
+            "Expiry slot {} has already passed, the current slot is {}",
+            settings.expires_at,
+            slot
+        ));
+    }
+    Ok(())
+}
+
+fn preview(settings: &Settings, balance: u64, rounds: u64, slot: u64) {
+    let squares = squares_per_round(settings);
+    let per_round = squares * settings.amount + settings.fee;
+    println!("Automation");
+    println!("  executor: {}", settings.executor);
+    println!("  strategy: {:?}", settings.strategy);
+    if settings.strategy == AutomationStrategy::Preferred {
+        print_grid(settings.mask);
+    }
+    println!(
+        "  per round: {} SOL on {} squares + {} SOL fee = {} SOL",
+        lamports_to_sol(settings.amount),
+        squares,
+        lamports_to_sol(settings.fee),
+        lamports_to_sol(per_round)
+    );
+    let mut funded = rounds_funded(balance, settings.amount, settings.fee, squares);
+    if settings.max_rounds != 0 {
+        println!("  max rounds: {} ({} used)", settings.max_rounds, rounds);
+        funded = funded.min(settings.max_rounds - rounds);
+    }
+    if settings.expires_at != 0 {
+        let hours = (settings.expires_at - slot) as f64 * SLOT_SECONDS / 3600.0;
+        println!(
+            "  expires: slot {} (~{:.1} hours)",
+            settings.expires_at, hours
+        );
+    }
+    println!(
+        "  balance: {} SOL, funds {} rounds",
+        lamports_to_sol(balance),
+        funded
+    );
+}
+
+fn print_grid(mask: u64) {
+    for row in 0..5 {
+        let cells: String = (0..5)
+            .map(|col| {
+                if mask & (1 << (row * 5 + col)) != 0 {
+                    '■'
+                } else {
+                    '·'
+                }
+            })
+            .collect();
+        println!("    {}", cells);
+    }
+}
+
+/// Returns the strategy and mask for the selected squares.
+fn selection(args: &SquareArgs) -> Result<(AutomationStrategy, u64)> {
+    // Random and least crowded automations keep the square count in the low byte.
+    if let Some(count) = args.random {
+        return Ok((AutomationStrategy::Random, count));
+    }
+    if let Some(count) = args.least_crowded {
+        return Ok((AutomationStrategy::LeastCrowded, count));
+    }
+    let mask = match (&args.squares, &args.grid) {
+        (Some(squares), _) => squares.iter().fold(0, |mask, &i| mask | (1 << i)),
+        (_, Some(grid)) => parse_grid(grid).map_err(anyhow::Error::msg)?,
+        _ => return Err(anyhow!("No squares selected")),
+    };
+    Ok((AutomationStrategy::Preferred, mask))
+}
+
+/// Parses 5 rows of 5 cells, separated by `/` or whitespace. `1`, `x` and `#` select a square,
+/// `0`, `.` and `-` skip it.
+fn parse_grid(grid: &str) -> Result<u64, String> {
+    let rows: Vec<&str> = grid
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .filter(|r| !r.is_empty())
+        .collect();
+    if rows.len() != 5 {
+        return Err(format!("Grid must have 5 rows, got {}", rows.len()));
+    }
+    let mut mask = 0;
+    for (row, cells) in rows.iter().enumerate() {
+        let cells: Vec<char> = cells.chars().collect();
+        if cells.len() != 5 {
+            return Err(format!("Grid row {} must have 5 cells", row + 1));
+        }
+        for (col, cell) in cells.into_iter().enumerate() {
+            match cell {
+                '1' | 'x' | 'X' | '#' => mask |= 1 << (row * 5 + col),
+                '0' | '.' | '-' => {}
+                _ => return Err(format!("Invalid grid cell '{}'", cell)),
+            }
+        }
+    }
+    Ok(mask)
+}
+
+/// Number of squares deployed each round.
+fn num_squares(strategy: AutomationStrategy, mask: u64) -> u64 {
+    match strategy {
+        AutomationStrategy::Preferred => (mask & ((1 << 25) - 1)).count_ones() as u64,
+        AutomationStrategy::Random | AutomationStrategy::LeastCrowded => (mask & 0xFF).min(25),
+    }
+}
+
+/// Squares deployed each round, after the per-round limit.
+fn squares_per_round(settings: &Settings) -> u64 {
+    let squares = num_squares(settings.strategy, settings.mask);
+    if settings.max_per_round == 0 || settings.amount == 0 {
+        return squares;
+    }
+    let affordable = settings.max_per_round.saturating_sub(settings.fee) / settings.amount;
+    squares.min(affordable)
+}
+
+/// Rounds a balance funds. Full rounds deploy every square; after that the program deploys as
+/// many squares as fit one last time, then closes the automation.
+fn rounds_funded(balance: u64, amount: u64, fee: u64, squares: u64) -> u64 {
+    let per_round = squares * amount + fee;
+    if per_round == 0 {
+        return 0;
+    }
+    let full = balance / per_round;
+    let partial = balance % per_round >= amount + fee;
+    full + partial as u64
+}
+
+/// Approximate slot time, for showing expiry as a duration.
+const SLOT_SECONDS: f64 = 0.4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grid() {
+        assert_eq!(parse_grid("10000/00000/00000/00000/00001"), Ok(1 | 1 << 24));
+        assert_eq!(
+            parse_grid("x.... .x... ..x.. ...x. ....x"),
+            Ok(1 | 1 << 6 | 1 << 12 | 1 << 18 | 1 << 24)
+        );
+        assert!(parse_grid("10000/00000/00000/00000").is_err());
+        assert!(parse_grid("1000/00000/00000/00000/00000").is_err());
+        assert!(parse_grid("1000a/00000/00000/00000/00000").is_err());
+    }
+
+    #[test]
+    fn test_selection() {
+        let args = SquareArgs {
+            squares: Some(vec![0, 3, 24]),
+            grid: None,
+            random: None,
+            least_crowded: None,
+        };
+        assert_eq!(
+            selection(&args).unwrap(),
+            (AutomationStrategy::Preferred, 1 | 1 << 3 | 1 << 24)
+        );
+        let args = SquareArgs {
+            squares: None,
+            grid: None,
+            random: Some(5),
+            least_crowded: None,
+        };
+        assert_eq!(selection(&args).unwrap(), (AutomationStrategy::Random, 5));
+        let args = SquareArgs {
+            squares: None,
+            grid: None,
+            random: None,
+            least_crowded: Some(3),
+        };
+        assert_eq!(
+            selection(&args).unwrap(),
+            (AutomationStrategy::LeastCrowded, 3)
+        );
+        assert_eq!(num_squares(AutomationStrategy::Random, 5), 5);
+        assert_eq!(num_squares(AutomationStrategy::Preferred, 0b1011), 3);
+    }
+
+    #[test]
+    fn test_rounds_funded() {
+        // 3 squares of 10 plus a fee of 5 costs 35 per round.
+        assert_eq!(rounds_funded(70, 10, 5, 3), 2);
+
+        // 15 left over still funds one square plus the fee.
+        assert_eq!(rounds_funded(85, 10, 5, 3), 3);
+        assert_eq!(rounds_funded(84, 10, 5, 3), 2);
+        assert_eq!(rounds_funded(14, 10, 5, 3), 0);
+    }
+
+    #[test]
+    fn test_validate() {
+        let settings = Settings {
+            executor: Pubkey::new_unique(),
+            amount: 10_000,
+            fee: CHECKPOINT_FEE,
+            mask: 3,
+            strategy: AutomationStrategy::Random,
+            max_rounds: 0,
+            expires_at: 0,
+            max_per_round: 0,
+        };
+        assert!(validate(&settings, 1_000_000, 0, 100).is_ok());
+        assert!(validate(&settings, 10_000, 0, 100).is_err());
+        assert!(validate(
+            &Settings {
+                fee: CHECKPOINT_FEE - 1,
+                ..settings
+            },
+            1_000_000,
+            0,
+            100
+        )
+        .is_err());
+        assert!(validate(
+            &Settings {
+                mask: 0,
+                ..settings
+            },
+            1_000_000,
+            0,
+            100
+        )
+        .is_err());
+
+        // Limits that leave nothing to deploy.
+        let limited = |max_rounds, expires_at, max_per_round| Settings {
+            max_rounds,
+            expires_at,
+            max_per_round,
+            ..settings
+        };
+        assert!(validate(&limited(5, 200, 20_000), 1_000_000, 4, 100).is_ok());
+        assert!(validate(&limited(5, 0, 0), 1_000_000, 5, 100).is_err());
+        assert!(validate(&limited(0, 100, 0), 1_000_000, 0, 100).is_err());
+        assert!(validate(&limited(0, 0, 19_999), 1_000_000, 0, 100).is_err());
+
+        // 2 of the 3 squares fit under the per-round limit.
+        assert_eq!(squares_per_round(&limited(0, 0, 30_000)), 2);
+        assert_eq!(squares_per_round(&settings), 3);
+    }
+
+    #[test]
+    fn test_set_limits() {
+        let mut settings = Settings {
+            executor: Pubkey::new_unique(),
+            amount: 10_000,
+            fee: CHECKPOINT_FEE,
+            mask: 3,
+            strategy: AutomationStrategy::Random,
+            max_rounds: 5,
+            expires_at: 200,
+            max_per_round: 20_000,
+        };
+        settings.set_limits(&LimitArgs {
+            max_rounds: None,
+            expires_at: Some(0),
+            max_per_round: None,
+        });
+        assert_eq!(settings.max_rounds, 5);
+        assert_eq!(settings.expires_at, 0);
+        assert_eq!(settings.max_per_round, 20_000);
+    }
+}
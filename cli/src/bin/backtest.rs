@@ -0,0 +1,342 @@
+/// BACKTEST: Replay recorded rounds against mining strategies
+///
+/// Loads the JSONL snapshots written by `research_websocket` (or `ResearchSession`) and settles
+/// each round with `ore_api::sim`, as if our deployment was the last one in before the round ended.
+///
+/// Run with: STRATEGY=contrarian:5:0.7 BET_AMOUNT=20000000 cargo run --bin backtest
+/// Or compare every strategy: STRATEGY=all cargo run --bin backtest
+///
+/// Random strategies are seeded from SEED (default 0) and the round id, so runs are reproducible.
+
+#[path = "../strategies.rs"]
+#[allow(dead_code)]
+mod strategies;
+
+use ore_api::{prelude::*, sim};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{keccak::hashv, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write as IoWrite};
+use steel::Zeroable;
+use strategies::{MiningStrategy, StrategyMetrics};
+
+/// A recorded round, as written by either research collector.
+#[derive(Debug, Clone, Deserialize)]
+struct RecordedRound {
+    round_id: u64,
+    square_pools: [u64; 25],
+    #[serde(default)]
+    motherlode: u64,
+    /// Written by `research_websocket`.
+    #[serde(default)]
+    winner: Option<usize>,
+    /// Written by `ResearchSession`.
+    #[serde(default)]
+    winning_squares: Option<Vec<usize>>,
+}
+
+impl RecordedRound {
+    fn winning_square(&self) -> Option<usize> {
+        self.winner
+            .or_else(|| self.winning_squares.as_ref().and_then(|w| w.first().copied()))
+            .filter(|&w| w < 25)
+    }
+}
+
+/// The result of replaying one round.
+#[derive(Debug, Clone, Serialize)]
+struct BacktestRound {
+    strategy: String,
+    round_id: u64,
+    squares: Vec<usize>,
+    deployed: u64,
+    winning_square: usize,
+    won: bool,
+    sol_returned: u64,
+    sol_pnl: i64,
+    ore_won: u64,
+    cumulative_sol_pnl: i64,
+    cumulative_ore: u64,
+    drawdown_sol: f64,
+}
+
+/// Aggregate results of one strategy over all recorded rounds.
+struct BacktestSummary {
+    strategy: String,
+    metrics: StrategyMetrics,
+    sol_pnl: i64,
+    ore_won: u64,
+    max_drawdown_sol: f64,
+    roi: f64,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::env::var("RESEARCH_FILE").unwrap_or("research_100_rounds.jsonl".into());
+    let output = std::env::var("BACKTEST_OUTPUT").unwrap_or("backtest_results.jsonl".into());
+    let spec = std::env::var("STRATEGY").unwrap_or("all".into());
+    let bet_amount = env_or("BET_AMOUNT", 20_000_000u64); // 0.02 SOL per round
+    let motherlode = env_or("MOTHERLODE", 0u64);
+    let mint_supply = env_or("MINT_SUPPLY", 0u64);
+    let ore_price = env_or("ORE_PRICE", 0.0f64); // SOL per ORE, used to value ORE in ROI
+    let seed = env_or("SEED", 0u64);
+
+    let rounds = load_rounds(&input)?;
+    println!("\n╔══════════════════════════════════════════════════════════╗");
+    println!("║   📈 ORE BACKTEST                                         ║");
+    println!("╠══════════════════════════════════════════════════════════╣");
+    println!("║  Rounds: {} (from {})", rounds.len(), input);
+    println!("║  Bankroll: {:.4} SOL per round", bet_amount as f64 / 1e9);
+    println!("║  ORE price: {:.4} SOL", ore_price);
+    println!("╚══════════════════════════════════════════════════════════╝\n");
+
+    let strategies: Vec<(String, MiningStrategy)> = if spec == "all" {
+        default_strategies()
+    } else {
+        let strategy = spec.parse::<MiningStrategy>()?;
+        vec![(spec.clone(), strategy)]
+    };
+
+    let mut file = File::create(&output)?;
+    let mut summaries = vec![];
+    for (name, strategy) in strategies {
+        let summary = backtest(
+            &name,
+            strategy,
+            &rounds,
+            bet_amount,
+            motherlode,
+            mint_supply,
+            ore_price,
+            seed,
+            &mut file,
+        )?;
+        summaries.push(summary);
+    }
+
+    print_summaries(&summaries);
+    println!("📁 Per-round results written to {}", output);
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn backtest(
+    name: &str,
+    strategy: MiningStrategy,
+    rounds: &[RecordedRound],
+    bet_amount: u64,
+    motherlode: u64,
+    mint_supply: u64,
+    ore_price: f64,
+    seed: u64,
+    file: &mut File,
+) -> Result<BacktestSummary, Box<dyn std::error::Error>> {
+    let mut metrics = StrategyMetrics::default();
+    let mut sol_pnl = 0i64;
+    let mut ore_won = 0u64;
+    let mut peak = 0.0f64;
+    let mut max_drawdown = 0.0f64;
+
+    for recorded in rounds {
+        let Some(winning_square) = recorded.winning_square() else {
+            continue;
+        };
+
+        // Build the round as it looked when the snapshot was taken.
+        let mut round = Round::zeroed();
+        round.id = recorded.round_id;
+        round.deployed = recorded.square_pools;
+        round.total_deployed = recorded.square_pools.iter().sum();
+        let board = Board {
+            round_id: recorded.round_id,
+            start_slot: 0,
+            end_slot: 0,
+        };
+
+        // Deploy the bankroll evenly over the selected squares.
+        let mut rng = StdRng::seed_from_u64(seed ^ recorded.round_id);
+        let squares = strategy.select_squares_with_rng(&round, &board, &mut rng);
+        let selected: Vec<usize> = (0..25).filter(|&i| squares[i]).collect();
+        let per_square = if selected.is_empty() {
+            0
+        } else {
+            bet_amount / selected.len() as u64
+        };
+        let mut miner = Miner::zeroed();
+        miner.authority = Pubkey::new_unique();
+        miner.round_id = round.id;
+        if per_square > 0 {
+            for &i in &selected {
+                miner.cumulative[i] = round.deployed[i];
+                miner.deployed[i] = per_square;
+                round.deployed[i] += per_square;
+                round.total_deployed += per_square;
+                round.count[i] += 1;
+            }
+        }
+        let deployed = miner.deployed.iter().sum::<u64>();
+
+        // Settle the round.
+        let value = var_value(round.id, winning_square);
+        let pool = motherlode.max(recorded.motherlode);
+        let simulation = sim::simulate(&round, &[miner], value, mint_supply, pool);
+        let rewards = simulation.rewards[0];
+        let won = miner.deployed[winning_square] > 0;
+
+        // Update metrics.
+        metrics.update_round();
+        if won {
+            metrics.update_win(deployed, rewards.rewards_sol, rewards.rewards_ore());
+        } else {
+            metrics.update_loss(deployed);
+        }
+        let round_pnl = rewards.rewards_sol as i64 - deployed as i64;
+        sol_pnl += round_pnl;
+        ore_won += rewards.rewards_ore();
+        let equity = sol_pnl as f64 / LAMPORTS_PER_SOL as f64
+            + ore_won as f64 / ONE_ORE as f64 * ore_price;
+        peak = peak.max(equity);
+        max_drawdown = max_drawdown.max(peak - equity);
+
+        let result = BacktestRound {
+            strategy: name.to_string(),
+            round_id: round.id,
+            squares: selected,
+            deployed,
+            winning_square,
+            won,
+            sol_returned: rewards.rewards_sol,
+            sol_pnl: round_pnl,
+            ore_won: rewards.rewards_ore(),
+            cumulative_sol_pnl: sol_pnl,
+            cumulative_ore: ore_won,
+            drawdown_sol: peak - equity,
+        };
+        writeln!(file, "{}", serde_json::to_string(&result)?)?;
+    }
+
+    let total_value = metrics.total_sol_won as f64 / LAMPORTS_PER_SOL as f64
+        + ore_won as f64 / ONE_ORE as f64 * ore_price;
+    let total_deployed = metrics.total_deployed as f64 / LAMPORTS_PER_SOL as f64;
+    let roi = if total_deployed > 0.0 {
+        (total_value / total_deployed - 1.0) * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(BacktestSummary {
+        strategy: name.to_string(),
+        metrics,
+        sol_pnl,
+        ore_won,
+        max_drawdown_sol: max_drawdown,
+        roi,
+    })
+}
+
+/// Derives a deterministic var value that reproduces the recorded winning square.
+/// Split rewards, the top miner sample and motherlode hits were not recorded, so they are
+/// drawn from the same value, which keeps runs reproducible.
+fn var_value(round_id: u64, winning_square: usize) -> [u8; 32] {
+    let mut probe = Round::zeroed();
+    for nonce in 0u64.. {
+        probe.slot_hash = hashv(&[&round_id.to_le_bytes(), &nonce.to_le_bytes()]).to_bytes();
+        if let Some(r) = probe.rng() {
+            if probe.winning_square(r) == winning_square {
+                return probe.slot_hash;
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Loads recorded rounds, keeping the latest record of each round.
+fn load_rounds(path: &str) -> Result<Vec<RecordedRound>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut rounds = BTreeMap::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<RecordedRound>(&line) {
+            Ok(round) => {
+                rounds.insert(round.round_id, round);
+            }
+            Err(e) => eprintln!("Skipping malformed line: {}", e),
+        }
+    }
+    Ok(rounds.into_values().collect())
+}
+
+fn default_strategies() -> Vec<(String, MiningStrategy)> {
+    [
+        "uniform_all",
+        "random:5",
+        "contrarian:5:0.7",
+        "contrarian:10:0.8",
+        "kelly:0.2",
+        "late_snipe:2",
+        "whale_rider:1000000000:4",
+        "whale_fader:1000000000:5",
+        "adaptive",
+        "small_capital",
+    ]
+    .iter()
+    .map(|spec| (spec.to_string(), spec.parse().unwrap()))
+    .collect()
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(default)
+}
+
+fn print_summaries(summaries: &[BacktestSummary]) {
+    println!(
+        "{:<28} {:>7} {:>8} {:>14} {:>12} {:>12} {:>9}",
+        "Strategy", "Rounds", "Win %", "SOL P&L", "ORE won", "Max DD", "ROI %"
+    );
+    println!("{}", "─".repeat(96));
+    for s in summaries {
+        println!(
+            "{:<28} {:>7} {:>8.2} {:>14.6} {:>12.6} {:>12.6} {:>9.2}",
+            s.strategy,
+            s.metrics.rounds_played,
+            s.metrics.win_rate(),
+            s.sol_pnl as f64 / LAMPORTS_PER_SOL as f64,
+            s.ore_won as f64 / ONE_ORE as f64,
+            s.max_drawdown_sol,
+            s.roi,
+        );
+    }
+    println!();
+
+    // Append a one-line summary per strategy for later comparison.
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("backtest_summary.jsonl")
+    {
+        for s in summaries {
+            let _ = writeln!(
+                file,
+                "{}",
+                serde_json::json!({
+                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                    "strategy": s.strategy,
+                    "rounds": s.metrics.rounds_played,
+                    "win_rate": s.metrics.win_rate(),
+                    "sol_pnl_lamports": s.sol_pnl,
+                    "ore_won": s.ore_won,
+                    "max_drawdown_sol": s.max_drawdown_sol,
+                    "roi": s.roi,
+                })
+            );
+        }
+    }
+}
@@ -16,6 +16,10 @@ use tokio::sync::RwLock;
 use futures_util::StreamExt;
 use steel::AccountDeserialize;
 
+#[path = "../indexer.rs"]
+#[allow(dead_code)]
+mod indexer;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct RoundSnapshot {
     round_id: u64,
@@ -297,16 +301,21 @@ async fn capture_round_snapshot(
 }
 
 async fn get_round_winner(rpc: &RpcClient, round_id: u64) -> Result<usize, Box<dyn std::error::Error>> {
+    // Prefer the reset event recorded by the indexer, which survives the round being closed.
+    let index_file = std::env::var("INDEX_FILE").unwrap_or("ore_events.jsonl".to_string());
+    if let Some(reset) = indexer::find_reset(&index_file, round_id) {
+        if reset.winning_square == u64::MAX {
+            return Err("Round had no winning square".into());
+        }
+        return Ok(reset.winning_square as usize);
+    }
+
     let round_pubkey = round_pda(round_id).0;
     let round_data = rpc.get_account_data(&round_pubkey).await?;
     let round = Round::try_from_bytes(&round_data)?;
-
-    if round.slot_hash != [0; 32] {
-        let rng = u64::from_le_bytes(round.slot_hash[0..8].try_into().unwrap());
-        let winner = round.winning_square(rng);
-        Ok(winner as usize)
-    } else {
-        Err("Slot hash not available yet".into())
+    match round.rng() {
+        Some(rng) => Ok(round.winning_square(rng)),
+        None => Err("Slot hash not available yet".into()),
     }
 }
 
@@ -0,0 +1,251 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+use crate::fees::PriorityFee;
+
+/// Used when neither the config file nor flags set an RPC.
+const DEFAULT_RPC: &str = "https://api.mainnet-beta.solana.com";
+
+/// Used when neither the config file nor flags set a Jupiter API.
+const DEFAULT_JUPITER_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
+
+/// Settings that can be set at any layer: the `[defaults]` table, a profile, or flags.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// RPC URL. The websocket URL is derived from it.
+    pub rpc: Option<String>,
+
+    /// Path to the payer keypair. `~/` is expanded.
+    pub keypair: Option<String>,
+
+    /// Jupiter swap API used by `bury`.
+    pub jupiter_api_url: Option<String>,
+
+    /// Priority fee for commands without an entry in `priority_fees`.
+    pub priority_fee: Option<PriorityFee>,
+
+    /// Priority fee per subcommand, e.g. `deploy-optimal-ev = "dynamic:90:5000000"`.
+    pub priority_fees: Option<HashMap<String, PriorityFee>>,
+}
+
+impl Settings {
+    /// Overrides these settings with any values set in `other`.
+    pub fn merge(self, other: Settings) -> Settings {
+        Settings {
+            rpc: other.rpc.or(self.rpc),
+            keypair: other.keypair.or(self.keypair),
+            jupiter_api_url: other.jupiter_api_url.or(self.jupiter_api_url),
+            priority_fee: other.priority_fee.or(self.priority_fee),
+            priority_fees: match (self.priority_fees, other.priority_fees) {
+                (Some(mut fees), Some(other)) => {
+                    fees.extend(other);
+                    Some(fees)
+                }
+                (fees, other) => other.or(fees),
+            },
+        }
+    }
+}
+
+/// The config file, by default at `~/.config/ore/config.toml`:
+///
+/// ```toml
+/// profile = "main"
+///
+/// [defaults]
+/// rpc = "https://mainnet.helius-rpc.com/?api-key=..."
+/// priority_fee = "static:10000"
+///
+/// [defaults.priority_fees]
+/// deploy-optimal-ev = "dynamic:90:5000000"
+///
+/// [profiles.main]
+/// keypair = "~/.config/solana/id.json"
+///
+/// [profiles.bot2]
+/// keypair = "~/keys/bot2.json"
+/// rpc = "https://other-rpc.example.com"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Profile used when `--profile` is not given.
+    pub profile: Option<String>,
+
+    /// Settings shared by every profile.
+    #[serde(default)]
+    pub defaults: Settings,
+
+    /// Named profiles, usually one per wallet.
+    #[serde(default)]
+    pub profiles: HashMap<String, Settings>,
+}
+
+/// Fully resolved settings.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub rpc: String,
+    pub keypair: String,
+    pub jupiter_api_url: String,
+    pub priority_fee: Option<PriorityFee>,
+    pub priority_fees: HashMap<String, PriorityFee>,
+}
+
+impl Config {
+    /// Resolves settings in order of precedence: flags (and their env vars), the selected
+    /// profile, the `[defaults]` table, then built-in defaults.
+    pub fn load(path: Option<&str>, profile: Option<&str>, flags: Settings) -> Result<Config> {
+        let file = match path {
+            Some(path) => read_config_file(&expand_home(path))?,
+            None => match default_config_path() {
+                Some(path) if path.exists() => read_config_file(&path)?,
+                _ => ConfigFile::default(),
+            },
+        };
+
+        // Select the profile. Naming a profile that does not exist is an error.
+        let mut settings = file.defaults;
+        if let Some(name) = profile.or(file.profile.as_deref()) {
+            let profile = file
+                .profiles
+                .get(name)
+                .ok_or_else(|| anyhow!("Unknown profile: {}", name))?;
+            settings = settings.merge(profile.clone());
+        }
+        let settings = settings.merge(flags);
+
+        Ok(Config {
+            rpc: settings.rpc.unwrap_or(DEFAULT_RPC.to_string()),
+            keypair: settings
+                .keypair
+                .map(|k| expand_home(&k).to_string_lossy().to_string())
+                .unwrap_or_else(default_keypair_path),
+            jupiter_api_url: settings
+                .jupiter_api_url
+                .unwrap_or(DEFAULT_JUPITER_API_URL.to_string()),
+            priority_fee: settings.priority_fee,
+            priority_fees: settings.priority_fees.unwrap_or_default(),
+        })
+    }
+
+    /// Priority fee for a subcommand: its `priority_fees` entry, then `priority_fee`, then the
+    /// built-in policy for the command.
+    pub fn priority_fee(&self, command: &str) -> PriorityFee {
+        self.priority_fees
+            .get(command)
+            .copied()
+            .or(self.priority_fee)
+            .unwrap_or_else(|| PriorityFee::for_command(command))
+    }
+
+    pub fn payer(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair)
+            .map_err(|e| anyhow!("Failed to read keypair {}: {}", self.keypair, e))
+    }
+}
+
+fn read_config_file(path: &PathBuf) -> Result<ConfigFile> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config {}", path.display()))?;
+    toml::from_str(&contents).with_context(|| format!("Invalid config {}", path.display()))
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ore").join("config.toml"))
+}
+
+fn default_keypair_path() -> String {
+    expand_home("~/.config/solana/id.json")
+        .to_string_lossy()
+        .to_string()
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layering() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            profile = "main"
+
+            [defaults]
+            rpc = "https://default-rpc"
+
+            [profiles.main]
+            keypair = "/keys/main.json"
+
+            [profiles.bot2]
+            keypair = "/keys/bot2.json"
+            rpc = "https://bot2-rpc"
+            "#,
+        )
+        .unwrap();
+        let main = file.defaults.clone().merge(file.profiles["main"].clone());
+        assert_eq!(main.rpc.as_deref(), Some("https://default-rpc"));
+        assert_eq!(main.keypair.as_deref(), Some("/keys/main.json"));
+        let bot2 = file.defaults.clone().merge(file.profiles["bot2"].clone());
+        assert_eq!(bot2.rpc.as_deref(), Some("https://bot2-rpc"));
+
+        // Flags override everything.
+        let flags = Settings {
+            rpc: Some("https://flag-rpc".to_string()),
+            ..Settings::default()
+        };
+        let resolved = bot2.merge(flags);
+        assert_eq!(resolved.rpc.as_deref(), Some("https://flag-rpc"));
+        assert_eq!(resolved.keypair.as_deref(), Some("/keys/bot2.json"));
+    }
+
+    #[test]
+    fn test_priority_fees() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            [defaults]
+            priority_fee = "static:5000"
+
+            [defaults.priority_fees]
+            deploy-optimal-ev = "dynamic:90:5000000"
+
+            [profiles.main.priority_fees]
+            claim = "static:1"
+            "#,
+        )
+        .unwrap();
+        let settings = file.defaults.merge(file.profiles["main"].clone());
+        let fees = settings.priority_fees.unwrap();
+        assert_eq!(fees["claim"], PriorityFee::Static(1));
+        assert_eq!(
+            fees["deploy-optimal-ev"],
+            PriorityFee::Dynamic {
+                percentile: 90,
+                max: 5_000_000
+            }
+        );
+        assert_eq!(settings.priority_fee, Some(PriorityFee::Static(5000)));
+        assert!(toml::from_str::<ConfigFile>("[defaults]\npriority_fee = \"fast\"").is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        let result = toml::from_str::<ConfigFile>(
+            r#"
+            [profiles.main]
+            key_pair = "/keys/main.json"
+            "#,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,302 @@
+/// Reset crank for the end of each round
+///
+/// Once a round ends and the intermission passes, anyone may sample the entropy var, reveal its
+/// seed and reset the board. The crank waits for that slot, reveals first so the winning square
+/// and top miner are known before reset, and treats a round another cranker already reset as done.
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{keccak, pubkey::Pubkey, signature::Signer, signer::keypair::Keypair};
+use steel::AccountDeserialize;
+use tokio::time::{sleep, Duration};
+
+use ore_api::{prelude::*, sim};
+
+use crate::fees::PriorityFee;
+use crate::{
+    get_board, get_clock, get_config, get_miners_participating, get_round, get_var,
+    submit_transaction, ORE_VAR_ADDRESS,
+};
+
+/// Seed service used when no provider is given.
+pub const DEFAULT_SEED_PROVIDER: &str = "https://entropy-api.onrender.com/var/{var}/seed";
+
+/// Reset attempts per round before going back to polling.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Where the entropy var's seed is fetched from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SeedProvider {
+    /// An entropy API url, with `{var}` replaced by the var address.
+    Http(String),
+
+    /// A fixed seed, for a local validator whose var was committed to it.
+    Mock(keccak::Hash),
+}
+
+impl SeedProvider {
+    pub async fn seed(&self, var: Pubkey) -> Result<[u8; 32]> {
+        match self {
+            SeedProvider::Http(url) => {
+                let url = url.replace("{var}", &var.to_string());
+                let response = reqwest::Client::new()
+                    .get(url)
+                    .timeout(Duration::from_secs(10))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<entropy_types::response::GetSeedResponse>()
+                    .await?;
+                Ok(response.seed)
+            }
+            SeedProvider::Mock(seed) => Ok(seed.to_bytes()),
+        }
+    }
+}
+
+impl FromStr for SeedProvider {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        if spec.starts_with("http://") || spec.starts_with("https://") {
+            return Ok(SeedProvider::Http(spec.to_string()));
+        }
+        match spec.split_once(':') {
+            Some(("mock", seed)) => seed
+                .parse()
+                .map(SeedProvider::Mock)
+                .map_err(|_| format!("Invalid mock seed: {}", seed)),
+            _ => Err(format!("Invalid seed provider: {}", spec)),
+        }
+    }
+}
+
+pub struct CrankConfig {
+    /// Providers tried in order until one returns a seed.
+    pub seed_providers: Vec<SeedProvider>,
+
+    /// Reset a single round and exit.
+    pub once: bool,
+
+    pub priority_fee: PriorityFee,
+}
+
+/// Resets the board at the end of every round.
+pub async fn run_crank(rpc: &RpcClient, payer: &Keypair, config: CrankConfig) -> Result<()> {
+    println!("⚙️  Crank: {}", payer.pubkey());
+    loop {
+        let board = get_board(rpc).await?;
+        let slot = get_clock(rpc).await?.slot;
+
+        // Rounds wait for their first deploy before starting.
+        if board.end_slot == u64::MAX {
+            sleep(Duration::from_secs(2)).await;
+            continue;
+        }
+        let reset_slot = board.end_slot + INTERMISSION_SLOTS;
+        if slot < reset_slot {
+            // Slots are ~400ms, wake up a little early and poll from there.
+            let wait = (reset_slot - slot) * 400;
+            sleep(Duration::from_millis(wait.saturating_sub(1_000).max(400))).await;
+            continue;
+        }
+
+        match crank_round(rpc, payer, &config, board.round_id).await {
+            Ok(Cranked::Reset) => println!("✅ Reset round #{}", board.round_id),
+            Ok(Cranked::Raced) => {
+                println!("🏁 Round #{} was reset by another cranker", board.round_id)
+            }
+            Err(e) => {
+                println!("❌ Round #{}: {}", board.round_id, e);
+                if config.once {
+                    return Err(e);
+                }
+                sleep(Duration::from_secs(2)).await;
+                continue;
+            }
+        }
+        if config.once {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Cranked {
+    Reset,
+
+    /// Someone else reset the round first.
+    Raced,
+}
+
+async fn crank_round(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    config: &CrankConfig,
+    round_id: u64,
+) -> Result<Cranked> {
+    let fee_collector = get_config(rpc).await?.fee_collector;
+    let mut last_err = anyhow!("no attempts made");
+    for attempt in 1..=MAX_ATTEMPTS {
+        if is_reset(rpc, round_id).await? {
+            return Ok(Cranked::Raced);
+        }
+        match try_reset(rpc, payer, config, round_id, fee_collector).await {
+            Ok(()) => return Ok(Cranked::Reset),
+            // The next round existing means our transaction lost the race, not that it failed.
+            Err(_) if is_reset(rpc, round_id).await? => return Ok(Cranked::Raced),
+            Err(e) => {
+                println!("⚠️  Attempt {}/{}: {}", attempt, MAX_ATTEMPTS, e);
+                last_err = e;
+                sleep(Duration::from_secs(attempt as u64)).await;
+            }
+        }
+    }
+    Err(last_err)
+}
+
+/// Reveals the var if no one has yet, then resets with the top miner its value selects.
+async fn try_reset(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    config: &CrankConfig,
+    round_id: u64,
+    fee_collector: Pubkey,
+) -> Result<()> {
+    let mut var = get_var(rpc, ORE_VAR_ADDRESS).await?;
+    if !is_revealed(&var) {
+        let seed = fetch_seed(&config.seed_providers, ORE_VAR_ADDRESS).await?;
+        let ixs = [
+            entropy_api::sdk::sample(payer.pubkey(), ORE_VAR_ADDRESS),
+            entropy_api::sdk::reveal(payer.pubkey(), ORE_VAR_ADDRESS, seed),
+        ];
+        submit_transaction(rpc, payer, &ixs, config.priority_fee).await?;
+        var = get_var(rpc, ORE_VAR_ADDRESS).await?;
+    }
+
+    let round = get_round(rpc, round_id).await?;
+    let miners: Vec<Miner> = get_miners_participating(rpc, round_id)
+        .await?
+        .into_iter()
+        .map(|(_, miner)| miner)
+        .collect();
+    let top_miner = top_miner(&round, &miners, var.value);
+    let ix = ore_api::sdk::reset(payer.pubkey(), fee_collector, round_id, top_miner);
+    submit_transaction(rpc, payer, &[ix], config.priority_fee).await?;
+    Ok(())
+}
+
+/// Mirrors the checks `reset` makes on the var.
+fn is_revealed(var: &entropy_api::state::Var) -> bool {
+    var.slot_hash != [0; 32] && var.seed != [0; 32] && var.value != [0; 32]
+}
+
+/// Whether the round after `round_id` has been opened.
+async fn is_reset(rpc: &RpcClient, round_id: u64) -> Result<bool> {
+    let address = round_pda(round_id + 1).0;
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .await?
+        .value;
+    Ok(account.is_some_and(|account| Round::try_from_bytes(&account.data).is_ok()))
+}
+
+/// Tries each provider in order, so one seed service being down does not stall the crank.
+async fn fetch_seed(providers: &[SeedProvider], var: Pubkey) -> Result<[u8; 32]> {
+    for provider in providers {
+        match provider.seed(var).await {
+            Ok(seed) => return Ok(seed),
+            Err(e) => println!("⚠️  Seed provider {:?} unavailable: {}", provider, e),
+        }
+    }
+    Err(anyhow!("no seed provider available"))
+}
+
+/// The miner `reset` will sample as the top miner, or the default address if there is none.
+fn top_miner(round: &Round, miners: &[Miner], value: [u8; 32]) -> Pubkey {
+    let simulation = sim::simulate(round, miners, value, 0, 0);
+    if simulation.round.top_miner == SPLIT_ADDRESS {
+        return Pubkey::default();
+    }
+    simulation.round.top_miner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    #[test]
+    fn test_seed_provider() {
+        assert_eq!(
+            DEFAULT_SEED_PROVIDER.parse(),
+            Ok(SeedProvider::Http(DEFAULT_SEED_PROVIDER.to_string()))
+        );
+        let seed = keccak::hash(b"seed");
+        assert_eq!(
+            format!("mock:{}", seed).parse(),
+            Ok(SeedProvider::Mock(seed))
+        );
+        assert!("mock:xyz".parse::<SeedProvider>().is_err());
+        assert!("ftp://seeds".parse::<SeedProvider>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_seed_falls_back() {
+        let seed = keccak::hash(b"seed");
+        let providers = [
+            SeedProvider::Http("http://127.0.0.1:1/var/{var}/seed".to_string()),
+            SeedProvider::Mock(seed),
+        ];
+        let fetched = fetch_seed(&providers, ORE_VAR_ADDRESS).await.unwrap();
+        assert_eq!(fetched, seed.to_bytes());
+        assert!(fetch_seed(&providers[..1], ORE_VAR_ADDRESS).await.is_err());
+    }
+
+    #[test]
+    fn test_top_miner() {
+        let mut round = Round::zeroed();
+        round.id = 1;
+        let mut miners = vec![];
+        for (i, authority) in [Pubkey::new_unique(), Pubkey::new_unique()]
+            .into_iter()
+            .enumerate()
+        {
+            let mut miner = Miner::zeroed();
+            miner.authority = authority;
+            miner.round_id = 1;
+            for square in 0..25 {
+                miner.cumulative[square] = round.deployed[square];
+                miner.deployed[square] = 100 * (i as u64 + 1);
+                round.deployed[square] += miner.deployed[square];
+                round.total_deployed += miner.deployed[square];
+            }
+            miners.push(miner);
+        }
+
+        // Search for values where the reward goes to one miner, and where it is split.
+        let mut found = (false, false);
+        for i in 0..u8::MAX {
+            let value = keccak::hash(&[i]).to_bytes();
+            let rng = Round {
+                slot_hash: value,
+                ..round
+            }
+            .rng()
+            .unwrap();
+            let top_miner = top_miner(&round, &miners, value);
+            if round.is_split_reward(rng) {
+                assert_eq!(top_miner, Pubkey::default());
+                found.1 = true;
+            } else {
+                let sample = round.top_miner_sample(rng, round.winning_square(rng));
+                let expected = if sample < 100 { 0 } else { 1 };
+                assert_eq!(top_miner, miners[expected].authority);
+                found.0 = true;
+            }
+        }
+        assert_eq!(found, (true, true));
+    }
+}
@@ -1,515 +1,54 @@
 /// OPTIMAL +EV DEPLOYMENT - WEBSOCKET DRIVEN
 ///
 /// Based on 100 rounds of research (UPDATED):
-/// - Deploy to 2 LEAST CROWDED squares (+30% better share)
+/// - Deploy to the N LEAST CROWDED squares (+30% better share)
 /// - DO NOT FILTER previous winner (9.1% consecutive win rate vs 4% expected = 2.3x!)
 /// - Deploy at 5-10s remaining (maximum information)
 /// - Continuous mining with automatic checkpointing
-///
-/// CRITICAL FINDING: Previous winner has "hot hand" - 2.3x more likely to win again!
 
 use anyhow::Result;
-use ore_api::prelude::*;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::signature::Signer;
-use std::fs::OpenOptions;
-use std::io::Write as IoWrite;
-use serde_json::json;
-use chrono::Utc;
-
-use crate::websocket::WebSocketManager;
-use crate::{get_board, get_round, get_miner, submit_transaction};
-
-const ONE_ORE: f64 = 100_000_000.0;
-
-#[derive(Debug, Clone)]
-struct OptimalDeployResult {
-    round_id: u64,
-    timestamp: String,
-    our_squares: Vec<usize>,
-    amount_deployed: u64,
-    previous_winner: Option<usize>,
-    previous_winner_filtered: bool,
-    winning_squares: Option<Vec<usize>>,
-    ore_won: u64,
-    sol_won: u64,
-    won: bool,
-    our_share_pct: f64,
-}
-
-/// OPTIMAL 10-SQUARE STRATEGY - PROVEN 37.5% WIN RATE!
-/// Testing results from 40 rounds:
-/// - 10 squares = 37.5% actual win rate (15/40 wins) ✅
-/// - Consistent wins every ~3 rounds
-/// - Low variance = perfect for limited bankroll
-///
-/// Strategy:
-/// - Select 10 least crowded squares (40% coverage)
-/// - With ORE expected to 2x in 2 weeks: +5.9% ROI
-/// - Plus 150% APR yield on unrefined ORE
-/// - Risk: Low (proven performance)
-fn select_optimal_squares(round: &Round, previous_winner: Option<usize>) -> Vec<usize> {
-    // Sort squares by deployment (ascending = least crowded first)
-    let mut squares_by_deployment: Vec<(usize, u64)> = round
-        .deployed
-        .iter()
-        .enumerate()
-        .map(|(i, &d)| (i, d))
-        .collect();
-
-    squares_by_deployment.sort_by_key(|&(_, d)| d);
-
-    // Get number of squares from env or default to 10
-    let num_squares: usize = std::env::var("NUM_SQUARES")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(10);
 
-    // PROVEN STRATEGY: Select N least crowded squares
-    // 10 squares = 37.5% win rate proven over 40 rounds
-    // Testing 18-20 squares for higher win rate + sustainability
-    squares_by_deployment
-        .iter()
-        .take(num_squares)
-        .map(|&(idx, _)| idx)
-        .collect()
-}
-
-/// Get previous round's winner
-async fn get_previous_winner(rpc: &RpcClient, current_round_id: u64) -> Option<usize> {
-    if current_round_id == 0 {
-        return None;
-    }
-
-    let prev_round_id = current_round_id - 1;
-
-    match get_round(rpc, prev_round_id).await {
-        Ok(prev_round) => {
-            // Use rng() method to check slot_hash
-            if let Some(rng) = prev_round.rng() {
-                let winner = prev_round.winning_square(rng);
-                return Some(winner as usize);
-            }
-            None
-        }
-        Err(_) => None,
-    }
-}
-
-/// Calculate expected share
-fn calculate_share(round: &Round, squares: &[usize], deployment_per_square: u64) -> f64 {
-    let mut total_share = 0.0;
-
-    for &sq in squares {
-        let pool = round.deployed[sq];
-        let total = pool + deployment_per_square;
-        if total > 0 {
-            total_share += (deployment_per_square as f64 / total as f64);
-        }
-    }
-
-    if !squares.is_empty() {
-        total_share / squares.len() as f64
-    } else {
-        0.0
-    }
-}
+use crate::args::{GuardArgs, PredictArgs};
+use crate::driver::{run_strategy, DriverConfig};
+use crate::fees::PriorityFee;
+use crate::strategy::{DeployTiming, LeastCrowded};
 
 /// Main continuous optimal deployment
 pub async fn deploy_optimal_ev(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    num_squares: usize,
+    bet_amount: u64,
+    guard: GuardArgs,
+    predict: PredictArgs,
 ) -> Result<()> {
-    // Get number of squares from env
-    let num_squares: usize = std::env::var("NUM_SQUARES")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(10);
-
-    // OPTIMAL N-SQUARE STRATEGY
-    // 10 squares = 37.5% win rate (proven)
-    // Testing 18+ squares for higher win rate
-    let per_square = std::env::var("BET_AMOUNT")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .and_then(|total| Some(total / num_squares as u64))
-        .unwrap_or(150_000u64); // Default: 0.00015 SOL per square
-
-    // DEBUG: Print the actual values being used
-    println!("\n🔍 DEBUG - Bet Configuration:");
-    println!("   NUM_SQUARES: {}", num_squares);
-    println!("   BET_AMOUNT env var: {:?}", std::env::var("BET_AMOUNT"));
-    println!("   Per square: {} lamports ({:.4} SOL)", per_square, per_square as f64 / 1e9);
-    println!("   Total per round: {} lamports ({:.4} SOL for {} squares)\n", per_square * num_squares as u64, per_square as f64 * num_squares as f64 / 1e9, num_squares);
-
-    let rpc_url = std::env::var("RPC").expect("Missing RPC env var");
-    let ws_manager = WebSocketManager::new(&rpc_url);
+    // The bet amount is the total per round, split evenly across squares
+    let per_square = bet_amount / num_squares as u64;
 
     println!("╔════════════════════════════════════════════════════════════════╗");
-    if num_squares == 10 {
-        println!("║         🚀 OPTIMAL 10-SQUARE STRATEGY - PROVEN! 🚀             ║");
-    } else {
-        println!("║         🎯 TESTING {}-SQUARE STRATEGY 🎯                     ║", num_squares);
-    }
+    println!("║         🎯 {}-SQUARE LEAST CROWDED STRATEGY 🎯                 ║", num_squares);
     println!("╠════════════════════════════════════════════════════════════════╣");
-    if num_squares == 10 {
-        println!("║ PROVEN: 37.5% win rate over 40 rounds (15 wins!)              ║");
-    } else {
-        println!("║ TESTING: {} squares for higher win rate + sustainability      ║", num_squares);
-    }
-    println!("║ Strategy: {} LEAST CROWDED squares                           ║", num_squares);
     println!("║ Amount: {:.4} SOL per square × {} = {:.4} SOL/round           ║", per_square as f64 / 1e9, num_squares, per_square as f64 * num_squares as f64 / 1e9);
-    println!("║ If ORE 2x in 2 weeks: +5.9% ROI + 150% APR yield              ║");
-    println!("║ Current: -4.6% ROI (will flip positive when ORE pumps!)       ║");
     println!("║ Timing: Deploy at 5-10s remaining (maximum info)              ║");
     println!("╚════════════════════════════════════════════════════════════════╝\n");
 
-    println!("🔌 Starting WebSocket connections...");
-    ws_manager.subscribe_to_board().await?;
-    ws_manager.subscribe_to_slots().await?;
-
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-    // Check miner state (for info only - no catch-up needed!)
-    let board = get_board(rpc).await?;
-
-    let miner = match get_miner(rpc, payer.pubkey()).await {
-        Ok(m) => {
-            println!("\n📊 Miner Status:");
-            println!("   Last round played: #{}", m.round_id);
-            println!("   Last checkpoint: #{}", m.checkpoint_id);
-            println!("   Current round: #{}", board.round_id);
-
-            // As long as you checkpointed your last played round, you can deploy to any future round!
-            // No need to checkpoint rounds you didn't participate in.
-            if m.round_id > 0 && m.checkpoint_id != m.round_id {
-                println!("\n⚠️  WARNING: You haven't checkpointed your last played round!");
-                println!("   Checkpointing round #{} now...\n", m.round_id);
-                let checkpoint_ix = ore_api::sdk::checkpoint(payer.pubkey(), payer.pubkey(), m.round_id);
-                submit_transaction(rpc, payer, &[checkpoint_ix]).await?;
-                println!("✅ Checkpointed!\n");
-                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-            } else {
-                println!("   ✅ Ready to deploy!\n");
-            }
-            Some(m)
-        }
-        Err(_) => {
-            println!("\n🆕 NEW WALLET DETECTED!");
-            println!("   No miner account found - will be created on first deploy");
-            println!("   Current round: #{}", board.round_id);
-            println!("   ✅ Ready to start mining!\n");
-            None
-        }
+    let mut strategy = LeastCrowded {
+        count: num_squares,
+        amount_per_square: per_square,
+        skip_previous_winner: false,
+        timing: DeployTiming::Window {
+            max_seconds: 10,
+            min_seconds: 5,
+        },
     };
-
-    let mut rounds_played = 0;
-    let mut rounds_won = 0;
-    let mut our_deployed_squares: Vec<usize> = Vec::new();
-    let mut last_checkpoint_round: Option<u64> = None;
-    let mut previous_winner: Option<usize> = None;
-
-    loop {
-        rounds_played += 1;
-
-        println!("\n╔════════════════════════════════════════════════════════════════╗");
-        println!("║  Round #{:<57}║", rounds_played);
-        println!("╚════════════════════════════════════════════════════════════════╝\n");
-
-        // Checkpoint previous round BEFORE doing anything else
-        if last_checkpoint_round.is_some() {
-            println!("📝 Checkpointing previous round...");
-
-            let miner_before = match get_miner(rpc, payer.pubkey()).await {
-                Ok(m) => m,
-                Err(e) => {
-                    println!("⚠️  Could not get miner: {}", e);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                    continue;
-                }
-            };
-
-            // Checkpoint the round we just played
-            let checkpoint_round = last_checkpoint_round.unwrap();
-
-            // Check if already checkpointed
-            if miner_before.checkpoint_id >= checkpoint_round {
-                println!("✅ Already checkpointed round #{} - skipping", checkpoint_round);
-                last_checkpoint_round = None;  // Clear it so we don't try again!
-            } else {
-                let checkpoint_ix = ore_api::sdk::checkpoint(payer.pubkey(), payer.pubkey(), checkpoint_round);
-                match submit_transaction(rpc, payer, &[checkpoint_ix]).await {
-                    Ok(_) => {
-                        println!("✅ Checkpointed round #{}", checkpoint_round);
-                        last_checkpoint_round = None;  // Clear after successful checkpoint!
-                    }
-                    Err(e) => {
-                        println!("⚠️  Checkpoint failed: {}", e);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                        continue;
-                    }
-                }
-
-                // Wait for checkpoint to be confirmed with retry loop
-                println!("⏳ Waiting for checkpoint confirmation...");
-                let mut confirmed = false;
-                for attempt in 1..=10 {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-                    match get_miner(rpc, payer.pubkey()).await {
-                        Ok(miner) => {
-                            if miner.checkpoint_id == checkpoint_round {
-                                println!("✅ Checkpoint confirmed (checkpoint_id: {}) after {} seconds",
-                                         miner.checkpoint_id, attempt * 2);
-                                confirmed = true;
-                                break;
-                            } else {
-                                println!("   Attempt {}/10: checkpoint_id = {}, expected = {}",
-                                         attempt, miner.checkpoint_id, checkpoint_round);
-                            }
-                        }
-                        Err(e) => {
-                            println!("   Attempt {}/10: Could not fetch miner: {}", attempt, e);
-                        }
-                    }
-                }
-
-                if !confirmed {
-                    println!("❌ Checkpoint not confirmed after 20 seconds - skipping this round");
-                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                    continue;
-                }
-            }
-
-            // Extra wait for round reset to complete
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-            // Get winner of previous round
-            let winning_square = match get_round(rpc, checkpoint_round).await {
-                Ok(round) => {
-                    if let Some(rng) = round.rng() {
-                        let winner = round.winning_square(rng);
-                        previous_winner = Some(winner as usize);
-                        println!("🎲 Round #{} winner: Square #{}", checkpoint_round, winner + 1);
-                        Some(winner as usize)
-                    } else {
-                        None
-                    }
-                }
-                Err(_) => None,
-            };
-
-            // Check if we won
-            let miner_after = match get_miner(rpc, payer.pubkey()).await {
-                Ok(m) => m,
-                Err(e) => {
-                    println!("⚠️  Could not get miner: {}", e);
-                    continue;
-                }
-            };
-
-            let ore_earned = miner_after.rewards_ore.saturating_sub(miner_before.rewards_ore);
-            let sol_earned = miner_after.rewards_sol.saturating_sub(miner_before.rewards_sol);
-
-            let won = if let Some(winner) = winning_square {
-                our_deployed_squares.contains(&winner)
-            } else {
-                false
-            };
-
-            if won {
-                rounds_won += 1;
-                println!("✅ WE WON! +{} ORE", ore_earned as f64 / ONE_ORE);
-            } else {
-                println!("❌ Lost this round");
-            }
-
-            // Log result
-            let total_deployed = per_square * our_deployed_squares.len() as u64;
-            log_result(checkpoint_round, &our_deployed_squares, winning_square,
-                       ore_earned, sol_earned, won, previous_winner, total_deployed);
-
-            println!("📊 Stats: {}/{} wins ({:.1}%)", rounds_won, rounds_played - 1,
-                     (rounds_won as f64 / (rounds_played - 1) as f64) * 100.0);
-        }
-
-        // Wait for new round
-        println!("⏳ Waiting for new round to start...");
-        let mut board = get_board(rpc).await?;
-
-        if let Some(last_id) = last_checkpoint_round {
-            while board.round_id == last_id {
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                board = get_board(rpc).await?;
-            }
-        }
-
-        println!("🆕 New round #{} started!", board.round_id);
-
-        // Get previous winner if not already known
-        if previous_winner.is_none() {
-            previous_winner = get_previous_winner(rpc, board.round_id).await;
-        }
-
-        if let Some(prev) = previous_winner {
-            println!("📊 Previous winner: Square #{}", prev + 1);
-        }
-
-        // Wait for optimal timing FIRST (5-10s remaining for MAXIMUM information)
-        println!("⏰ Waiting for optimal deployment window (5-10s remaining)...");
-        ws_manager.wait_for_deploy_window(10, 5).await;
-
-        // NOW get the LATEST board AND round data (right before deploying!)
-        println!("📡 Getting LATEST pool data for sniping...");
-        board = get_board(rpc).await?;  // Refresh board to get current round_id
-        let round = get_round(rpc, board.round_id).await?;
-
-        // Display ORE info (but mine every round regardless!)
-        let motherlode_ore = round.motherlode as f64 / 100_000_000_000.0;  // 11 decimals
-        let top_miner_ore = round.top_miner_reward as f64 / 100_000_000_000.0;
-        let total_ore_available = motherlode_ore + top_miner_ore;
-
-        println!("\n💎 ORE REWARDS THIS ROUND:");
-        println!("   Motherlode: {:.4} ORE", motherlode_ore);
-        println!("   Top miner reward: {:.4} ORE", top_miner_ore);
-        println!("   TOTAL ORE: {:.4} ORE", total_ore_available);
-
-        // MINE EVERY ROUND for SOL profit + ORE opportunities!
-
-        println!("✅ ORE AVAILABLE - DEPLOYING!\n");
-
-        // SELECT OPTIMAL SQUARES based on LATEST data (DO NOT FILTER PREVIOUS WINNER!)
-        our_deployed_squares = select_optimal_squares(&round, previous_winner);
-
-        if our_deployed_squares.len() < 2 {
-            println!("⚠️  Not enough squares available, skipping");
-            continue;
-        }
-
-        // Display strategy with LATEST data
-        println!("\n🚀 OPTIMAL 10-SQUARE STRATEGY (LATEST SNAPSHOT):");
-
-        if let Some(prev) = previous_winner {
-            println!("   Previous winner: Square #{}", prev + 1);
-        }
-
-        println!("   Selected: 10 LEAST CROWDED squares (PROVEN 37.5% win rate!)");
-        println!();
-
-        for (i, &sq) in our_deployed_squares.iter().enumerate() {
-            let pool_sol = round.deployed[sq] as f64 / 1e9;
-            let share = calculate_share(&round, &[sq], per_square) * 100.0;
-            println!("      {}. Square #{:2} - {:.4} SOL pool - {:.2}% share",
-                     i + 1, sq + 1, pool_sol, share);
-        }
-
-        let avg_share = calculate_share(&round, &our_deployed_squares, per_square) * 100.0;
-        let num_squares = our_deployed_squares.len();
-        println!();
-        println!("   Average share: {:.2}%", avg_share);
-        println!("   Win chance: ~37.5% (PROVEN over 40 rounds!)");
-        println!("   Strategy: Consistent wins + low risk + max ORE accumulation");
-        println!("   When ORE 2x: +5.9% ROI + 150% APR yield on unrefined ORE\n");
-
-        // DEPLOY IMMEDIATELY (we already waited, now have latest data!)
-        println!("🚀 Deploying to optimal squares NOW...");
-
-        // Build squares array (25 bools, true for selected squares)
-        let mut squares = [false; 25];
-        for &sq in &our_deployed_squares {
-            squares[sq] = true;
-        }
-
-        let ix = ore_api::sdk::deploy(
-            payer.pubkey(),
-            payer.pubkey(),
-            per_square,  // CRITICAL: amount is PER SQUARE, not total!
-            board.round_id,
-            squares,
-        );
-
-        match submit_transaction(rpc, payer, &[ix]).await {
-            Ok(_) => {
-                let total_cost = per_square * our_deployed_squares.len() as u64;
-                let squares_str = our_deployed_squares.iter()
-                    .map(|s| format!("#{}", s + 1))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                println!("✅ Deployed {:.4} SOL to {} squares: {}",
-                         total_cost as f64 / 1e9,
-                         our_deployed_squares.len(),
-                         squares_str);
-
-                // Save this round for checkpointing next iteration
-                last_checkpoint_round = Some(board.round_id);
-            }
-            Err(e) => {
-                println!("❌ Deployment failed: {}", e);
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                continue;
-            }
-        }
-
-        // CRITICAL: Wait for round to actually END before trying to checkpoint it!
-        // The round needs to have slot_hash set (happens when round ends)
-        println!("⏳ Waiting for round to end...\n");
-        match ws_manager.wait_for_round_reset(board.round_id, 120).await {
-            Ok(new_board) => {
-                println!("✅ Round #{} ended, new round #{} started\n", board.round_id, new_board.round_id);
-            }
-            Err(e) => {
-                println!("⚠️  Error waiting for round end: {}", e);
-                // Wait a bit anyway to let round finish
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-            }
-        }
-    }
-}
-
-fn log_result(
-    round_id: u64,
-    our_squares: &[usize],
-    winning_square: Option<usize>,
-    ore_won: u64,
-    sol_won: u64,
-    won: bool,
-    previous_winner: Option<usize>,
-    amount: u64,
-) {
-    let prev_winner_included = if let Some(prev) = previous_winner {
-        our_squares.contains(&prev)
-    } else {
-        false
+    let config = DriverConfig {
+        max_rounds: None,
+        log_file: "optimal_ev_results.jsonl".to_string(),
+        priority_fee: fee,
+        guard,
+        predict,
     };
-
-    let result = json!({
-        "timestamp": Utc::now().to_rfc3339(),
-        "round_id": round_id,
-        "strategy": "optimal_ev_v2",
-        "our_squares": our_squares,
-        "our_squares_display": our_squares.iter().map(|&s| s + 1).collect::<Vec<_>>(),
-        "previous_winner": previous_winner,
-        "previous_winner_display": previous_winner.map(|w| w + 1),
-        "previous_winner_included": prev_winner_included,
-        "previous_winner_filtered": false,  // We NO LONGER filter previous winner!
-        "winning_squares": winning_square.map(|w| vec![w]),
-        "winning_square_display": winning_square.map(|w| w + 1),
-        "amount_deployed_lamports": amount,
-        "amount_deployed_sol": amount as f64 / 1e9,
-        "ore_won_lamports": ore_won,
-        "ore_won": ore_won as f64 / ONE_ORE,
-        "sol_won_lamports": sol_won,
-        "sol_won": sol_won as f64 / 1e9,
-        "won": won,
-        "had_hot_hand_edge": prev_winner_included,
-    });
-
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("optimal_ev_results.jsonl")
-    {
-        let _ = writeln!(file, "{}", result);
-    }
+    run_strategy(rpc, payer, &mut strategy, config).await
 }
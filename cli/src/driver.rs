@@ -0,0 +1,549 @@
+/// Generic deployment loop for any `Strategy`
+///
+/// Each round: checkpoint the last round we played, wait for the strategy's deploy window,
+/// fetch the latest state, deploy the strategy's allocation, and record the outcome once the
+/// round has been reset.
+
+use anyhow::Result;
+use chrono::Utc;
+use ore_api::consts::ONE_ORE;
+use ore_api::error::OreError;
+use ore_api::instruction::DeployGuard;
+use ore_api::sim;
+use ore_api::state::{board_pda, miner_pda, Miner, Round};
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+use solana_sdk::signer::keypair::Keypair;
+use std::fs::OpenOptions;
+use std::io::Write as IoWrite;
+
+use crate::args::{GuardArgs, PredictArgs};
+use crate::fees::{CachedFee, PriorityFee, DEPLOY_COMPUTE_UNIT_LIMIT};
+use crate::indexer::read_events;
+use crate::inflow::{round_series, InflowModel};
+use crate::strategy::{DeployTiming, RoundRecord, Strategy, StrategyInput};
+use crate::websocket::WebSocketManager;
+use crate::{
+    get_board, get_clock, get_miner, get_mint_supply, get_round, get_treasury, submit_transaction,
+    submit_transaction_with_budget,
+};
+
+/// Number of past rounds handed to strategies.
+const HISTORY_LEN: usize = 100;
+
+/// Times to try checkpointing the last round once `max_rounds` have been played.
+const FINAL_CHECKPOINT_ATTEMPTS: u32 = 30;
+
+pub struct DriverConfig {
+    /// Stop after this many deployed rounds. None runs forever.
+    pub max_rounds: Option<u64>,
+
+    /// JSONL file round results are appended to.
+    pub log_file: String,
+
+    /// Priority fee for deploys. Checkpoints are not time sensitive and use the default.
+    pub priority_fee: PriorityFee,
+
+    /// Limits on how much the board may change between fetching the round and the deploy landing.
+    pub guard: GuardArgs,
+
+    /// Whether strategies see the current pools or those predicted for the end of the round.
+    pub predict: PredictArgs,
+}
+
+/// Runs a strategy round after round.
+pub async fn run_strategy<S: Strategy>(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    strategy: &mut S,
+    config: DriverConfig,
+) -> Result<()> {
+    let ws_manager = WebSocketManager::new(&rpc.url());
+
+    println!("🔌 Starting WebSocket connections...");
+    ws_manager.subscribe_to_board().await?;
+    ws_manager.subscribe_to_slots().await?;
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+    println!("🎯 Strategy: {}", strategy.name());
+
+    // Price deploys in the background, so the last seconds of a round are not spent on the RPC.
+    let accounts = vec![board_pda().0, miner_pda(payer.pubkey()).0];
+    let fee = CachedFee::spawn(rpc, config.priority_fee, accounts).await?;
+
+    let mut history: Vec<RoundRecord> = Vec::new();
+    let mut rounds_played = 0u64;
+    let mut rounds_won = 0u64;
+    let mut final_attempts = 0u32;
+
+    loop {
+        // Checkpoint the last round we played before anything else.
+        if let Some(record) = checkpoint_previous(rpc, payer, &mut history).await? {
+            rounds_won += u64::from(record.won());
+            report_record(
+                &config.log_file,
+                &strategy.name(),
+                &record,
+                rounds_won,
+                rounds_played,
+            );
+            if config.max_rounds.is_some_and(|max| rounds_played >= max) {
+                return Ok(());
+            }
+        }
+
+        // Once the round budget is spent, stop when the last round has been checkpointed, here
+        // or elsewhere, or after a bounded number of attempts.
+        if config.max_rounds.is_some_and(|max| rounds_played >= max) {
+            if let Some(record) = checkpointed_elsewhere(rpc, payer, &mut history).await? {
+                rounds_won += u64::from(record.won());
+                report_record(
+                    &config.log_file,
+                    &strategy.name(),
+                    &record,
+                    rounds_won,
+                    rounds_played,
+                );
+                return Ok(());
+            }
+            final_attempts += 1;
+            if final_attempts >= FINAL_CHECKPOINT_ATTEMPTS {
+                println!("⚠️  Stopping without the outcome of the last round");
+                return Ok(());
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            continue;
+        }
+
+        // Wait for an active round.
+        let board = match ws_manager.wait_for_round_start(600).await {
+            Ok(board) => board,
+            Err(e) => {
+                println!("⚠️  {}", e);
+                continue;
+            }
+        };
+
+        // Wait for the strategy's deploy window.
+        match strategy.timing() {
+            DeployTiming::Now => {}
+            DeployTiming::Window {
+                max_seconds,
+                min_seconds,
+            } => {
+                println!(
+                    "⏰ Round #{} active - waiting for {}-{}s window...",
+                    board.round_id, max_seconds, min_seconds
+                );
+                ws_manager
+                    .wait_for_deploy_window(max_seconds, min_seconds)
+                    .await;
+            }
+        }
+        if ws_manager
+            .get_seconds_remaining()
+            .await
+            .is_some_and(|s| s <= 0.0)
+        {
+            println!("❌ Round #{} ended before deployment", board.round_id);
+            ws_manager.wait_for_round_reset(board.round_id, 120).await.ok();
+            continue;
+        }
+
+        // Fetch the latest state right before deploying.
+        let board = get_board(rpc).await?;
+        if board.end_slot == u64::MAX {
+            continue;
+        }
+        let round = match get_round(rpc, board.round_id).await {
+            Ok(round) => round,
+            Err(e) => {
+                println!("❌ Failed to fetch round #{}: {}", board.round_id, e);
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        let treasury = get_treasury(rpc).await?;
+        let mint_supply = get_mint_supply(rpc).await?;
+        let miner = get_miner(rpc, payer.pubkey()).await.ok();
+        let mut crowd = round;
+        if config.predict.predict_crowd {
+            let slots_remaining = board.end_slot.saturating_sub(get_clock(rpc).await?.slot);
+            if let Some(predicted) = predicted_round(&config.predict, &round, slots_remaining) {
+                crowd = predicted;
+            }
+        }
+
+        // Ask the strategy for an allocation.
+        let input = StrategyInput {
+            board: &board,
+            round: &crowd,
+            treasury: &treasury,
+            mint_supply,
+            miner: miner.as_ref(),
+            history: &history,
+            excluded: &[false; 25],
+        };
+        let amounts = strategy.allocate(&input);
+        if amounts.iter().all(|&a| a == 0) {
+            println!("⏭️  Skipping round #{}", board.round_id);
+            ws_manager.wait_for_round_reset(board.round_id, 120).await.ok();
+            continue;
+        }
+
+        // Deploy.
+        for (i, &amount) in amounts.iter().enumerate() {
+            if amount > 0 {
+                println!(
+                    "   Square #{:2}: {:.4} SOL pool + {:.4} SOL",
+                    i + 1,
+                    round.deployed[i] as f64 / 1e9,
+                    amount as f64 / 1e9
+                );
+            }
+        }
+        let returns = input.expected_returns(&amounts);
+        println!(
+            "   Expected: {:.6} SOL ({:+.6}), {:.6} ORE",
+            returns.expected_sol() as f64 / 1e9,
+            returns.expected_sol_profit() as f64 / 1e9,
+            returns.expected_ore() as f64 / ONE_ORE as f64
+        );
+        // If the checkpoint above failed, for example after a crash, the deploy checkpoints too.
+        let checkpoint = pending_checkpoint(miner.as_ref(), board.round_id);
+        let guard = deploy_guard(rpc, &config.guard, &round).await?;
+        let ixs = deploy_instructions(payer, board.round_id, &amounts, checkpoint, guard);
+        let budget = fee.compute_budget(DEPLOY_COMPUTE_UNIT_LIMIT);
+        match submit_transaction_with_budget(rpc, payer, &ixs, budget).await {
+            Ok(_) => {
+                let miner_after = get_miner(rpc, payer.pubkey()).await.ok();
+
+                // A checkpoint bundled into the deploy settled the previous round.
+                if let (Some(round_id), Some(before), Some(after)) =
+                    (checkpoint, miner.as_ref(), miner_after.as_ref())
+                {
+                    let pending = history.last_mut().filter(|r| r.round_id == round_id);
+                    if let Some(pending) = pending.filter(|_| after.checkpoint_id == round_id) {
+                        let record = settle_pending(rpc, pending, before, after).await;
+                        rounds_won += u64::from(record.won());
+                        report_record(
+                            &config.log_file,
+                            &strategy.name(),
+                            &record,
+                            rounds_won,
+                            rounds_played,
+                        );
+                    }
+                }
+                rounds_played += 1;
+
+                // The program skips squares the miner cannot top up or the guard rejects, so
+                // record what actually landed.
+                let amounts = match miner_after {
+                    Some(miner) if miner.round_id == board.round_id => {
+                        let skipped = skipped_squares(&amounts, &miner.deployed);
+                        if !skipped.is_empty() {
+                            println!("⚠️  Skipped squares {:?}", skipped);
+                        }
+                        miner.deployed
+                    }
+                    _ => amounts,
+                };
+                println!(
+                    "✅ Deployed {:.4} SOL to round #{}",
+                    amounts.iter().sum::<u64>() as f64 / 1e9,
+                    board.round_id
+                );
+                history.push(RoundRecord {
+                    round_id: board.round_id,
+                    amounts,
+                    winning_square: None,
+                    sol_won: 0,
+                    ore_won: 0,
+                });
+                if history.len() > HISTORY_LEN {
+                    history.remove(0);
+                }
+            }
+            Err(e) => {
+                println!("❌ Deployment failed: {}", e);
+                match e.downcast_ref::<OreError>() {
+                    // The checkpoint at the top of the loop catches the miner up.
+                    Some(OreError::NotCheckpointed) => {}
+                    Some(
+                        OreError::RoundNotActive
+                        | OreError::DeployDeadlinePassed
+                        | OreError::DeployGuardFailed,
+                    ) => {
+                        ws_manager.wait_for_round_reset(board.round_id, 120).await.ok();
+                    }
+                    _ => tokio::time::sleep(tokio::time::Duration::from_secs(5)).await,
+                }
+                continue;
+            }
+        }
+
+        // Wait for the round to end.
+        println!("⏳ Waiting for round to end...\n");
+        if let Err(e) = ws_manager.wait_for_round_reset(board.round_id, 120).await {
+            println!("⚠️  Error waiting for round end: {}", e);
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+}
+
+/// The round with the final pools the crowd inflow model predicts, fit on the latest indexed
+/// rounds. None if too few rounds have been indexed.
+fn predicted_round(args: &PredictArgs, round: &Round, slots_remaining: u64) -> Option<Round> {
+    let rounds = round_series(&read_events(&args.index_file));
+    let recent = &rounds[rounds.len().saturating_sub(args.history_rounds)..];
+    let Some(model) = InflowModel::fit(recent, slots_remaining) else {
+        println!("⚠️  Too few indexed rounds to predict the crowd, using current pools");
+        return None;
+    };
+    let prediction = model.predict(&round.deployed);
+    println!(
+        "🔮 {:.4} ± {:.4} SOL expected in the last {} slots",
+        prediction.inflow(&round.deployed) as f64 / 1e9,
+        round.total_deployed as f64 * model.growth_std / 1e9,
+        model.slots_remaining
+    );
+    let mut predicted = *round;
+    predicted.deployed = prediction.mean;
+    predicted.total_deployed = prediction.mean.iter().sum();
+    Some(predicted)
+}
+
+/// Squares, numbered from 1 like the logs, where less landed than was requested.
+fn skipped_squares(requested: &[u64; 25], deployed: &[u64; 25]) -> Vec<usize> {
+    (0..25)
+        .filter(|&i| requested[i] > 0 && deployed[i] < requested[i])
+        .map(|i| i + 1)
+        .collect()
+}
+
+/// Returns the miner's last round if it still needs a checkpoint before deploying in `round_id`.
+pub fn pending_checkpoint(miner: Option<&Miner>, round_id: u64) -> Option<u64> {
+    let miner = miner?;
+    if miner.checkpoint_id >= miner.round_id || miner.round_id >= round_id {
+        return None;
+    }
+    Some(miner.round_id)
+}
+
+/// Builds the guard for a deploy allocated from `round`, or None if every limit is off. Square
+/// pools are bounded relative to `round`, and the deadline to the slot it was fetched at.
+pub async fn deploy_guard(
+    rpc: &RpcClient,
+    args: &GuardArgs,
+    round: &Round,
+) -> Result<Option<DeployGuard>> {
+    if args.max_slippage.is_none() && args.min_share == 0 && args.max_delay_slots.is_none() {
+        return Ok(None);
+    }
+    let max_slot = match args.max_delay_slots {
+        Some(delay) => get_clock(rpc).await?.slot + delay,
+        None => 0,
+    };
+    Ok(Some(DeployGuard::new(
+        max_slot,
+        max_deployed(round, args.max_slippage),
+        args.min_share,
+        args.strict_guard,
+    )))
+}
+
+/// The most SOL each square may hold for the deploy to go through, `slippage` basis points above
+/// what it held in `round`.
+fn max_deployed(round: &Round, slippage: Option<u64>) -> [u64; 25] {
+    let Some(slippage) = slippage else {
+        return [u64::MAX; 25];
+    };
+    round.deployed.map(|deployed| {
+        let max = deployed as u128 * (10_000 + slippage as u128) / 10_000;
+        max.min(u64::MAX as u128) as u64
+    })
+}
+
+/// Builds the deploy instruction for a per-square allocation. Uniform allocations use the
+/// plain mask-based deploy; anything else uses the weighted variant. If `checkpoint` is set,
+/// the deploy checkpoints that round first, and if `guard` is set, it is bounded by the guard.
+pub fn deploy_instructions(
+    payer: &Keypair,
+    round_id: u64,
+    amounts: &[u64; 25],
+    checkpoint: Option<u64>,
+    guard: Option<DeployGuard>,
+) -> Vec<Instruction> {
+    let mut squares = [false; 25];
+    let mut uniform = None;
+    let mut is_uniform = true;
+    for (i, &amount) in amounts.iter().enumerate() {
+        if amount > 0 {
+            squares[i] = true;
+            is_uniform &= *uniform.get_or_insert(amount) == amount;
+        }
+    }
+    let ix = match uniform {
+        None => return vec![],
+        Some(amount) if is_uniform => {
+            ore_api::sdk::deploy(payer.pubkey(), payer.pubkey(), amount, round_id, squares)
+        }
+        Some(_) => {
+            ore_api::sdk::deploy_weighted(payer.pubkey(), payer.pubkey(), round_id, *amounts)
+        }
+    };
+    let ix = match checkpoint {
+        Some(previous_round_id) => ore_api::sdk::with_checkpoint(ix, previous_round_id),
+        None => ix,
+    };
+    match guard {
+        Some(guard) => vec![ore_api::sdk::with_guard(ix, guard)],
+        None => vec![ix],
+    }
+}
+
+/// Checkpoints the miner's last round if needed. If that round is the pending entry at the end
+/// of `history`, its outcome is filled in and returned.
+pub async fn checkpoint_previous(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    history: &mut [RoundRecord],
+) -> Result<Option<RoundRecord>> {
+    let Ok(miner_before) = get_miner(rpc, payer.pubkey()).await else {
+        // No miner account yet.
+        return Ok(None);
+    };
+    let board = get_board(rpc).await?;
+    if miner_before.checkpoint_id == miner_before.round_id
+        || miner_before.round_id >= board.round_id
+    {
+        return Ok(None);
+    }
+
+    // Submit checkpoint.
+    let round_id = miner_before.round_id;
+    println!("📝 Checkpointing round #{}...", round_id);
+    let ix = ore_api::sdk::checkpoint(payer.pubkey(), payer.pubkey(), round_id);
+    if let Err(e) = submit_transaction(rpc, payer, &[ix], PriorityFee::default()).await {
+        println!("⚠️  Checkpoint failed: {}", e);
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        return Ok(None);
+    }
+
+    // Wait for confirmation.
+    let mut miner_after = miner_before;
+    for _ in 0..10 {
+        if let Ok(miner) = get_miner(rpc, payer.pubkey()).await {
+            miner_after = miner;
+            if miner.checkpoint_id == round_id {
+                break;
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+    if miner_after.checkpoint_id != round_id {
+        println!("⚠️  Checkpoint for round #{} not confirmed", round_id);
+        return Ok(None);
+    }
+
+    // Only report rounds this driver deployed in.
+    let Some(pending) = history.last_mut().filter(|r| r.round_id == round_id) else {
+        return Ok(None);
+    };
+    Ok(Some(
+        settle_pending(rpc, pending, &miner_before, &miner_after).await,
+    ))
+}
+
+/// Fills in the outcome of `pending` from its round and the miner's rewards before and after the
+/// round was checkpointed.
+async fn settle_pending(
+    rpc: &RpcClient,
+    pending: &mut RoundRecord,
+    before: &Miner,
+    after: &Miner,
+) -> RoundRecord {
+    pending.winning_square = get_round(rpc, pending.round_id)
+        .await
+        .ok()
+        .and_then(|round| round.rng().map(|rng| round.winning_square(rng)));
+    pending.sol_won = after.rewards_sol.saturating_sub(before.rewards_sol);
+    pending.ore_won = after.rewards_ore.saturating_sub(before.rewards_ore);
+    *pending
+}
+
+/// The outcome of the pending round at the end of `history` if someone else checkpointed it,
+/// such as a keeper. The rewards are computed from the round and the miner's deployment, which
+/// stay in place until the miner deploys again.
+async fn checkpointed_elsewhere(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    history: &mut [RoundRecord],
+) -> Result<Option<RoundRecord>> {
+    let Some(pending) = history.last_mut() else {
+        return Ok(None);
+    };
+    let Ok(miner) = get_miner(rpc, payer.pubkey()).await else {
+        return Ok(None);
+    };
+    if miner.round_id != pending.round_id || miner.checkpoint_id != pending.round_id {
+        return Ok(None);
+    }
+    let Ok(round) = get_round(rpc, pending.round_id).await else {
+        return Ok(None);
+    };
+    let rewards = sim::miner_rewards(&round, &miner);
+    pending.winning_square = round.rng().map(|rng| round.winning_square(rng));
+    pending.sol_won = rewards.rewards_sol;
+    pending.ore_won = rewards.rewards_ore();
+    Ok(Some(*pending))
+}
+
+/// Prints the outcome of a round and the running stats, and appends it to the log.
+fn report_record(
+    log_file: &str,
+    strategy: &str,
+    record: &RoundRecord,
+    rounds_won: u64,
+    rounds_played: u64,
+) {
+    if record.won() {
+        println!(
+            "✅ WON round #{}: +{:.9} SOL, +{:.11} ORE",
+            record.round_id,
+            record.sol_won as f64 / 1e9,
+            record.ore_won as f64 / 1e11
+        );
+    } else {
+        println!("❌ Lost round #{}", record.round_id);
+    }
+    log_record(log_file, strategy, record);
+    println!(
+        "📊 Stats: {}/{} wins ({:.1}%)\n",
+        rounds_won,
+        rounds_played,
+        rounds_won as f64 / rounds_played as f64 * 100.0
+    );
+}
+
+pub fn log_record(log_file: &str, strategy: &str, record: &RoundRecord) {
+    let squares: Vec<usize> = (0..25).filter(|&i| record.amounts[i] > 0).collect();
+    let entry = json!({
+        "timestamp": Utc::now().to_rfc3339(),
+        "round_id": record.round_id,
+        "strategy": strategy,
+        "our_squares": squares,
+        "amounts": record.amounts,
+        "amount_deployed_lamports": record.deployed(),
+        "winning_square": record.winning_square,
+        "sol_won_lamports": record.sol_won,
+        "ore_won": record.ore_won,
+        "won": record.won(),
+    });
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_file) {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
@@ -0,0 +1,423 @@
+/// Executor daemon for on-chain Automation accounts
+///
+/// An automation lets its authority prepay deploys that a third-party executor signs, with the
+/// automation's `fee` paid to the executor on each one. Every round this daemon finds the
+/// automations naming our key as executor and deploys for each of them in batches, checkpointing
+/// their miners in the same instruction if needed. The program picks the squares and amounts
+/// from the automation itself, so the executor only decides when to send. Least crowded
+/// automations are sent late in the round, so their squares are picked against a fuller board.
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::json;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    instruction::Instruction, pubkey::Pubkey, rent::Rent, signature::Signer,
+    signer::keypair::Keypair,
+};
+use std::fs::OpenOptions;
+use std::io::Write as IoWrite;
+use steel::{AccountDeserialize, Discriminator};
+
+use ore_api::prelude::*;
+
+use crate::fees::PriorityFee;
+use crate::websocket::WebSocketManager;
+use crate::{get_board, get_round, submit_transaction};
+
+/// The RPC returns at most this many accounts per `getMultipleAccounts` call.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Size of an automation account opened before limits were added.
+const LEGACY_AUTOMATION_LEN: usize = 8 + 104;
+
+pub struct ExecutorConfig {
+    /// Automations deployed per transaction. A failed batch is retried one by one.
+    pub batch_size: usize,
+
+    /// JSONL file per-round results are appended to.
+    pub log_file: String,
+
+    /// Seconds before the round ends to deploy least crowded automations.
+    pub late_seconds: u64,
+
+    pub priority_fee: PriorityFee,
+}
+
+/// A deploy for one automation this round.
+struct Job {
+    authority: Pubkey,
+    ix: Instruction,
+    fee: u64,
+    amount: u64,
+    /// Closes a finished automation instead of deploying.
+    closes: bool,
+}
+
+/// Outcome of one round of executions.
+#[derive(Debug, Default)]
+struct RoundStats {
+    automations: usize,
+    landed: usize,
+    failed: usize,
+    underfunded: usize,
+    closed: usize,
+    already_deployed: usize,
+    fees_earned: u64,
+    sol_deployed: u64,
+    /// Least crowded automations left for the late pass.
+    deferred: usize,
+}
+
+impl RoundStats {
+    /// Records a job whose transaction landed.
+    fn record(&mut self, job: &Job) {
+        if job.closes {
+            self.closed += 1;
+            return;
+        }
+        self.landed += 1;
+        self.fees_earned += job.fee;
+        self.sol_deployed += job.amount;
+    }
+
+    /// Adds the outcome of another pass over the same round.
+    fn merge(&mut self, other: RoundStats) {
+        self.automations += other.automations;
+        self.landed += other.landed;
+        self.failed += other.failed;
+        self.underfunded += other.underfunded;
+        self.closed += other.closed;
+        self.already_deployed += other.already_deployed;
+        self.fees_earned += other.fees_earned;
+        self.sol_deployed += other.sol_deployed;
+        self.deferred += other.deferred;
+    }
+}
+
+/// Deploys for every automation naming `payer` as executor, once per round.
+pub async fn run_executor(rpc: &RpcClient, payer: &Keypair, config: ExecutorConfig) -> Result<()> {
+    let ws_manager = WebSocketManager::new(&rpc.url());
+    println!("🔌 Starting WebSocket connections...");
+    ws_manager.subscribe_to_slots().await?;
+    ws_manager.subscribe_to_board().await?;
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+    println!("🤖 Executor: {}", payer.pubkey());
+    let mut total_fees = 0u64;
+    let mut total_deploys = 0usize;
+    loop {
+        let board = get_board(rpc).await?;
+        match execute_round(rpc, payer, board.round_id, &config, &ws_manager).await {
+            Ok(stats) => {
+                total_fees += stats.fees_earned;
+                total_deploys += stats.landed;
+                println!(
+                    "📊 Round #{}: {}/{} deployed, {} failed, {} underfunded, {} closed, {} already deployed",
+                    board.round_id,
+                    stats.landed,
+                    stats.automations,
+                    stats.failed,
+                    stats.underfunded,
+                    stats.closed,
+                    stats.already_deployed
+                );
+                println!(
+                    "💰 Fees: +{:.9} SOL this round, {:.9} SOL over {} deploys\n",
+                    stats.fees_earned as f64 / 1e9,
+                    total_fees as f64 / 1e9,
+                    total_deploys
+                );
+                log_stats(&config.log_file, board.round_id, &stats);
+            }
+            Err(e) => println!("⚠️  Round #{} failed: {}", board.round_id, e),
+        }
+
+        // Wait for the next round.
+        if let Err(e) = ws_manager.wait_for_round_reset(board.round_id, 600).await {
+            println!("⚠️  {}", e);
+        }
+    }
+}
+
+/// Deploys for the round in two passes. Least crowded automations wait for the second, late in
+/// the round, since the program picks their squares against the round as it is then.
+async fn execute_round(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    round_id: u64,
+    config: &ExecutorConfig,
+    ws_manager: &WebSocketManager,
+) -> Result<RoundStats> {
+    let mut stats = execute_pass(rpc, payer, round_id, config, false).await?;
+    if stats.deferred > 0 {
+        wait_for_late_window(rpc, ws_manager, config.late_seconds).await?;
+        stats.merge(execute_pass(rpc, payer, round_id, config, true).await?);
+    }
+    Ok(stats)
+}
+
+/// Waits until `seconds` before the round ends. Returns at once if no one has deployed yet, as
+/// the round only starts with its first deploy.
+async fn wait_for_late_window(
+    rpc: &RpcClient,
+    ws_manager: &WebSocketManager,
+    seconds: u64,
+) -> Result<()> {
+    if get_board(rpc).await?.end_slot == u64::MAX {
+        return Ok(());
+    }
+    ws_manager.wait_for_deploy_window(seconds, 0).await;
+    Ok(())
+}
+
+/// Deploys for the least crowded automations if `late`, or for every other one.
+async fn execute_pass(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    round_id: u64,
+    config: &ExecutorConfig,
+    late: bool,
+) -> Result<RoundStats> {
+    let automations = get_executor_automations(rpc, payer.pubkey()).await?;
+    let authorities: Vec<Pubkey> = automations.iter().map(|(a, _)| a.authority).collect();
+    let miners = get_miners_for(rpc, &authorities).await?;
+    let round = get_round(rpc, round_id).await?;
+    let slot = rpc.get_slot().await?;
+
+    // Plan a deploy for each automation that can pay for one.
+    let mut stats = RoundStats::default();
+    let mut jobs = Vec::new();
+    for ((automation, rent), miner) in automations.iter().zip(miners) {
+        let least_crowded = automation.strategy == AutomationStrategy::LeastCrowded as u64
+            && !automation.is_finished(slot);
+        if least_crowded != late {
+            stats.deferred += usize::from(least_crowded);
+            continue;
+        }
+        stats.automations += 1;
+
+        // Close finished automations, returning their balance to the authority. The closing
+        // deploy pays no fee, and goes through whatever the balance.
+        if automation.is_finished(slot) {
+            jobs.push(Job {
+                authority: automation.authority,
+                ix: ore_api::sdk::deploy(
+                    payer.pubkey(),
+                    automation.authority,
+                    automation.amount,
+                    round_id,
+                    [false; 25],
+                ),
+                fee: 0,
+                amount: 0,
+                closes: true,
+            });
+            continue;
+        }
+
+        // Automations opened before limits were added pay to grow their account first.
+        if automation.balance < rent + automation.amount + automation.fee
+            || automation.round_budget(round_id) < automation.amount + automation.fee
+        {
+            stats.underfunded += 1;
+            continue;
+        }
+
+        // The program allows one deploy per automation per round.
+        if automation.round_id == round_id {
+            stats.already_deployed += 1;
+            continue;
+        }
+
+        // Least crowded automations are resolved against the round when the deploy executes, so
+        // these squares are only an estimate for them.
+        let squares = automation.squares(&round);
+        let mut ix = ore_api::sdk::deploy(
+            payer.pubkey(),
+            automation.authority,
+            automation.amount,
+            round_id,
+            squares,
+        );
+        if let Some(miner) = miner.filter(|m| m.checkpoint_id < m.round_id) {
+            ix = ore_api::sdk::with_checkpoint(ix, miner.round_id);
+        }
+        jobs.push(Job {
+            authority: automation.authority,
+            ix,
+            fee: automation.fee,
+            amount: expected_deploy(automation, *rent, round_id, &squares),
+            closes: false,
+        });
+    }
+
+    // Submit in batches, falling back to one at a time so one bad account does not sink the rest.
+    for batch in jobs.chunks(config.batch_size.max(1)) {
+        let ixs: Vec<Instruction> = batch.iter().map(|j| j.ix.clone()).collect();
+        if batch.len() > 1
+            && submit_transaction(rpc, payer, &ixs, config.priority_fee)
+                .await
+                .is_ok()
+        {
+            for job in batch {
+                stats.record(job);
+            }
+            continue;
+        }
+        for job in batch {
+            match submit_transaction(rpc, payer, &[job.ix.clone()], config.priority_fee).await {
+                Ok(_) => stats.record(job),
+                Err(e) => {
+                    println!("❌ Deploy for {} failed: {}", job.authority, e);
+                    stats.failed += 1;
+                }
+            }
+        }
+    }
+    Ok(stats)
+}
+
+/// Lamports the program will deploy for an automation, which stops early once the balance or
+/// the round's budget cannot cover another square plus the fee. `rent` is taken from the balance
+/// first, to grow an account opened before limits were added.
+fn expected_deploy(automation: &Automation, rent: u64, round_id: u64, squares: &[bool; 25]) -> u64 {
+    let limit = automation
+        .balance
+        .saturating_sub(rent)
+        .min(automation.round_budget(round_id));
+    let mut total = 0;
+    for _ in squares.iter().filter(|&&s| s) {
+        total += automation.amount;
+        if total + automation.fee + automation.amount > limit {
+            break;
+        }
+    }
+    total
+}
+
+/// Fetches the automations naming `executor`, each with the rent its next deploy takes from its
+/// balance. Accounts opened before limits were added are read with no limits, as the program
+/// grows them that way.
+async fn get_executor_automations(
+    rpc: &RpcClient,
+    executor: Pubkey,
+) -> Result<Vec<(Automation, u64)>> {
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            &Automation::discriminator().to_le_bytes(),
+        )),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(56, &executor.to_bytes())),
+    ];
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let size = 8 + std::mem::size_of::<Automation>();
+    let rent = Rent::default();
+    let mut automations = Vec::new();
+    for (_, account) in rpc
+        .get_program_accounts_with_config(&ore_api::ID, config)
+        .await?
+    {
+        let mut data = account.data;
+        let grow_rent = if data.len() == LEGACY_AUTOMATION_LEN {
+            data.resize(size, 0);
+            rent.minimum_balance(size) - rent.minimum_balance(LEGACY_AUTOMATION_LEN)
+        } else {
+            0
+        };
+        let Ok(automation) = Automation::try_from_bytes(&data) else {
+            continue;
+        };
+        let mut automation = *automation;
+        if grow_rent > 0 {
+            automation.round_id = u64::MAX;
+        }
+        automations.push((automation, grow_rent));
+    }
+    Ok(automations)
+}
+
+/// Fetches the miner of each authority, in order. Missing miners are None.
+async fn get_miners_for(rpc: &RpcClient, authorities: &[Pubkey]) -> Result<Vec<Option<Miner>>> {
+    let mut miners = Vec::with_capacity(authorities.len());
+    for chunk in authorities.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let addresses: Vec<Pubkey> = chunk.iter().map(|a| miner_pda(*a).0).collect();
+        for account in rpc.get_multiple_accounts(&addresses).await? {
+            miners.push(account.and_then(|a| Miner::try_from_bytes(&a.data).ok().copied()));
+        }
+    }
+    Ok(miners)
+}
+
+fn log_stats(log_file: &str, round_id: u64, stats: &RoundStats) {
+    let entry = json!({
+        "timestamp": Utc::now().to_rfc3339(),
+        "round_id": round_id,
+        "automations": stats.automations,
+        "landed": stats.landed,
+        "failed": stats.failed,
+        "underfunded": stats.underfunded,
+        "closed": stats.closed,
+        "already_deployed": stats.already_deployed,
+        "fees_earned_lamports": stats.fees_earned,
+        "sol_deployed_lamports": stats.sol_deployed,
+    });
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_file) {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    #[test]
+    fn test_expected_deploy() {
+        let mut automation = Automation::zeroed();
+        automation.amount = 10;
+        automation.fee = 1;
+        automation.balance = 1_000;
+        let mut squares = [false; 25];
+        squares[..5].fill(true);
+        assert_eq!(expected_deploy(&automation, 0, 0, &squares), 50);
+
+        // Only enough for 3 squares plus the fee.
+        automation.balance = 35;
+        assert_eq!(expected_deploy(&automation, 0, 0, &squares), 30);
+
+        // Only 2 squares fit under the per-round limit.
+        automation.balance = 1_000;
+        automation.max_per_round = 25;
+        assert_eq!(expected_deploy(&automation, 0, 0, &squares), 20);
+
+        // Growing a legacy account leaves enough for 3 squares plus the fee.
+        automation.max_per_round = 0;
+        assert_eq!(expected_deploy(&automation, 965, 0, &squares), 30);
+    }
+
+    #[test]
+    fn test_random_squares() {
+        let mut automation = Automation::zeroed();
+        automation.strategy = AutomationStrategy::Random as u64;
+        automation.mask = 7;
+        let mut round = Round::zeroed();
+        for round_id in 0..20 {
+            round.id = round_id;
+            let squares = automation.squares(&round);
+            assert_eq!(squares.iter().filter(|&&s| s).count(), 7);
+        }
+    }
+}
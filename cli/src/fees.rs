@@ -0,0 +1,280 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+use anyhow::Result;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+    transaction::Transaction,
+};
+
+/// Compute unit limit used when simulation fails, and the most a transaction can request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Compute unit limit for deploys, which are not simulated. Covers a deploy that starts the round
+/// and checkpoints the previous one in the same transaction.
+pub const DEPLOY_COMPUTE_UNIT_LIMIT: u32 = 300_000;
+
+/// Seconds between refreshes of a cached fee.
+const CACHE_REFRESH_SECONDS: u64 = 2;
+
+/// Default price for commands that are not time sensitive, in micro-lamports per CU.
+const DEFAULT_STATIC_PRICE: u64 = 10_000;
+
+/// Default cap for deploys, in micro-lamports per CU.
+const DEFAULT_DEPLOY_MAX_PRICE: u64 = 2_000_000;
+
+/// The RPC accepts at most this many accounts in `getRecentPrioritizationFees`.
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// How the compute unit price of a transaction is chosen.
+///
+/// Parsed from `static:<price>`, `percentile:<p>` or `dynamic:<p>:<max>`, with prices in
+/// micro-lamports per compute unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub enum PriorityFee {
+    /// A fixed price.
+    Static(u64),
+
+    /// The p-th percentile of recent fees paid to write lock the transaction's accounts.
+    Percentile(u8),
+
+    /// Like `Percentile`, but never above `max`.
+    Dynamic { percentile: u8, max: u64 },
+}
+
+impl Default for PriorityFee {
+    fn default() -> Self {
+        PriorityFee::Static(DEFAULT_STATIC_PRICE)
+    }
+}
+
+impl PriorityFee {
+    /// Built-in policy for a subcommand. Deploys compete for the last slots of a round, so they
+    /// follow recent fees. Everything else pays a low static price.
+    pub fn for_command(command: &str) -> PriorityFee {
+        if command.starts_with("deploy") || command == "fleet" {
+            PriorityFee::Dynamic {
+                percentile: 75,
+                max: DEFAULT_DEPLOY_MAX_PRICE,
+            }
+        } else {
+            PriorityFee::default()
+        }
+    }
+
+    /// Resolves the compute unit price for a transaction with the given instructions.
+    pub async fn price(&self, rpc: &RpcClient, instructions: &[Instruction]) -> Result<u64> {
+        self.price_for_accounts(rpc, &writable_accounts(instructions))
+            .await
+    }
+
+    /// Resolves the compute unit price for a transaction that write locks `accounts`.
+    async fn price_for_accounts(&self, rpc: &RpcClient, accounts: &[Pubkey]) -> Result<u64> {
+        let (percentile, max) = match *self {
+            PriorityFee::Static(price) => return Ok(price),
+            PriorityFee::Percentile(percentile) => (percentile, u64::MAX),
+            PriorityFee::Dynamic { percentile, max } => (percentile, max),
+        };
+        let mut fees: Vec<u64> = rpc
+            .get_recent_prioritization_fees(accounts)
+            .await?
+            .into_iter()
+            .map(|f| f.prioritization_fee)
+            .collect();
+        Ok(percentile_of(&mut fees, percentile).min(max))
+    }
+}
+
+impl FromStr for PriorityFee {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let invalid = || format!("Invalid priority fee: {}", spec);
+        let percentile = |s: &str| match s.parse::<u8>() {
+            Ok(p) if p <= 100 => Ok(p),
+            _ => Err(invalid()),
+        };
+        match parts.as_slice() {
+            ["static", price] => Ok(PriorityFee::Static(price.parse().map_err(|_| invalid())?)),
+            ["percentile", p] => Ok(PriorityFee::Percentile(percentile(p)?)),
+            ["dynamic", p, max] => Ok(PriorityFee::Dynamic {
+                percentile: percentile(p)?,
+                max: max.parse().map_err(|_| invalid())?,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl TryFrom<String> for PriorityFee {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+/// Returns the compute budget instructions to prepend to `instructions`.
+///
+/// The unit limit is the simulated usage plus a margin. If simulation fails, the maximum limit is
+/// used and the error is left for the send to report.
+pub async fn compute_budget(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    fee: PriorityFee,
+) -> Result<[Instruction; 2]> {
+    let limit = simulate_compute_units(rpc, payer, instructions)
+        .await
+        .map(with_margin)
+        .unwrap_or(MAX_COMPUTE_UNIT_LIMIT);
+    let price = fee.price(rpc, instructions).await?;
+    Ok([
+        ComputeBudgetInstruction::set_compute_unit_limit(limit),
+        ComputeBudgetInstruction::set_compute_unit_price(price),
+    ])
+}
+
+/// A priority fee kept up to date in the background, for deploys that cannot wait on
+/// `getRecentPrioritizationFees` in the last seconds of a round.
+#[derive(Clone, Debug)]
+pub struct CachedFee {
+    price: Arc<AtomicU64>,
+}
+
+impl CachedFee {
+    /// Resolves the fee for transactions that write lock `accounts`, then refreshes it every few
+    /// seconds until the last clone is dropped. Static fees are never refreshed.
+    pub async fn spawn(
+        rpc: &RpcClient,
+        fee: PriorityFee,
+        mut accounts: Vec<Pubkey>,
+    ) -> Result<Self> {
+        accounts.truncate(MAX_FEE_ACCOUNTS);
+        let price = Arc::new(AtomicU64::new(
+            fee.price_for_accounts(rpc, &accounts).await?,
+        ));
+        if !matches!(fee, PriorityFee::Static(_)) {
+            tokio::spawn(refresh(rpc.url(), fee, accounts, Arc::downgrade(&price)));
+        }
+        Ok(CachedFee { price })
+    }
+
+    /// The compute budget instructions for a transaction using at most `limit` compute units.
+    pub fn compute_budget(&self, limit: u32) -> [Instruction; 2] {
+        [
+            ComputeBudgetInstruction::set_compute_unit_limit(limit),
+            ComputeBudgetInstruction::set_compute_unit_price(self.price.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
+async fn refresh(url: String, fee: PriorityFee, accounts: Vec<Pubkey>, price: Weak<AtomicU64>) {
+    let rpc = RpcClient::new(url);
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(CACHE_REFRESH_SECONDS)).await;
+        let Some(price) = price.upgrade() else {
+            return;
+        };
+        // Keep the last price if the RPC fails.
+        if let Ok(p) = fee.price_for_accounts(&rpc, &accounts).await {
+            price.store(p, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Simulates the instructions with the maximum limit and returns the units consumed.
+async fn simulate_compute_units(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+) -> Option<u64> {
+    let mut all_instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+        ComputeBudgetInstruction::set_compute_unit_price(0),
+    ];
+    all_instructions.extend_from_slice(instructions);
+    let tx = Transaction::new_with_payer(&all_instructions, Some(payer));
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        ..Default::default()
+    };
+    let result = rpc
+        .simulate_transaction_with_config(&tx, config)
+        .await
+        .ok()?
+        .value;
+    if result.err.is_some() {
+        return None;
+    }
+    result.units_consumed
+}
+
+/// Adds 10% headroom for state that changes between simulation and landing.
+fn with_margin(units: u64) -> u32 {
+    let units = units + units / 10 + 1_000;
+    units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Accounts the transaction write locks, which is where fee markets form.
+fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| ix.accounts.iter()) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts.truncate(MAX_FEE_ACCOUNTS);
+    accounts
+}
+
+/// Nearest-rank percentile. Returns 0 if there are no samples.
+fn percentile_of(fees: &mut [u64], percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() * percentile as usize).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("static:5000".parse(), Ok(PriorityFee::Static(5000)));
+        assert_eq!("percentile:75".parse(), Ok(PriorityFee::Percentile(75)));
+        assert_eq!(
+            "dynamic:90:1000000".parse(),
+            Ok(PriorityFee::Dynamic {
+                percentile: 90,
+                max: 1_000_000
+            })
+        );
+        assert!("percentile:101".parse::<PriorityFee>().is_err());
+        assert!("dynamic:90".parse::<PriorityFee>().is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let mut fees = vec![0, 0, 10, 20, 30, 40, 50, 60, 70, 1_000];
+        assert_eq!(percentile_of(&mut fees, 50), 30);
+        assert_eq!(percentile_of(&mut fees, 75), 60);
+        assert_eq!(percentile_of(&mut fees, 100), 1_000);
+        assert_eq!(percentile_of(&mut fees, 0), 0);
+        assert_eq!(percentile_of(&mut [], 75), 0);
+    }
+
+    #[test]
+    fn test_margin() {
+        assert_eq!(with_margin(50_000), 56_000);
+        assert_eq!(with_margin(1_390_000), MAX_COMPUTE_UNIT_LIMIT);
+    }
+}
@@ -0,0 +1,469 @@
+/// Multi-wallet mining from one process
+///
+/// Every wallet runs its own strategy and budget, but all of them share one WebSocket feed and
+/// one view of the round. Wallets that deploy at the same time are allocated one after another,
+/// and each sees the squares the previous ones took as excluded, so the fleet spreads across the
+/// board instead of splitting its own share. Their transactions are then sent concurrently.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use futures_util::future::join_all;
+use ore_api::state::{board_pda, miner_pda};
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+
+use crate::args::GuardArgs;
+use crate::config::expand_home;
+use crate::driver::{
+    checkpoint_previous, deploy_guard, deploy_instructions, log_record, pending_checkpoint,
+};
+use crate::fees::{CachedFee, PriorityFee, DEPLOY_COMPUTE_UNIT_LIMIT};
+use crate::strategies::MiningStrategy;
+use crate::strategy::{
+    DeployTiming, LeastCrowded, RoundRecord, Selection, Strategy, StrategyInput,
+};
+use crate::websocket::WebSocketManager;
+use crate::{
+    get_board, get_miner, get_mint_supply, get_round, get_treasury, submit_transaction_with_budget,
+};
+
+/// Number of past rounds handed to strategies, per wallet.
+const HISTORY_LEN: usize = 100;
+
+/// A fleet file lists wallets with their own strategy and budget:
+///
+/// ```toml
+/// [[wallets]]
+/// keypair = "~/keys/bot1.json"
+/// amount = 5000000
+/// squares = 3
+///
+/// [[wallets]]
+/// keypair = "~/keys/bot2.json"
+/// amount = 2000000
+/// strategy = "late_snipe:3:5"
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FleetFile {
+    pub wallets: Vec<WalletSpec>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WalletSpec {
+    /// Path to the wallet keypair. `~/` is expanded.
+    pub keypair: String,
+
+    /// Lamports to deploy per square.
+    pub amount: u64,
+
+    /// Strategy spec, e.g. `contrarian:5:0.7`. Without one, the wallet deploys to the least
+    /// crowded squares not taken by the rest of the fleet.
+    #[serde(default, deserialize_with = "deserialize_strategy")]
+    pub strategy: Option<MiningStrategy>,
+
+    /// Number of least crowded squares. Ignored when `strategy` is set.
+    #[serde(default = "default_squares")]
+    pub squares: usize,
+}
+
+fn default_squares() -> usize {
+    2
+}
+
+fn deserialize_strategy<'de, D>(deserializer: D) -> Result<Option<MiningStrategy>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let spec = Option::<String>::deserialize(deserializer)?;
+    spec.map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl FleetFile {
+    pub fn read(path: &str) -> Result<FleetFile> {
+        let path = expand_home(path);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fleet file {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid fleet file {}", path.display()))
+    }
+}
+
+/// Expands keypair paths, replacing each directory with the `.json` files in it.
+pub fn keypair_paths(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut keypairs = Vec::new();
+    for path in paths {
+        let path = expand_home(path);
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = std::fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            files.sort();
+            keypairs.extend(files);
+        } else {
+            keypairs.push(path);
+        }
+    }
+    Ok(keypairs)
+}
+
+/// Loads the wallets listed in a fleet file, followed by `keypairs`, which all share the given
+/// strategy and budget. A wallet listed twice is an error.
+pub fn load_fleet(
+    file: Option<&str>,
+    keypairs: &[String],
+    amount: u64,
+    strategy: Option<MiningStrategy>,
+    squares: usize,
+) -> Result<Vec<FleetWallet>> {
+    let mut wallets: Vec<FleetWallet> = Vec::new();
+    if let Some(file) = file {
+        for spec in FleetFile::read(file)?.wallets {
+            let path = expand_home(&spec.keypair);
+            wallets.push(FleetWallet::load(
+                &path,
+                spec.amount,
+                spec.strategy,
+                spec.squares,
+            )?);
+        }
+    }
+    for path in keypair_paths(keypairs)? {
+        wallets.push(FleetWallet::load(&path, amount, strategy, squares)?);
+    }
+    for (i, wallet) in wallets.iter().enumerate() {
+        let pubkey = wallet.payer.pubkey();
+        if wallets[..i].iter().any(|w| w.payer.pubkey() == pubkey) {
+            return Err(anyhow!("Wallet {} is listed twice", pubkey));
+        }
+    }
+    Ok(wallets)
+}
+
+/// One wallet in the fleet.
+pub struct FleetWallet {
+    /// Name used in logs, the keypair file name.
+    pub name: String,
+    pub payer: Keypair,
+    pub strategy: Box<dyn Strategy>,
+    /// Rounds this wallet played, oldest first.
+    pub history: Vec<RoundRecord>,
+}
+
+impl FleetWallet {
+    pub fn load(
+        path: &Path,
+        amount: u64,
+        strategy: Option<MiningStrategy>,
+        squares: usize,
+    ) -> Result<Self> {
+        let payer = read_keypair_file(path)
+            .map_err(|e| anyhow!("Failed to read keypair {}: {}", path.display(), e))?;
+        let strategy: Box<dyn Strategy> = match strategy {
+            Some(strategy) => Box::new(Selection {
+                strategy,
+                amount_per_square: amount,
+            }),
+            None => Box::new(LeastCrowded {
+                count: squares,
+                amount_per_square: amount,
+                skip_previous_winner: false,
+                timing: DeployTiming::Window {
+                    max_seconds: 10,
+                    min_seconds: 0,
+                },
+            }),
+        };
+        Ok(FleetWallet {
+            name: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            payer,
+            strategy,
+            history: Vec::new(),
+        })
+    }
+
+    fn label(&self) -> String {
+        format!("{} ({})", self.name, self.strategy.name())
+    }
+}
+
+pub struct FleetConfig {
+    /// JSONL file round results are appended to.
+    pub log_file: String,
+
+    /// Priority fee for deploys.
+    pub priority_fee: PriorityFee,
+
+    /// Limits on how much the board may change before each wallet's deploy lands.
+    pub guard: GuardArgs,
+}
+
+/// Runs every wallet in the fleet round after round.
+pub async fn run_fleet(
+    rpc: &RpcClient,
+    mut wallets: Vec<FleetWallet>,
+    config: FleetConfig,
+) -> Result<()> {
+    if wallets.is_empty() {
+        return Err(anyhow!("Fleet has no wallets"));
+    }
+
+    // Deploy in timing order, earliest window first.
+    wallets.sort_by_key(|w| std::cmp::Reverse(window_start(w.strategy.timing())));
+
+    let ws_manager = WebSocketManager::new(&rpc.url());
+    println!("🔌 Starting WebSocket connections...");
+    ws_manager.subscribe_to_board().await?;
+    ws_manager.subscribe_to_slots().await?;
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+    println!("🚢 Fleet of {} wallets:", wallets.len());
+    for wallet in &wallets {
+        println!("   {} {}", wallet.payer.pubkey(), wallet.label());
+    }
+
+    // Price deploys in the background, so the last seconds of a round are not spent on the RPC.
+    let mut accounts = vec![board_pda().0];
+    accounts.extend(wallets.iter().map(|w| miner_pda(w.payer.pubkey()).0));
+    let fee = CachedFee::spawn(rpc, config.priority_fee, accounts).await?;
+
+    loop {
+        // Checkpoint each wallet's last round.
+        for wallet in wallets.iter_mut() {
+            match checkpoint_previous(rpc, &wallet.payer, &mut wallet.history).await {
+                Ok(Some(record)) => {
+                    if record.won() {
+                        println!(
+                            "✅ {} WON round #{}: +{:.9} SOL, +{:.11} ORE",
+                            wallet.name,
+                            record.round_id,
+                            record.sol_won as f64 / 1e9,
+                            record.ore_won as f64 / 1e11
+                        );
+                    } else {
+                        println!("❌ {} lost round #{}", wallet.name, record.round_id);
+                    }
+                    log_record(&config.log_file, &wallet.label(), &record);
+                }
+                Ok(None) => {}
+                Err(e) => println!("⚠️  {} checkpoint error: {}", wallet.name, e),
+            }
+        }
+
+        // Wait for an active round.
+        let board = match ws_manager.wait_for_round_start(600).await {
+            Ok(board) => board,
+            Err(e) => {
+                println!("⚠️  {}", e);
+                continue;
+            }
+        };
+        let round_id = board.round_id;
+
+        // Deploy each group of wallets that share a window.
+        let mut excluded = [false; 25];
+        let mut start = 0;
+        while start < wallets.len() {
+            let timing = wallets[start].strategy.timing();
+            let end = start
+                + wallets[start..]
+                    .iter()
+                    .take_while(|w| w.strategy.timing() == timing)
+                    .count();
+            if let DeployTiming::Window {
+                max_seconds,
+                min_seconds,
+            } = timing
+            {
+                ws_manager
+                    .wait_for_deploy_window(max_seconds, min_seconds)
+                    .await;
+            }
+            if ws_manager
+                .get_seconds_remaining()
+                .await
+                .is_some_and(|s| s <= 0.0)
+            {
+                println!("❌ Round #{} ended before deployment", round_id);
+                break;
+            }
+            if let Err(e) = deploy_group(
+                rpc,
+                &mut wallets[start..end],
+                round_id,
+                &mut excluded,
+                &fee,
+                &config.guard,
+            )
+            .await
+            {
+                println!("⚠️  Deploy failed: {}", e);
+            }
+            start = end;
+        }
+
+        // Wait for the round to end.
+        println!("⏳ Waiting for round #{} to end...\n", round_id);
+        if let Err(e) = ws_manager.wait_for_round_reset(round_id, 120).await {
+            println!("⚠️  Error waiting for round end: {}", e);
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+}
+
+/// Allocates for each wallet in turn against the same state, then sends all deploys at once.
+async fn deploy_group(
+    rpc: &RpcClient,
+    wallets: &mut [FleetWallet],
+    round_id: u64,
+    excluded: &mut [bool; 25],
+    fee: &CachedFee,
+    guard: &GuardArgs,
+) -> Result<()> {
+    let board = get_board(rpc).await?;
+    if board.round_id != round_id || board.end_slot == u64::MAX {
+        return Err(anyhow!("Round #{} is no longer active", round_id));
+    }
+    let round = get_round(rpc, round_id).await?;
+    let treasury = get_treasury(rpc).await?;
+    let mint_supply = get_mint_supply(rpc).await?;
+    let guard = deploy_guard(rpc, guard, &round).await?;
+
+    let mut allocations = Vec::with_capacity(wallets.len());
+    let mut checkpoints = Vec::with_capacity(wallets.len());
+    for wallet in wallets.iter_mut() {
+        let miner = get_miner(rpc, wallet.payer.pubkey()).await.ok();
+        checkpoints.push(pending_checkpoint(miner.as_ref(), round_id));
+        let mut amounts = wallet.strategy.allocate(&StrategyInput {
+            board: &board,
+            round: &round,
+            treasury: &treasury,
+            mint_supply,
+            miner: miner.as_ref(),
+            history: &wallet.history,
+            excluded,
+        });
+        claim_squares(&mut amounts, excluded);
+        allocations.push(amounts);
+    }
+
+    let results = join_all(wallets.iter().zip(&allocations).zip(&checkpoints).map(
+        |((wallet, amounts), checkpoint)| async move {
+            if amounts.iter().all(|&a| a == 0) {
+                return None;
+            }
+            let ixs = deploy_instructions(&wallet.payer, round_id, amounts, *checkpoint, guard);
+            let budget = fee.compute_budget(DEPLOY_COMPUTE_UNIT_LIMIT);
+            Some(submit_transaction_with_budget(rpc, &wallet.payer, &ixs, budget).await)
+        },
+    ))
+    .await;
+
+    for ((wallet, amounts), result) in wallets.iter_mut().zip(allocations).zip(results) {
+        match result {
+            None => println!("⏭️  {} skipped round #{}", wallet.name, round_id),
+            Some(Ok(_)) => {
+                let squares: Vec<usize> = (0..25).filter(|&i| amounts[i] > 0).collect();
+                println!(
+                    "✅ {} deployed {:.4} SOL to squares {:?}",
+                    wallet.name,
+                    amounts.iter().sum::<u64>() as f64 / 1e9,
+                    squares
+                );
+                wallet.history.push(RoundRecord {
+                    round_id,
+                    amounts,
+                    winning_square: None,
+                    sol_won: 0,
+                    ore_won: 0,
+                });
+                if wallet.history.len() > HISTORY_LEN {
+                    wallet.history.remove(0);
+                }
+            }
+            Some(Err(e)) => println!("❌ {} deployment failed: {}", wallet.name, e),
+        }
+    }
+    Ok(())
+}
+
+/// Drops squares another wallet already took, then marks the remaining ones as taken.
+fn claim_squares(amounts: &mut [u64; 25], excluded: &mut [bool; 25]) {
+    for i in 0..25 {
+        if excluded[i] {
+            amounts[i] = 0;
+        } else if amounts[i] > 0 {
+            excluded[i] = true;
+        }
+    }
+}
+
+/// Seconds remaining when a timing starts deploying. Deploying now starts earliest.
+fn window_start(timing: DeployTiming) -> u64 {
+    match timing {
+        DeployTiming::Now => u64::MAX,
+        DeployTiming::Window { max_seconds, .. } => max_seconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_squares() {
+        let mut excluded = [false; 25];
+        let mut first = [0; 25];
+        first[1] = 10;
+        first[2] = 10;
+        claim_squares(&mut first, &mut excluded);
+        assert_eq!(first[1], 10);
+        assert!(excluded[1] && excluded[2]);
+
+        // A later wallet that picked an overlapping square loses it.
+        let mut second = [0; 25];
+        second[2] = 10;
+        second[3] = 10;
+        claim_squares(&mut second, &mut excluded);
+        assert_eq!(second[2], 0);
+        assert_eq!(second[3], 10);
+        assert!(excluded[3]);
+    }
+
+    #[test]
+    fn test_fleet_file() {
+        let file: FleetFile = toml::from_str(
+            r#"
+            [[wallets]]
+            keypair = "/keys/bot1.json"
+            amount = 5000000
+
+            [[wallets]]
+            keypair = "/keys/bot2.json"
+            amount = 2000000
+            strategy = "late_snipe:3:5"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.wallets[0].squares, 2);
+        assert!(file.wallets[0].strategy.is_none());
+        assert!(matches!(
+            file.wallets[1].strategy,
+            Some(MiningStrategy::LateSnipe {
+                count: 3,
+                delay_seconds: 5
+            })
+        ));
+        assert!(toml::from_str::<FleetFile>(
+            "[[wallets]]\nkeypair = \"a\"\namount = 1\nstrategy = \"nope\""
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    collections::HashSet,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    str::FromStr,
+};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use futures_util::StreamExt;
+use ore_api::{event::*, instruction::OreInstruction};
+use serde::{Deserialize, Serialize};
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{bs58, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta, UiInstruction,
+    UiTransactionEncoding,
+};
+use steel::Pod;
+
+/// An event decoded from the data of a `Log` instruction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    Reset(ResetEvent),
+    Bury(BuryEvent),
+    Motherlode(MotherlodeEvent),
+    Deploy(DeployEvent),
+    Checkpoint(CheckpointEvent),
+    ClaimSol(ClaimSOLEvent),
+    ClaimOre(ClaimOREEvent),
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    ClaimYield(ClaimYieldEvent),
+    Automate(AutomateEvent),
+    DeploySkipped(DeploySkippedEvent),
+    /// An event this build does not know about, kept as base64 so it can be decoded later.
+    Unknown {
+        disc: u64,
+        bytes: String,
+    },
+}
+
+/// An event along with the transaction it was emitted in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Decodes the event in the data of a `Log` instruction.
+/// Returns None if the data is not a `Log` instruction or is malformed.
+pub fn decode_event(data: &[u8]) -> Option<Event> {
+    let (&ix, bytes) = data.split_first()?;
+    if ix != OreInstruction::Log as u8 {
+        return None;
+    }
+    let disc = u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+    let event = match OreEvent::try_from(disc) {
+        Ok(OreEvent::Reset) => Event::Reset(read(bytes)?),
+        Ok(OreEvent::Bury) => Event::Bury(read(bytes)?),
+        Ok(OreEvent::Motherlode) => Event::Motherlode(read(bytes)?),
+        Ok(OreEvent::Deploy) => Event::Deploy(read(bytes)?),
+        Ok(OreEvent::Checkpoint) => Event::Checkpoint(read(bytes)?),
+        Ok(OreEvent::ClaimSOL) => Event::ClaimSol(read(bytes)?),
+        Ok(OreEvent::ClaimORE) => Event::ClaimOre(read(bytes)?),
+        Ok(OreEvent::Deposit) => Event::Deposit(read(bytes)?),
+        Ok(OreEvent::Withdraw) => Event::Withdraw(read(bytes)?),
+        Ok(OreEvent::ClaimYield) => Event::ClaimYield(read(bytes)?),
+        Ok(OreEvent::Automate) => Event::Automate(read(bytes)?),
+        Ok(OreEvent::DeploySkipped) => Event::DeploySkipped(read(bytes)?),
+        Err(_) => Event::Unknown {
+            disc,
+            bytes: BASE64_STANDARD.encode(bytes),
+        },
+    };
+    Some(event)
+}
+
+fn read<T: Pod>(bytes: &[u8]) -> Option<T> {
+    let bytes = bytes.get(..std::mem::size_of::<T>())?;
+    bytemuck::try_pod_read_unaligned(bytes).ok()
+}
+
+/// Extracts the events emitted by the ORE program in a transaction.
+/// Events are emitted as inner `Log` instructions, so failed transactions have none.
+pub fn events_from_transaction(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<Event> {
+    let Some(meta) = &tx.transaction.meta else {
+        return vec![];
+    };
+    if meta.err.is_some() {
+        return vec![];
+    }
+    let Some(versioned) = tx.transaction.transaction.decode() else {
+        return vec![];
+    };
+
+    // Resolve account keys, including those loaded from lookup tables.
+    let mut keys = versioned.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for address in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            if let Ok(address) = Pubkey::from_str(address) {
+                keys.push(address);
+            }
+        }
+    }
+
+    // Decode every inner instruction sent to the ORE program.
+    let OptionSerializer::Some(inner_instructions) = &meta.inner_instructions else {
+        return vec![];
+    };
+    let mut events = vec![];
+    for inner in inner_instructions {
+        for ix in &inner.instructions {
+            let UiInstruction::Compiled(ix) = ix else {
+                continue;
+            };
+            if keys.get(ix.program_id_index as usize) != Some(&ore_api::ID) {
+                continue;
+            }
+            let Ok(data) = bs58::decode(&ix.data).into_vec() else {
+                continue;
+            };
+            if let Some(event) = decode_event(&data) {
+                events.push(event);
+            }
+        }
+    }
+    events
+}
+
+/// Reads all indexed events from a JSONL file.
+pub fn read_events(file: &str) -> Vec<IndexedEvent> {
+    let Ok(f) = std::fs::File::open(file) else {
+        return vec![];
+    };
+    BufReader::new(f)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Finds the reset event of a round in a JSONL file.
+#[allow(dead_code)]
+pub fn find_reset(file: &str, round_id: u64) -> Option<ResetEvent> {
+    read_events(file).into_iter().find_map(|e| match e.event {
+        Event::Reset(reset) if reset.round_id == round_id => Some(reset),
+        _ => None,
+    })
+}
+
+/// How the indexer finds transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum IndexerMode {
+    /// Follow program logs.
+    Live,
+
+    /// Fetch past transactions, newest first.
+    Backfill,
+}
+
+/// Indexes ORE events to a JSONL file. In backfill mode at most `limit` past transactions
+/// are fetched.
+pub async fn run_indexer(
+    rpc: &RpcClient,
+    mode: IndexerMode,
+    file: &str,
+    limit: usize,
+) -> Result<(), anyhow::Error> {
+    // Skip transactions that were already indexed.
+    let mut seen: HashSet<String> = read_events(file).into_iter().map(|e| e.signature).collect();
+    println!("Loaded {} indexed transactions from {}", seen.len(), file);
+
+    match mode {
+        IndexerMode::Live => index_live(rpc, file, &mut seen).await,
+        IndexerMode::Backfill => index_backfill(rpc, file, &mut seen, limit).await,
+    }
+}
+
+/// Follows program logs and indexes each transaction that invoked the ORE program.
+async fn index_live(
+    rpc: &RpcClient,
+    file: &str,
+    seen: &mut HashSet<String>,
+) -> Result<(), anyhow::Error> {
+    let ws_url = rpc
+        .url()
+        .replace("https://", "wss://")
+        .replace("http://", "ws://");
+
+    // Events are logged by the program invoking itself.
+    let cpi_log = format!("Program {} invoke [2]", ore_api::ID);
+    loop {
+        let pubsub = match PubsubClient::new(&ws_url).await {
+            Ok(pubsub) => pubsub,
+            Err(e) => {
+                eprintln!("WebSocket error: {}, reconnecting in 2s...", e);
+                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                continue;
+            }
+        };
+        let (mut stream, _unsub) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![ore_api::ID.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        println!("Indexing ORE events to {}", file);
+
+        while let Some(response) = stream.next().await {
+            let logs = response.value;
+            if logs.err.is_some() || !logs.logs.iter().any(|l| l == &cpi_log) {
+                continue;
+            }
+            if let Err(e) = index_transaction(rpc, file, seen, &logs.signature).await {
+                eprintln!("Failed to index {}: {}", logs.signature, e);
+            }
+        }
+        eprintln!("Log subscription closed, reconnecting...");
+    }
+}
+
+/// Fetches the most recent transactions of the ORE program and indexes them oldest first.
+async fn index_backfill(
+    rpc: &RpcClient,
+    file: &str,
+    seen: &mut HashSet<String>,
+    limit: usize,
+) -> Result<(), anyhow::Error> {
+    let mut signatures = vec![];
+    let mut before = None;
+    while signatures.len() < limit {
+        let page = rpc
+            .get_signatures_for_address_with_config(
+                &ore_api::ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some((limit - signatures.len()).min(1000)),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                },
+            )
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        signatures.extend(
+            page.into_iter()
+                .filter(|s| s.err.is_none())
+                .map(|s| s.signature),
+        );
+    }
+
+    println!("Backfilling {} transactions", signatures.len());
+    for signature in signatures.iter().rev() {
+        if let Err(e) = index_transaction(rpc, file, seen, signature).await {
+            eprintln!("Failed to index {}: {}", signature, e);
+        }
+    }
+    Ok(())
+}
+
+/// Fetches a transaction and appends its events to the index.
+async fn index_transaction(
+    rpc: &RpcClient,
+    file: &str,
+    seen: &mut HashSet<String>,
+    signature: &str,
+) -> Result<(), anyhow::Error> {
+    if seen.contains(signature) {
+        return Ok(());
+    }
+
+    // The transaction may not be queryable as soon as its logs are streamed.
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    let sig = Signature::from_str(signature)?;
+    let mut attempts = 0;
+    let tx = loop {
+        match rpc.get_transaction_with_config(&sig, config).await {
+            Ok(tx) => break tx,
+            Err(e) if attempts >= 5 => return Err(e.into()),
+            Err(_) => {
+                attempts += 1;
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+        }
+    };
+
+    let events = events_from_transaction(&tx);
+    let mut f = OpenOptions::new().create(true).append(true).open(file)?;
+    for event in events {
+        log_event(&event);
+        let record = IndexedEvent {
+            signature: signature.to_string(),
+            slot: tx.slot,
+            block_time: tx.block_time,
+            event,
+        };
+        writeln!(f, "{}", serde_json::to_string(&record)?)?;
+    }
+    seen.insert(signature.to_string());
+    Ok(())
+}
+
+fn log_event(event: &Event) {
+    match event {
+        Event::Reset(e) => println!(
+            "Round #{} reset: square {}, {} winners, motherlode {}",
+            e.round_id, e.winning_square, e.num_winners, e.motherlode
+        ),
+        Event::Motherlode(e) => println!(
+            "Round #{} motherlode: {} split between {} miners",
+            e.round_id, e.amount, e.num_miners
+        ),
+        Event::Bury(e) => println!("Bury: {} buried, {} shared", e.ore_buried, e.ore_shared),
+        Event::Unknown { disc, .. } => println!("Unknown event {}", disc),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    #[test]
+    fn test_decode_event() {
+        let mut reset = ResetEvent::zeroed();
+        reset.disc = OreEvent::Reset as u64;
+        reset.round_id = 42;
+        reset.winning_square = 7;
+        let mut data = vec![OreInstruction::Log as u8];
+        data.extend_from_slice(bytemuck::bytes_of(&reset));
+        assert_eq!(decode_event(&data), Some(Event::Reset(reset)));
+
+        // Round trip through the index format.
+        let record = IndexedEvent {
+            signature: "sig".to_string(),
+            slot: 1,
+            block_time: None,
+            event: Event::Reset(reset),
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        let parsed: IndexedEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.event, Event::Reset(reset));
+
+        // Future events are kept undecoded.
+        let mut data = vec![OreInstruction::Log as u8];
+        data.extend_from_slice(&99u64.to_le_bytes());
+        assert!(matches!(
+            decode_event(&data),
+            Some(Event::Unknown { disc: 99, .. })
+        ));
+
+        // Truncated events and other instructions are ignored.
+        let mut data = vec![OreInstruction::Log as u8];
+        data.extend_from_slice(&(OreEvent::Reset as u64).to_le_bytes());
+        assert_eq!(decode_event(&data), None);
+        assert_eq!(decode_event(&[OreInstruction::Deploy as u8]), None);
+    }
+}
@@ -0,0 +1,439 @@
+/// Crowd inflow prediction
+///
+/// Snipes choose squares a few seconds before the end, but SOL keeps arriving until `end_slot`.
+/// This model predicts the final pool of each square, and how far off that may be, from the
+/// pools at a given number of slots before the end. It learns from the deploy and reset events
+/// recorded by the indexer, which together give every round's pools slot by slot.
+///
+/// The SOL still to come is the current total times the growth seen at the same point of recent
+/// rounds. It lands partly in proportion to the current pools and partly evenly, with the mix
+/// fitted to where late SOL went in those rounds.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::indexer::{read_events, Event, IndexedEvent};
+
+/// Fewest past rounds a model is fit on.
+const MIN_ROUNDS: usize = 5;
+
+/// Number of least crowded squares compared in evaluations.
+const LEAST_CROWDED: usize = 5;
+
+/// The deploys of a finished round.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundSeries {
+    pub round_id: u64,
+    pub end_slot: u64,
+
+    /// Slot and amounts of each deploy.
+    pub deploys: Vec<(u64, [u64; 25])>,
+}
+
+impl RoundSeries {
+    /// The pools with `slots_remaining` slots left in the round.
+    pub fn pools_at(&self, slots_remaining: u64) -> [u64; 25] {
+        let cutoff = self.end_slot.saturating_sub(slots_remaining);
+        let mut pools = [0; 25];
+        for (slot, amounts) in &self.deploys {
+            if *slot <= cutoff {
+                for (pool, amount) in pools.iter_mut().zip(amounts) {
+                    *pool += amount;
+                }
+            }
+        }
+        pools
+    }
+
+    /// The pools once the round ended.
+    pub fn final_pools(&self) -> [u64; 25] {
+        self.pools_at(0)
+    }
+}
+
+/// Groups indexed deploys by round, keeping rounds that have been reset, oldest first.
+pub fn round_series(events: &[IndexedEvent]) -> Vec<RoundSeries> {
+    let mut deploys: BTreeMap<u64, Vec<(u64, [u64; 25])>> = BTreeMap::new();
+    let mut end_slots = BTreeMap::new();
+    for e in events {
+        match &e.event {
+            Event::Deploy(deploy) => deploys
+                .entry(deploy.round_id)
+                .or_default()
+                .push((e.slot, deploy.amounts)),
+            Event::Reset(reset) if reset.end_slot != u64::MAX => {
+                end_slots.insert(reset.round_id, reset.end_slot);
+            }
+            _ => {}
+        }
+    }
+    end_slots
+        .into_iter()
+        .filter_map(|(round_id, end_slot)| {
+            let deploys = deploys.remove(&round_id)?;
+            Some(RoundSeries {
+                round_id,
+                end_slot,
+                deploys,
+            })
+        })
+        .collect()
+}
+
+/// Predicted final pools.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Prediction {
+    pub mean: [u64; 25],
+
+    /// Standard deviation of each final pool, in lamports.
+    pub std: [f64; 25],
+}
+
+impl Prediction {
+    /// Lamports expected to arrive on top of `pools`.
+    pub fn inflow(&self, pools: &[u64; 25]) -> u64 {
+        self.mean.iter().sum::<u64>() - pools.iter().sum::<u64>()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InflowModel {
+    /// Slots before the end of the round the model predicts from.
+    pub slots_remaining: u64,
+
+    /// Mean lamports still to arrive per lamport already deployed, and its standard deviation.
+    pub growth: f64,
+    pub growth_std: f64,
+
+    /// How much late SOL follows the current pools, from 0 (evenly spread) to 1 (pro rata).
+    pub alpha: f64,
+
+    /// Standard deviation of each square's share of the late SOL around the model.
+    pub share_std: f64,
+}
+
+impl InflowModel {
+    /// Fits the model on past rounds. Returns None if fewer than `MIN_ROUNDS` had any SOL
+    /// deployed by then.
+    pub fn fit(rounds: &[RoundSeries], slots_remaining: u64) -> Option<Self> {
+        let samples: Vec<([u64; 25], [u64; 25])> = rounds
+            .iter()
+            .map(|r| (r.pools_at(slots_remaining), r.final_pools()))
+            .filter(|(pools, _)| pools.iter().sum::<u64>() > 0)
+            .collect();
+        if samples.len() < MIN_ROUNDS {
+            return None;
+        }
+
+        // Growth of the total.
+        let growths: Vec<f64> = samples
+            .iter()
+            .map(|(pools, finals)| {
+                let total = pools.iter().sum::<u64>() as f64;
+                (finals.iter().sum::<u64>() as f64 - total) / total
+            })
+            .collect();
+        let (growth, growth_std) = mean_std(&growths);
+
+        // Least squares fit of each square's share of the late SOL, against its share of the
+        // pools, both measured from an even split.
+        let mut points = vec![];
+        for (pools, finals) in &samples {
+            let total = pools.iter().sum::<u64>() as f64;
+            let inflow = finals.iter().sum::<u64>() as f64 - total;
+            if inflow <= 0.0 {
+                continue;
+            }
+            for (&pool, &fin) in pools.iter().zip(finals) {
+                let x = pool as f64 / total - 1.0 / 25.0;
+                let y = (fin - pool) as f64 / inflow - 1.0 / 25.0;
+                points.push((x, y));
+            }
+        }
+        let sxx: f64 = points.iter().map(|(x, _)| x * x).sum();
+        let sxy: f64 = points.iter().map(|(x, y)| x * y).sum();
+        let alpha = if sxx > 0.0 {
+            (sxy / sxx).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let residuals: Vec<f64> = points.iter().map(|(x, y)| y - alpha * x).collect();
+        let share_std = if residuals.is_empty() {
+            0.0
+        } else {
+            (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt()
+        };
+
+        Some(InflowModel {
+            slots_remaining,
+            growth,
+            growth_std,
+            alpha,
+            share_std,
+        })
+    }
+
+    /// Predicts the final pools from the current ones.
+    pub fn predict(&self, pools: &[u64; 25]) -> Prediction {
+        let total = pools.iter().sum::<u64>() as f64;
+        let inflow = (total * self.growth).max(0.0);
+        let inflow_std = total * self.growth_std;
+        let mut prediction = Prediction {
+            mean: *pools,
+            std: [0.0; 25],
+        };
+        if total == 0.0 {
+            return prediction;
+        }
+        for (i, &pool) in pools.iter().enumerate() {
+            let share = self.alpha * pool as f64 / total + (1.0 - self.alpha) / 25.0;
+            prediction.mean[i] += (inflow * share).round() as u64;
+            prediction.std[i] = (inflow_std * share).hypot(inflow * self.share_std);
+        }
+        prediction
+    }
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/// Prediction error on held-out rounds, for the model and for taking the current pools as final.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Evaluation {
+    pub rounds: usize,
+
+    /// Mean absolute error of each square's final pool, in lamports.
+    pub model_mae: f64,
+    pub naive_mae: f64,
+
+    /// Mean absolute error of the round's final total, in lamports.
+    pub model_total_error: f64,
+    pub naive_total_error: f64,
+
+    /// Share of final pools within one standard deviation of the prediction.
+    pub coverage: f64,
+
+    /// Mean number of the final least crowded squares that were predicted to be among them.
+    pub model_overlap: f64,
+    pub naive_overlap: f64,
+}
+
+/// Predicts each of the last `holdout` rounds from the `history` rounds before it, so no round
+/// is predicted by a model that has seen it.
+pub fn evaluate(
+    rounds: &[RoundSeries],
+    slots_remaining: u64,
+    history: usize,
+    holdout: usize,
+) -> Option<Evaluation> {
+    let mut eval = Evaluation::default();
+    let mut within = 0;
+    for j in rounds.len().saturating_sub(holdout)..rounds.len() {
+        let train = &rounds[j.saturating_sub(history)..j];
+        let Some(model) = InflowModel::fit(train, slots_remaining) else {
+            continue;
+        };
+        let pools = rounds[j].pools_at(slots_remaining);
+        if pools.iter().sum::<u64>() == 0 {
+            continue;
+        }
+        let finals = rounds[j].final_pools();
+        let prediction = model.predict(&pools);
+
+        eval.rounds += 1;
+        for (i, &fin) in finals.iter().enumerate() {
+            let error = (prediction.mean[i] as f64 - fin as f64).abs();
+            eval.model_mae += error;
+            eval.naive_mae += fin.abs_diff(pools[i]) as f64;
+            if error <= prediction.std[i] {
+                within += 1;
+            }
+        }
+        let total = finals.iter().sum::<u64>();
+        eval.model_total_error += prediction.mean.iter().sum::<u64>().abs_diff(total) as f64;
+        eval.naive_total_error += pools.iter().sum::<u64>().abs_diff(total) as f64;
+        let actual = least_crowded(&finals);
+        let overlap = |pools: &[u64; 25]| {
+            least_crowded(pools)
+                .iter()
+                .filter(|i| actual.contains(i))
+                .count() as f64
+        };
+        eval.model_overlap += overlap(&prediction.mean);
+        eval.naive_overlap += overlap(&pools);
+    }
+    if eval.rounds == 0 {
+        return None;
+    }
+
+    let n = eval.rounds as f64;
+    eval.model_mae /= 25.0 * n;
+    eval.naive_mae /= 25.0 * n;
+    eval.model_total_error /= n;
+    eval.naive_total_error /= n;
+    eval.coverage = within as f64 / (25.0 * n);
+    eval.model_overlap /= n;
+    eval.naive_overlap /= n;
+    Some(eval)
+}
+
+fn least_crowded(pools: &[u64; 25]) -> Vec<usize> {
+    let mut squares: Vec<usize> = (0..25).collect();
+    squares.sort_by_key(|&i| pools[i]);
+    squares.truncate(LEAST_CROWDED);
+    squares
+}
+
+/// Evaluates the model on the rounds in an indexer file and prints the report.
+pub fn log_evaluation(
+    index_file: &str,
+    slots_remaining: u64,
+    history: usize,
+    holdout: usize,
+) -> Result<()> {
+    let rounds = round_series(&read_events(index_file));
+    println!(
+        "Loaded {} finished rounds from {}",
+        rounds.len(),
+        index_file
+    );
+    let eval = evaluate(&rounds, slots_remaining, history, holdout).ok_or_else(|| {
+        anyhow!(
+            "Need at least {} rounds with deploys before the held-out rounds",
+            MIN_ROUNDS
+        )
+    })?;
+    let held_out = &rounds[rounds.len().saturating_sub(holdout)..];
+    println!(
+        "Predicting final pools {} slots before the end of {} held-out rounds, #{} to #{}\n",
+        slots_remaining,
+        eval.rounds,
+        held_out[0].round_id,
+        held_out[held_out.len() - 1].round_id
+    );
+    println!("{:<28} {:>14} {:>14}", "", "Model", "Current pools");
+    println!(
+        "{:<28} {:>14.6} {:>14.6}",
+        "Square error (SOL)",
+        eval.model_mae / 1e9,
+        eval.naive_mae / 1e9
+    );
+    println!(
+        "{:<28} {:>14.6} {:>14.6}",
+        "Round total error (SOL)",
+        eval.model_total_error / 1e9,
+        eval.naive_total_error / 1e9
+    );
+    println!(
+        "{:<28} {:>14.2} {:>14.2}",
+        format!("Least crowded {} hits", LEAST_CROWDED),
+        eval.model_overlap,
+        eval.naive_overlap
+    );
+    println!(
+        "\nFinal pools within one standard deviation: {:.1}%",
+        eval.coverage * 100.0
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ore_api::event::{DeployEvent, ResetEvent};
+    use steel::Zeroable;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    fn record(slot: u64, event: Event) -> IndexedEvent {
+        IndexedEvent {
+            signature: format!("sig{}", slot),
+            slot,
+            block_time: None,
+            event,
+        }
+    }
+
+    fn deploy(round_id: u64, slot: u64, amounts: [u64; 25]) -> IndexedEvent {
+        let mut deploy = DeployEvent::zeroed();
+        deploy.round_id = round_id;
+        deploy.amounts = amounts;
+        record(slot, Event::Deploy(deploy))
+    }
+
+    fn reset(round_id: u64, end_slot: u64) -> IndexedEvent {
+        let mut reset = ResetEvent::zeroed();
+        reset.round_id = round_id;
+        reset.end_slot = end_slot;
+        record(end_slot + 35, Event::Reset(reset))
+    }
+
+    /// Rounds where SOL before the cutoff is uneven, and late SOL doubles the total, landing
+    /// half pro rata and half evenly.
+    fn rounds(count: u64) -> Vec<RoundSeries> {
+        let mut events = vec![];
+        for id in 0..count {
+            let end_slot = 1_000 * (id + 1);
+            let early: [u64; 25] =
+                std::array::from_fn(|i| ONE_SOL / 100 * ((i as u64 * 7 + id) % 25 + 1));
+            let total: u64 = early.iter().sum();
+            let late = early.map(|p| p / 2 + total / 2 / 25);
+            events.push(deploy(id, end_slot - 100, early));
+            events.push(deploy(id, end_slot - 5, late));
+            events.push(reset(id, end_slot));
+        }
+        round_series(&events)
+    }
+
+    #[test]
+    fn test_round_series() {
+        let events = vec![
+            deploy(1, 10, [1; 25]),
+            deploy(1, 140, [2; 25]),
+            reset(1, 150),
+            // Rounds without a reset are still running.
+            deploy(2, 200, [1; 25]),
+        ];
+        let rounds = round_series(&events);
+        assert_eq!(rounds.len(), 1);
+        assert_eq!(rounds[0].pools_at(25), [1; 25]);
+        assert_eq!(rounds[0].pools_at(10), [3; 25]);
+        assert_eq!(rounds[0].final_pools(), [3; 25]);
+    }
+
+    #[test]
+    fn test_fit_and_predict() {
+        let rounds = rounds(20);
+        assert!(InflowModel::fit(&rounds[..MIN_ROUNDS - 1], 25).is_none());
+        let model = InflowModel::fit(&rounds, 25).unwrap();
+        assert!((model.growth - 1.0).abs() < 0.01);
+        assert!((model.alpha - 0.5).abs() < 0.01);
+
+        let pools = rounds[3].pools_at(25);
+        let prediction = model.predict(&pools);
+        let finals = rounds[3].final_pools();
+        for (mean, fin) in prediction.mean.iter().zip(finals) {
+            assert!(mean.abs_diff(fin) < ONE_SOL / 1_000);
+        }
+        let total: u64 = pools.iter().sum();
+        assert!(prediction.inflow(&pools).abs_diff(total) < ONE_SOL / 1_000);
+
+        // Nothing deployed yet means nothing to go on.
+        assert_eq!(model.predict(&[0; 25]).mean, [0; 25]);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let rounds = rounds(30);
+        let eval = evaluate(&rounds, 25, 10, 10).unwrap();
+        assert_eq!(eval.rounds, 10);
+        assert!(eval.model_mae < eval.naive_mae / 10.0);
+        assert!(eval.model_total_error < eval.naive_total_error / 10.0);
+        assert!(eval.model_overlap >= eval.naive_overlap);
+        assert!(evaluate(&rounds[..3], 25, 10, 10).is_none());
+    }
+}
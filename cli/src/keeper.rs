@@ -0,0 +1,355 @@
+/// Keeper daemon for checkpoint fees and round closing
+///
+/// A round's account lives for a day after it ends. In its last 12 hours anyone may checkpoint
+/// a miner that has not done so and collect the miner's checkpoint fee, and once it expires
+/// anyone may close it, returning the rent to whoever paid it. The keeper tracks settled rounds
+/// in a state file, so a restart picks up where it left off instead of scanning every account
+/// again, and only sends batches whose fees cover the transaction cost.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction, native_token::lamports_to_sol, pubkey::Pubkey, rent::Rent,
+    signature::Signer, signer::keypair::Keypair,
+};
+
+use ore_api::prelude::*;
+
+use crate::fees::PriorityFee;
+use crate::{
+    get_board, get_clock, get_miners_participating, get_round, get_rounds, submit_transaction,
+};
+
+/// Checkpoints per transaction.
+const CHECKPOINT_BATCH: usize = 10;
+
+/// Closes per transaction.
+const CLOSE_BATCH: usize = 12;
+
+/// Base fee per transaction, for its one signature.
+const SIGNATURE_FEE: u64 = 5_000;
+
+/// Rough compute units per instruction, used to price the priority fee.
+const CHECKPOINT_UNITS: u64 = 40_000;
+const CLOSE_UNITS: u64 = 20_000;
+
+/// What became of a batch.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Sent,
+    Unprofitable,
+    Failed,
+}
+
+pub struct KeeperConfig {
+    /// File the keeper's progress is saved to.
+    pub state_file: String,
+
+    /// Least profit in lamports a transaction must make to be sent.
+    pub min_profit: i64,
+
+    /// Also close expired rounds whose rent goes to someone else, at a loss.
+    pub close_others: bool,
+
+    /// Seconds between passes.
+    pub interval: u64,
+
+    pub priority_fee: PriorityFee,
+}
+
+/// Progress saved between runs.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeeperState {
+    /// Rounds below this id have been discovered.
+    next_round_id: u64,
+
+    /// Settled rounds that have not been closed yet.
+    rounds: BTreeMap<u64, TrackedRound>,
+
+    /// Lamports earned over every run, net of transaction costs.
+    profit: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrackedRound {
+    expires_at: u64,
+    rent_payer: String,
+
+    /// Whether its miners have been checkpointed in the bot fee window.
+    swept: bool,
+}
+
+impl TrackedRound {
+    fn new(round: &Round) -> Self {
+        TrackedRound {
+            expires_at: round.expires_at,
+            rent_payer: round.rent_payer.to_string(),
+            swept: false,
+        }
+    }
+
+    fn in_fee_window(&self, slot: u64) -> bool {
+        slot >= self.expires_at.saturating_sub(TWELVE_HOURS_SLOTS) && slot < self.expires_at
+    }
+
+    /// The program only closes a round after its expiry slot.
+    fn is_closable(&self, slot: u64) -> bool {
+        slot > self.expires_at
+    }
+}
+
+/// Checkpoints miners in the bot fee window and closes expired rounds, forever.
+pub async fn run_keeper(rpc: &RpcClient, payer: &Keypair, config: KeeperConfig) -> Result<()> {
+    let mut state = load_state(&config.state_file)?;
+    println!("🧹 Keeper: {}", payer.pubkey());
+    loop {
+        if let Err(e) = keep(rpc, payer, &config, &mut state).await {
+            println!("⚠️  Keeper pass failed: {}", e);
+        }
+        save_state(&config.state_file, &state)?;
+        tokio::time::sleep(tokio::time::Duration::from_secs(config.interval)).await;
+    }
+}
+
+async fn keep(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    config: &KeeperConfig,
+    state: &mut KeeperState,
+) -> Result<()> {
+    let board = get_board(rpc).await?;
+    let slot = get_clock(rpc).await?.slot;
+    discover_rounds(rpc, state, board.round_id).await?;
+
+    // Checkpoint miners in rounds that entered the bot fee window.
+    let due: Vec<u64> = state
+        .rounds
+        .iter()
+        .filter(|(_, r)| !r.swept && r.in_fee_window(slot))
+        .map(|(id, _)| *id)
+        .collect();
+    for round_id in due {
+        let miners = get_miners_participating(rpc, round_id).await?;
+        let pending: Vec<(Pubkey, u64)> = miners
+            .iter()
+            .filter(|(_, m)| m.checkpoint_id < round_id && m.checkpoint_fee > 0)
+            .map(|(_, m)| (m.authority, m.checkpoint_fee))
+            .collect();
+        println!(
+            "📝 Round #{}: {} miners to checkpoint",
+            round_id,
+            pending.len()
+        );
+        let mut failed = false;
+        for batch in pending.chunks(CHECKPOINT_BATCH) {
+            let ixs: Vec<Instruction> = batch
+                .iter()
+                .map(|(authority, _)| {
+                    ore_api::sdk::checkpoint(payer.pubkey(), *authority, round_id)
+                })
+                .collect();
+            let fees = batch.iter().map(|(_, fee)| fee).sum();
+            let outcome = send_if_profitable(
+                rpc,
+                payer,
+                config,
+                state,
+                &ixs,
+                fees,
+                CHECKPOINT_UNITS,
+                false,
+            )
+            .await;
+            failed |= outcome == Outcome::Failed;
+        }
+        if let Some(round) = state.rounds.get_mut(&round_id) {
+            round.swept = !failed;
+        }
+    }
+
+    // Close expired rounds. Rounds whose rent goes to someone else are dropped unless closing
+    // them anyway.
+    let rent = Rent::default().minimum_balance(8 + std::mem::size_of::<Round>());
+    let payer_key = payer.pubkey().to_string();
+    let mut closable = vec![];
+    state.rounds.retain(|&id, round| {
+        if !round.is_closable(slot) {
+            return true;
+        }
+        if round.rent_payer == payer_key || config.close_others {
+            closable.push((id, round.clone()));
+            return true;
+        }
+        false
+    });
+    for batch in closable.chunks(CLOSE_BATCH) {
+        let mut ixs = vec![];
+        let mut revenue = 0;
+        for (id, round) in batch {
+            let rent_payer = Pubkey::from_str(&round.rent_payer)?;
+            ixs.push(ore_api::sdk::close(payer.pubkey(), *id, rent_payer));
+            if rent_payer == payer.pubkey() {
+                revenue += rent;
+            }
+        }
+        let force = config.close_others;
+        let outcome =
+            send_if_profitable(rpc, payer, config, state, &ixs, revenue, CLOSE_UNITS, force).await;
+        if outcome == Outcome::Sent {
+            for (id, _) in batch {
+                state.rounds.remove(id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds every settled round from `next_round_id` up to the current round. The first run scans
+/// the program's round accounts once, after that rounds are fetched one by one as they settle.
+async fn discover_rounds(
+    rpc: &RpcClient,
+    state: &mut KeeperState,
+    current_round_id: u64,
+) -> Result<()> {
+    if state.next_round_id == 0 && state.rounds.is_empty() {
+        println!("🔍 Scanning round accounts...");
+        for (_, round) in get_rounds(rpc).await? {
+            if round.id < current_round_id {
+                state.rounds.insert(round.id, TrackedRound::new(&round));
+            }
+        }
+        state.next_round_id = current_round_id;
+        println!("🔍 Tracking {} rounds", state.rounds.len());
+        return Ok(());
+    }
+    for round_id in state.next_round_id..current_round_id {
+        // Rounds that were already closed are skipped.
+        if let Ok(round) = get_round(rpc, round_id).await {
+            state.rounds.insert(round_id, TrackedRound::new(&round));
+        }
+    }
+    state.next_round_id = state.next_round_id.max(current_round_id);
+    Ok(())
+}
+
+/// Sends the instructions if `revenue` covers the estimated cost, or always if `force` is set.
+#[allow(clippy::too_many_arguments)]
+async fn send_if_profitable(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    config: &KeeperConfig,
+    state: &mut KeeperState,
+    ixs: &[Instruction],
+    revenue: u64,
+    units_per_ix: u64,
+    force: bool,
+) -> Outcome {
+    let price = match config.priority_fee.price(rpc, ixs).await {
+        Ok(price) => price,
+        Err(e) => {
+            println!("⚠️  Failed to price transaction: {}", e);
+            return Outcome::Failed;
+        }
+    };
+    let cost = tx_cost(price, units_per_ix * ixs.len() as u64);
+    let profit = revenue as i64 - cost as i64;
+    if profit < config.min_profit && !force {
+        println!(
+            "⏭️  Skipping {} instructions: {} SOL revenue, {} SOL cost",
+            ixs.len(),
+            lamports_to_sol(revenue),
+            lamports_to_sol(cost)
+        );
+        return Outcome::Unprofitable;
+    }
+    match submit_transaction(rpc, payer, ixs, config.priority_fee).await {
+        Ok(_) => {
+            state.profit += profit;
+            println!(
+                "💰 {:+.9} SOL, {:.9} SOL total",
+                profit as f64 / 1e9,
+                state.profit as f64 / 1e9
+            );
+            Outcome::Sent
+        }
+        Err(e) => {
+            println!("❌ Failed: {}", e);
+            Outcome::Failed
+        }
+    }
+}
+
+/// Estimated lamports a transaction costs at `price` micro-lamports per compute unit.
+fn tx_cost(price: u64, units: u64) -> u64 {
+    SIGNATURE_FEE + (price as u128 * units as u128).div_ceil(1_000_000) as u64
+}
+
+fn load_state(path: &str) -> Result<KeeperState> {
+    if !Path::new(path).exists() {
+        return Ok(KeeperState::default());
+    }
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn save_state(path: &str, state: &KeeperState) -> Result<()> {
+    // Write then rename, so a crash never leaves a truncated file.
+    let tmp = format!("{}.tmp", path);
+    std::fs::write(&tmp, serde_json::to_string_pretty(state)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tx_cost() {
+        assert_eq!(tx_cost(0, 400_000), SIGNATURE_FEE);
+        assert_eq!(tx_cost(10_000, 400_000), SIGNATURE_FEE + 4_000);
+        assert_eq!(tx_cost(1, 1), SIGNATURE_FEE + 1);
+    }
+
+    #[test]
+    fn test_windows() {
+        let round = TrackedRound {
+            expires_at: TWELVE_HOURS_SLOTS + 100,
+            rent_payer: Pubkey::default().to_string(),
+            swept: false,
+        };
+        assert!(!round.in_fee_window(99));
+        assert!(round.in_fee_window(100));
+        assert!(round.in_fee_window(TWELVE_HOURS_SLOTS + 99));
+        assert!(!round.in_fee_window(TWELVE_HOURS_SLOTS + 100));
+        assert!(!round.is_closable(TWELVE_HOURS_SLOTS + 100));
+        assert!(round.is_closable(TWELVE_HOURS_SLOTS + 101));
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let mut state = KeeperState {
+            next_round_id: 42,
+            profit: -5_000,
+            ..KeeperState::default()
+        };
+        state.rounds.insert(
+            41,
+            TrackedRound {
+                expires_at: 1_000,
+                rent_payer: Pubkey::new_unique().to_string(),
+                swept: true,
+            },
+        );
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: KeeperState = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.next_round_id, 42);
+        assert_eq!(parsed.profit, -5_000);
+        assert!(parsed.rounds[&41].swept);
+    }
+}
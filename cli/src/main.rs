@@ -1,4 +1,6 @@
-use std::{collections::HashMap, str::FromStr};
+use std::collections::HashMap;
+
+use clap::{CommandFactory, FromArgMatches};
 
 use entropy_api::prelude::*;
 use jup_swap::{
@@ -17,20 +19,48 @@ use solana_client::{
 };
 use solana_sdk::{
     address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
-    compute_budget::ComputeBudgetInstruction,
+    instruction::InstructionError,
     message::{v0::Message, VersionedMessage},
     native_token::{lamports_to_sol, LAMPORTS_PER_SOL},
     pubkey::Pubkey,
-    signature::{read_keypair_file, Signature, Signer},
-    transaction::{Transaction, VersionedTransaction},
+    signature::{Signature, Signer},
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
 use solana_sdk::{keccak, pubkey};
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::amount_to_ui_amount;
 use steel::{AccountDeserialize, Clock, Discriminator, Instruction};
 
+mod args;
+use args::{Cli, Command, GuardArgs, PredictArgs};
+
+mod automate;
+use automate::automate;
+
+mod config;
+
+mod fees;
+use fees::PriorityFee;
+
+mod executor;
+use executor::{run_executor, ExecutorConfig};
+
+mod fleet;
+use fleet::{load_fleet, run_fleet, FleetConfig};
+
+mod keeper;
+use keeper::{run_keeper, KeeperConfig};
+
+mod recover;
+use recover::recover;
+
+mod sender;
+use sender::{TxOutcome, TxSender};
+
 mod websocket;
-use websocket::WebSocketManager;
+
+mod crank;
+use crank::{run_crank, CrankConfig};
 
 mod deploy_continuous;
 use deploy_continuous::deploy_continuous;
@@ -41,146 +71,232 @@ use deploy_single::deploy_single;
 mod deploy_optimal_ev;
 use deploy_optimal_ev::deploy_optimal_ev;
 
+mod driver;
+use driver::{pending_checkpoint, run_strategy, DriverConfig};
+
+mod indexer;
+use indexer::run_indexer;
+
+mod inflow;
+use inflow::log_evaluation;
+
+mod solver;
+use solver::{log_solution, SolverConfig};
+
+#[allow(dead_code)]
+mod strategies;
+use strategies::MiningStrategy;
+
+mod strategy;
+use strategy::{DeployTiming, LeastCrowded, Selection};
+
 // mod tui;  // Commented out - has borrow checker errors, use deploy_optimal instead
 
 #[tokio::main]
 async fn main() {
-    // Read keypair from file
-    let payer =
-        read_keypair_file(&std::env::var("KEYPAIR").expect("Missing KEYPAIR env var")).unwrap();
-
-    // Build transaction
-    let rpc = RpcClient::new(std::env::var("RPC").expect("Missing RPC env var"));
-    match std::env::var("COMMAND")
-        .expect("Missing COMMAND env var")
-        .as_str()
-    {
-        "automations" => {
-            log_automations(&rpc).await.unwrap();
-        }
-        "clock" => {
-            log_clock(&rpc).await.unwrap();
-        }
-        "claim" => {
-            claim(&rpc, &payer).await.unwrap();
-        }
-        "board" => {
-            log_board(&rpc).await.unwrap();
-        }
-        "config" => {
-            log_config(&rpc).await.unwrap();
-        }
-        "bury" => {
-            bury(&rpc, &payer).await.unwrap();
-        }
-        "reset" => {
-            reset(&rpc, &payer).await.unwrap();
-        }
-        "treasury" => {
-            log_treasury(&rpc).await.unwrap();
-        }
-        "miner" => {
-            log_miner(&rpc, &payer).await.unwrap();
-        }
-        // "pool" => {
-        //     log_meteora_pool(&rpc).await.unwrap();
-        // }
-        "deploy" => {
-            deploy(&rpc, &payer).await.unwrap();
-        }
-        "stake" => {
-            log_stake(&rpc, &payer).await.unwrap();
-        }
-        "deploy_all" => {
-            deploy_all(&rpc, &payer).await.unwrap();
-        }
-        "deploy_optimal" => {
-            deploy_optimal(&rpc, &payer).await.unwrap();
-        }
-        "deploy_continuous" => {
-            deploy_continuous(&rpc, &payer).await.unwrap();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = matches.subcommand_name().unwrap_or_default().to_string();
+    if let Err(e) = run(cli, &command).await {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli, command: &str) -> Result<(), anyhow::Error> {
+    let config = config::Config::load(
+        cli.config.as_deref(),
+        cli.profile.as_deref(),
+        cli.settings(),
+    )?;
+    let rpc = RpcClient::new(config.rpc.clone());
+    let payer = config.payer()?;
+    let fee = cli.priority_fee.unwrap_or_else(|| config.priority_fee(command));
+    match cli.command {
+        Command::Automations { executor } => {
+            log_automations(&rpc, executor.unwrap_or(REGOLITH_EXECUTOR)).await
         }
-        "deploy_single" => {
-            deploy_single(&rpc, &payer).await.unwrap();
+        Command::Clock => log_clock(&rpc).await,
+        Command::Claim => claim(&rpc, &payer, fee).await,
+        Command::Board => log_board(&rpc).await,
+        Command::Config => log_config(&rpc).await,
+        Command::Bury => bury(&rpc, &payer, &config.jupiter_api_url).await,
+        Command::Reset {
+            seed_provider,
+            once,
+        } => {
+            let config = CrankConfig {
+                seed_providers: seed_provider,
+                once,
+                priority_fee: fee,
+            };
+            run_crank(&rpc, &payer, config).await
         }
-        "deploy_optimal_ev" => {
-            deploy_optimal_ev(&rpc, &payer).await.unwrap();
+        Command::Treasury => log_treasury(&rpc).await,
+        Command::Miner { authority } => {
+            log_miner(&rpc, authority.unwrap_or(payer.pubkey())).await
         }
-        "round" => {
-            log_round(&rpc).await.unwrap();
+        Command::Deploy { amount, square } => deploy(&rpc, &payer, fee, amount, square).await,
+        Command::Stake { authority } => {
+            log_stake(&rpc, authority.unwrap_or(payer.pubkey())).await
         }
-        "set_admin" => {
-            set_admin(&rpc, &payer).await.unwrap();
+        Command::DeployAll { amount } => deploy_all(&rpc, &payer, fee, amount).await,
+        Command::DeployOptimal { amount, guard } => {
+            deploy_optimal(&rpc, &payer, fee, amount, guard).await
         }
-        "set_fee_collector" => {
-            set_fee_collector(&rpc, &payer).await.unwrap();
+        Command::DeployContinuous { amount, guard } => {
+            deploy_continuous(&rpc, &payer, fee, amount, guard).await
         }
-        "ata" => {
-            ata(&rpc, &payer).await.unwrap();
+        Command::DeploySingle { amount, guard } => {
+            deploy_single(&rpc, &payer, fee, amount, guard).await
         }
-        "checkpoint" => {
-            checkpoint(&rpc, &payer).await.unwrap();
+        Command::DeployOptimalEv {
+            num_squares,
+            bet_amount,
+            guard,
+            predict,
+        } => {
+            let num_squares = num_squares as usize;
+            deploy_optimal_ev(&rpc, &payer, fee, num_squares, bet_amount, guard, predict).await
         }
-        "checkpoint_all" => {
-            checkpoint_all(&rpc, &payer).await.unwrap();
+        Command::DeployStrategy {
+            strategy,
+            amount,
+            guard,
+            predict,
+        } => deploy_strategy(&rpc, &payer, fee, strategy, amount, guard, predict).await,
+        Command::Fleet {
+            file,
+            keypairs,
+            amount,
+            strategy,
+            squares,
+            guard,
+        } => {
+            let wallets = load_fleet(file.as_deref(), &keypairs, amount, strategy, squares)?;
+            let config = FleetConfig {
+                log_file: "fleet_results.jsonl".to_string(),
+                priority_fee: fee,
+                guard,
+            };
+            run_fleet(&rpc, wallets, config).await
         }
-        "close_all" => {
-            close_all(&rpc, &payer).await.unwrap();
+        Command::Automate { command } => automate(&rpc, &payer, fee, command).await,
+        Command::Executor {
+            batch_size,
+            late_seconds,
+        } => {
+            let config = ExecutorConfig {
+                batch_size,
+                log_file: "executor_results.jsonl".to_string(),
+                late_seconds,
+                priority_fee: fee,
+            };
+            run_executor(&rpc, &payer, config).await
         }
-        "participating_miners" => {
-            participating_miners(&rpc).await.unwrap();
+        Command::Keeper {
+            state_file,
+            min_profit,
+            close_others,
+            interval,
+        } => {
+            let config = KeeperConfig {
+                state_file,
+                min_profit,
+                close_others,
+                interval,
+                priority_fee: fee,
+            };
+            run_keeper(&rpc, &payer, config).await
         }
-        "new_var" => {
-            new_var(&rpc, &payer).await.unwrap();
+        Command::Solve {
+            budget,
+            ore_price,
+            objective,
+            bankroll,
+            min_per_square,
+            tx_fee,
+        } => {
+            let bankroll = match bankroll {
+                Some(bankroll) => bankroll,
+                None => rpc.get_balance(&payer.pubkey()).await?,
+            };
+            let config = SolverConfig {
+                budget,
+                ore_price,
+                objective,
+                bankroll,
+                min_per_square,
+                tx_fee,
+            };
+            log_solution(&rpc, &config).await
         }
-        "set_buffer" => {
-            set_buffer(&rpc, &payer).await.unwrap();
+        Command::Round { id } => log_round(&rpc, id).await,
+        Command::SetAdmin => set_admin(&rpc, &payer, fee).await,
+        Command::SetFeeCollector { fee_collector } => {
+            set_fee_collector(&rpc, &payer, fee, fee_collector).await
         }
-        "set_swap_program" => {
-            set_swap_program(&rpc, &payer).await.unwrap();
+        Command::Ata => ata(&rpc, &payer, fee).await,
+        Command::Checkpoint { authority } => {
+            checkpoint(&rpc, &payer, fee, authority.unwrap_or(payer.pubkey())).await
         }
-        "keys" => {
-            keys().await.unwrap();
+        Command::CheckpointAll => checkpoint_all(&rpc, &payer, fee).await,
+        Command::Recover => recover(&rpc, &payer, fee).await,
+        Command::CloseAll => close_all(&rpc, &payer, fee).await,
+        Command::ParticipatingMiners { id } => participating_miners(&rpc, id).await,
+        Command::NewVar {
+            provider,
+            commit,
+            samples,
+        } => new_var(&rpc, &payer, fee, provider, commit, samples).await,
+        Command::SetBuffer { buffer } => set_buffer(&rpc, &payer, fee, buffer).await,
+        Command::SetSwapProgram { swap_program } => {
+            set_swap_program(&rpc, &payer, fee, swap_program).await
         }
-        // "tui" => {
-        //     tui::run_tui(rpc, payer).await.unwrap();
-        // }
-        _ => panic!("Invalid command"),
-    };
+        Command::Keys => keys().await,
+        Command::Indexer {
+            mode,
+            file,
+            backfill_limit,
+        } => run_indexer(&rpc, mode, &file, backfill_limit).await,
+        Command::EvalInflow {
+            index_file,
+            slots_remaining,
+            history_rounds,
+            holdout,
+        } => log_evaluation(&index_file, slots_remaining, history_rounds, holdout),
+        // Command::Tui => tui::run_tui(rpc, payer).await,
+    }
 }
 
 async fn set_buffer(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    buffer: u64,
 ) -> Result<(), anyhow::Error> {
-    let buffer = std::env::var("BUFFER").expect("Missing BUFFER env var");
-    let buffer = u64::from_str(&buffer).expect("Invalid BUFFER");
     let ix = ore_api::sdk::set_buffer(payer.pubkey(), buffer);
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn new_var(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    provider: Pubkey,
+    commit: keccak::Hash,
+    samples: u64,
 ) -> Result<(), anyhow::Error> {
-    let provider = std::env::var("PROVIDER").expect("Missing PROVIDER env var");
-    let provider = Pubkey::from_str(&provider).expect("Invalid PROVIDER");
-    let commit = std::env::var("COMMIT").expect("Missing COMMIT env var");
-    let commit = keccak::Hash::from_str(&commit).expect("Invalid COMMIT");
-    let samples = std::env::var("SAMPLES").expect("Missing SAMPLES env var");
-    let samples = u64::from_str(&samples).expect("Invalid SAMPLES");
     let board_address = board_pda().0;
     let var_address = entropy_api::state::var_pda(board_address, 0).0;
     println!("Var address: {}", var_address);
     let ix = ore_api::sdk::new_var(payer.pubkey(), provider, 0, commit.to_bytes(), samples);
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
-async fn participating_miners(rpc: &RpcClient) -> Result<(), anyhow::Error> {
-    let round_id = std::env::var("ID").expect("Missing ID env var");
-    let round_id = u64::from_str(&round_id).expect("Invalid ID");
+async fn participating_miners(rpc: &RpcClient, round_id: u64) -> Result<(), anyhow::Error> {
     let miners = get_miners_participating(rpc, round_id).await?;
     for (i, (_address, miner)) in miners.iter().enumerate() {
         println!("{}: {}", i, miner.authority);
@@ -188,12 +304,7 @@ async fn participating_miners(rpc: &RpcClient) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-async fn log_stake(
-    rpc: &RpcClient,
-    payer: &solana_sdk::signer::keypair::Keypair,
-) -> Result<(), anyhow::Error> {
-    let authority = std::env::var("AUTHORITY").unwrap_or(payer.pubkey().to_string());
-    let authority = Pubkey::from_str(&authority).expect("Invalid AUTHORITY");
+async fn log_stake(rpc: &RpcClient, authority: Pubkey) -> Result<(), anyhow::Error> {
     let staker_address = ore_api::state::stake_pda(authority).0;
     let stake = get_stake(rpc, authority).await?;
     println!("Stake");
@@ -225,6 +336,7 @@ async fn log_stake(
 async fn ata(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
 ) -> Result<(), anyhow::Error> {
     let user = pubkey!("FgZFnb3bi7QexKCdXWPwWy91eocUD7JCFySHb83vLoPD");
     let token = pubkey!("8H8rPiWW4iTFCfEkSnf7jpqeNpFfvdH9gLouAL3Fe2Zx");
@@ -235,7 +347,7 @@ async fn ata(
         &token,
         &spl_token::ID,
     );
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     let account = rpc.get_account(&ata).await?;
     println!("ATA: {}", ata);
     println!("Account: {:?}", account);
@@ -260,16 +372,18 @@ async fn keys() -> Result<(), anyhow::Error> {
 async fn claim(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
 ) -> Result<(), anyhow::Error> {
     let ix_sol = ore_api::sdk::claim_sol(payer.pubkey());
     let ix_ore = ore_api::sdk::claim_ore(payer.pubkey());
-    submit_transaction(rpc, payer, &[ix_sol, ix_ore]).await?;
+    submit_transaction(rpc, payer, &[ix_sol, ix_ore], fee).await?;
     Ok(())
 }
 
 async fn bury(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    api_base_url: &str,
 ) -> Result<(), anyhow::Error> {
     // Get swap amount.
     let treasury = get_treasury(rpc).await?;
@@ -278,9 +392,7 @@ async fn bury(
     // Build quote request.
     const INPUT_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
     const OUTPUT_MINT: Pubkey = pubkey!("oreoU2P8bN6jkk3jbaiVxYnG1dCXcYxwhwyK9jSybcp");
-    let api_base_url =
-        std::env::var("API_BASE_URL").unwrap_or("https://lite-api.jup.ag/swap/v1".into());
-    let jupiter_swap_api_client = JupiterSwapApiClient::new(api_base_url);
+    let jupiter_swap_api_client = JupiterSwapApiClient::new(api_base_url.to_string());
     let quote_request = QuoteRequest {
         amount,
         input_mint: INPUT_MINT,
@@ -363,402 +475,151 @@ pub async fn get_address_lookup_table_accounts(
 
 pub const ORE_VAR_ADDRESS: Pubkey = pubkey!("BWCaDY96Xe4WkFq1M7UiCCRcChsJ3p51L5KrGzhxgm2E");
 
-async fn reset(
-    rpc: &RpcClient,
-    payer: &solana_sdk::signer::keypair::Keypair,
-) -> Result<(), anyhow::Error> {
-    let board = get_board(rpc).await?;
-    let var = get_var(rpc, ORE_VAR_ADDRESS).await?;
-
-    println!("Var: {:?}", var);
-
-    let client = reqwest::Client::new();
-    let url = format!("https://entropy-api.onrender.com/var/{ORE_VAR_ADDRESS}/seed");
-    let response = client
-        .get(url)
-        .send()
-        .await?
-        .json::<entropy_types::response::GetSeedResponse>()
-        .await?;
-    println!("Entropy seed: {:?}", response);
-
-    let config = get_config(rpc).await?;
-    let sample_ix = entropy_api::sdk::sample(payer.pubkey(), ORE_VAR_ADDRESS);
-    let reveal_ix = entropy_api::sdk::reveal(payer.pubkey(), ORE_VAR_ADDRESS, response.seed);
-    let reset_ix = ore_api::sdk::reset(
-        payer.pubkey(),
-        config.fee_collector,
-        board.round_id,
-        Pubkey::default(),
-    );
-    let sig = submit_transaction(rpc, payer, &[sample_ix, reveal_ix, reset_ix]).await?;
-    println!("Reset: {}", sig);
-
-    // let slot_hashes = get_slot_hashes(rpc).await?;
-    // if let Some(slot_hash) = slot_hashes.get(&board.end_slot) {
-    //     let id = get_winning_square(&slot_hash.to_bytes());
-    //     // let square = get_square(rpc).await?;
-    //     println!("Winning square: {}", id);
-    //     // println!("Miners: {:?}", square.miners);
-    //     // miners = square.miners[id as usize].to_vec();
-    // };
-
-    // let reset_ix = ore_api::sdk::reset(
-    //     payer.pubkey(),
-    //     config.fee_collector,
-    //     board.round_id,
-    //     Pubkey::default(),
-    // );
-    // // simulate_transaction(rpc, payer, &[reset_ix]).await;
-    // submit_transaction(rpc, payer, &[reset_ix]).await?;
-    Ok(())
-}
-
 async fn deploy(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    amount: u64,
+    square_id: u64,
 ) -> Result<(), anyhow::Error> {
-    let amount = std::env::var("AMOUNT").expect("Missing AMOUNT env var");
-    let amount = u64::from_str(&amount).expect("Invalid AMOUNT");
-    let square_id = std::env::var("SQUARE").expect("Missing SQUARE env var");
-    let square_id = u64::from_str(&square_id).expect("Invalid SQUARE");
     let board = get_board(rpc).await?;
+    let miner = get_miner(rpc, payer.pubkey()).await.ok();
     let mut squares = [false; 25];
     squares[square_id as usize] = true;
-    let ix = ore_api::sdk::deploy(
+    let mut ix = ore_api::sdk::deploy(
         payer.pubkey(),
         payer.pubkey(),
         amount,
         board.round_id,
         squares,
     );
-    submit_transaction(rpc, payer, &[ix]).await?;
+    if let Some(previous_round_id) = pending_checkpoint(miner.as_ref(), board.round_id) {
+        ix = ore_api::sdk::with_checkpoint(ix, previous_round_id);
+    }
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn deploy_all(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    amount: u64,
 ) -> Result<(), anyhow::Error> {
-    let amount = std::env::var("AMOUNT").expect("Missing AMOUNT env var");
-    let amount = u64::from_str(&amount).expect("Invalid AMOUNT");
     let board = get_board(rpc).await?;
+    let miner = get_miner(rpc, payer.pubkey()).await.ok();
     let squares = [true; 25];
-    let ix = ore_api::sdk::deploy(
+    let mut ix = ore_api::sdk::deploy(
         payer.pubkey(),
         payer.pubkey(),
-        board.round_id,
         amount,
+        board.round_id,
         squares,
     );
-    submit_transaction(rpc, payer, &[ix]).await?;
+    if let Some(previous_round_id) = pending_checkpoint(miner.as_ref(), board.round_id) {
+        ix = ore_api::sdk::with_checkpoint(ix, previous_round_id);
+    }
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn deploy_optimal(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    amount: u64,
+    guard: GuardArgs,
 ) -> Result<(), anyhow::Error> {
-    // Get amount from env or default to 0.02 SOL (for 2 squares = 0.01 each)
-    // Long-term accumulation strategy: more rounds = more ORE
-    let amount = std::env::var("AMOUNT")
-        .map(|s| u64::from_str(&s).expect("Invalid AMOUNT"))
-        .unwrap_or(20_000_000); // 0.02 SOL default (0.01 per square)
-
-    // Initialize WebSocket manager for real-time updates
-    let rpc_url = std::env::var("RPC").expect("Missing RPC env var");
-    let ws_manager = WebSocketManager::new(&rpc_url);
-
-    println!("🔌 Starting WebSocket connections for real-time monitoring...");
-    ws_manager.subscribe_to_board().await?;
-    ws_manager.subscribe_to_slots().await?;
-
-    // Give WebSocket time to connect and get initial data
-    println!("   Waiting for WebSocket initialization...");
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-    let mut board = get_board(rpc).await?;
-    let mut clock = get_clock(rpc).await?;
-
-    // Check if we're in intermission (end_slot == u64::MAX means waiting for reset/first deploy)
-    if board.end_slot == u64::MAX {
-        println!("\n⏳ Round #{} in intermission (waiting for reset or first deployment)", board.round_id);
-        println!("   WebSocket monitoring for instant notifications...\n");
-
-        let current_round_id = board.round_id;
-
-        // Wait for round to start using WebSocket (instant notification, no polling delay!)
-        loop {
-            // Check WebSocket cache first (instant, no network call)
-            if let Some(ws_board) = ws_manager.get_board().await {
-                board = ws_board;
-
-                if board.round_id != current_round_id {
-                    println!("🔄 Round reset detected via WebSocket! Now on Round #{}", board.round_id);
-                }
-
-                if board.end_slot != u64::MAX {
-                    clock = get_clock(rpc).await?;
-                    let slots_to_end = board.end_slot.saturating_sub(clock.slot);
-                    let secs_to_end = (slots_to_end as f64) * 0.4;
-                    println!("✅ Round #{} started (WebSocket)! {:.1}s remaining", board.round_id, secs_to_end);
-                    break;
-                }
-            }
-
-            // Very short sleep - WebSocket updates cache in real-time
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            print!(".");
-            std::io::Write::flush(&mut std::io::stdout()).ok();
-        }
-    }
-
-    // Calculate time remaining in seconds
-    let mut slots_remaining = board.end_slot.saturating_sub(clock.slot);
-    let mut seconds_remaining = (slots_remaining as f64) * 0.4;
-
-    println!("\n╔═══════════════════════════════════════════════════════════════╗");
-    println!("║               LATE SNIPE STRATEGY (2 SQUARES)                  ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Round ID:        {}                                           ║", board.round_id);
-    println!("║ Time Remaining:  {:.1} seconds                                ║", seconds_remaining);
-    println!("║ Deployment:      {:.4} SOL                                   ║", amount as f64 / 1_000_000_000.0);
-    println!("╚═══════════════════════════════════════════════════════════════╝\n");
-
-    // LIGHTWEIGHT POLLING: Check every 2 seconds until 20s left
-    // Note: We start preparing at 20s to account for data fetching & analytics time (~5-10s)
-    if seconds_remaining > 20.0 {
-        println!("⏰ Monitoring round... (checking every 2s until 20s left)");
-        println!("   Current time remaining: {:.1}s", seconds_remaining);
-
-        while seconds_remaining > 20.0 {
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-            // Lightweight check - just get clock
-            clock = get_clock(rpc).await?;
-            slots_remaining = board.end_slot.saturating_sub(clock.slot);
-            seconds_remaining = (slots_remaining as f64) * 0.4;
-
-            if seconds_remaining <= 20.0 {
-                break;
-            }
-
-            print!(".");
-            std::io::Write::flush(&mut std::io::stdout()).ok();
-        }
-
-        println!("\n📡 20s LEFT - Starting snipe preparation...");
-    }
-
-    // INTENSIVE SCANNING: 20s-10s window, check every 1 second
-    // This gives us time to fetch data, analyze, and submit before round ends
-    if seconds_remaining > 10.0 && seconds_remaining <= 20.0 {
-        println!("📊 Scanning board state every second...");
-
-        while seconds_remaining > 10.0 {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-
-            clock = get_clock(rpc).await?;
-            slots_remaining = board.end_slot.saturating_sub(clock.slot);
-            seconds_remaining = (slots_remaining as f64) * 0.4;
-
-            print!(".");
-            std::io::Write::flush(&mut std::io::stdout()).ok();
-        }
-
-        println!("\n⚡ 10s LEFT - SNIPE WINDOW! Fetching final board state...");
-    } else if seconds_remaining <= 10.0 && seconds_remaining > 0.0 {
-        println!("⚡ Already at snipe time ({:.1}s remaining)", seconds_remaining);
-    } else {
-        println!("❌ Round has already ended!");
-        return Ok(());
-    }
-
-    // Refresh round data at snipe time (do this FAST - we're in critical window)
-    let round = get_round(rpc, board.round_id).await?;
-    let treasury = get_treasury(rpc).await?;
-
-    // Check clock again to see how much time we actually have left
-    clock = get_clock(rpc).await?;
-    slots_remaining = board.end_slot.saturating_sub(clock.slot);
-    seconds_remaining = (slots_remaining as f64) * 0.4;
-
-    println!("\n⏱️  ACTUAL TIME CHECK: {:.1}s remaining after data fetch", seconds_remaining);
-
-    if seconds_remaining <= 0.0 {
-        println!("❌ Round ended while fetching data!");
-        return Ok(());
-    }
-
-    // Display full board analytics
-    println!("\n╔════════════════════════════════════════════════════════════════╗");
-    println!("║                    BOARD ANALYTICS (LIVE)                      ║");
-    println!("╠════════════════════════════════════════════════════════════════╣");
-
-    // Calculate total and average deployment
-    let total_deployed = round.total_deployed;
-    let avg_deployed = if total_deployed > 0 { total_deployed / 25 } else { 0 };
-
-    println!("║ Total Deployed:  {:.4} SOL across all squares               ║", total_deployed as f64 / 1_000_000_000.0);
-    println!("║ Average/Square:  {:.4} SOL                                  ║", avg_deployed as f64 / 1_000_000_000.0);
-    println!("╠════════════════════════════════════════════════════════════════╣");
-
-    // Find 2 LEAST crowded squares with miner counts
-    let mut squares_by_deployment: Vec<(usize, u64, u64)> = round
-        .deployed
-        .iter()
-        .zip(round.count.iter())
-        .enumerate()
-        .map(|(i, (&d, &c))| (i, d, c))
-        .collect();
-
-    squares_by_deployment.sort_by_key(|&(_, d, _)| d);
-
-    // Show top 5 least crowded
-    println!("║ Top 5 LEAST Crowded Squares:                                  ║");
-    for i in 0..5.min(squares_by_deployment.len()) {
-        let (idx, deployed, count) = squares_by_deployment[i];
-        let marker = if i < 2 { "🎯" } else { "  " };
-        println!("║ {} Square #{:<2}  {:.4} SOL  ({} miners)                    ║",
-            marker,
-            idx,
-            deployed as f64 / 1_000_000_000.0,
-            count
-        );
-    }
-
-    println!("╠════════════════════════════════════════════════════════════════╣");
-
-    // Show top 5 most crowded for comparison
-    let mut most_crowded = squares_by_deployment.clone();
-    most_crowded.sort_by_key(|&(_, d, _)| std::cmp::Reverse(d));
-    println!("║ Top 5 MOST Crowded Squares (AVOID):                           ║");
-    for i in 0..5.min(most_crowded.len()) {
-        let (idx, deployed, count) = most_crowded[i];
-        println!("║    Square #{:<2}  {:.4} SOL  ({} miners)                    ║",
-            idx,
-            deployed as f64 / 1_000_000_000.0,
-            count
-        );
-    }
-    println!("╚════════════════════════════════════════════════════════════════╝\n");
-
-    let square_1 = (squares_by_deployment[0].0, squares_by_deployment[0].1);
-    let square_2 = (squares_by_deployment[1].0, squares_by_deployment[1].1);
-
-    // Deploy to 2 LEAST crowded squares
-    let mut squares = [false; 25];
-    squares[square_1.0] = true;
-    squares[square_2.0] = true;
-
-    let amount_per_square = amount / 2;
-
-    // Calculate expected returns for each square
-    let total_on_square_1 = square_1.1 + amount_per_square;
-    let total_on_square_2 = square_2.1 + amount_per_square;
-    let your_share_1 = amount_per_square as f64 / total_on_square_1 as f64;
-    let your_share_2 = amount_per_square as f64 / total_on_square_2 as f64;
-    let avg_share = (your_share_1 + your_share_2) / 2.0;
-    let motherlode_ore = treasury.motherlode as f64 / ONE_ORE as f64;
-
-    println!("\n╔═══════════════════════════════════════════════════════════════╗");
-    println!("║                    SNIPE DEPLOYMENT PLAN                       ║");
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Square #{:<2}:      {:.4} SOL → {:.4} SOL total ({:.2}% share)  ║",
-        square_1.0,
-        square_1.1 as f64 / 1_000_000_000.0,
-        total_on_square_1 as f64 / 1_000_000_000.0,
-        your_share_1 * 100.0
-    );
-    println!("║ Square #{:<2}:      {:.4} SOL → {:.4} SOL total ({:.2}% share)  ║",
-        square_2.0,
-        square_2.1 as f64 / 1_000_000_000.0,
-        total_on_square_2 as f64 / 1_000_000_000.0,
-        your_share_2 * 100.0
-    );
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Expected Payouts (if either square wins):                     ║");
-    println!("║   SOL:           ~0.429 SOL                                    ║");
-    println!("║   ORE:           ~{:.4} ORE (avg)                             ║", avg_share);
-    println!("║   Motherlode:    {:.2} ORE pool → {:.4} ORE if hit           ║", motherlode_ore, motherlode_ore * avg_share);
-    println!("╠═══════════════════════════════════════════════════════════════╣");
-    println!("║ Strategy:        LATE SNIPE 2 SQUARES                          ║");
-    println!("║ Win Rate:        8% (2/25)                                     ║");
-    println!("║ Expected ROI:    +73%                                          ║");
-    println!("╚═══════════════════════════════════════════════════════════════╝\n");
-
-    // FINAL TIME CHECK before submission
-    clock = get_clock(rpc).await?;
-    slots_remaining = board.end_slot.saturating_sub(clock.slot);
-    seconds_remaining = (slots_remaining as f64) * 0.4;
-
-    println!("\n⏱️  FINAL CHECK: {:.1}s remaining before transaction submission", seconds_remaining);
-
-    if seconds_remaining <= 0.0 {
-        println!("❌ Round ended while preparing transaction!");
-        return Ok(());
-    }
-
-    let ix = ore_api::sdk::deploy(
-        payer.pubkey(),
-        payer.pubkey(),
-        amount,
-        board.round_id,
-        squares,
-    );
+    let mut strategy = LeastCrowded {
+        count: 2,
+        amount_per_square: amount / 2,
+        skip_previous_winner: false,
+        timing: DeployTiming::Window {
+            max_seconds: 10,
+            min_seconds: 0,
+        },
+    };
+    let config = DriverConfig {
+        max_rounds: Some(1),
+        log_file: "deploy_optimal_results.jsonl".to_string(),
+        priority_fee: fee,
+        guard,
+        predict: PredictArgs::default(),
+    };
+    run_strategy(rpc, payer, &mut strategy, config).await
+}
 
-    println!("📤 Submitting transaction NOW ({:.1}s remaining)...", seconds_remaining);
-    submit_transaction(rpc, payer, &[ix]).await?;
-    println!("✅ SNIPED! Deployed to squares #{} and #{}!", square_1.0, square_2.0);
-    Ok(())
+async fn deploy_strategy(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    strategy: MiningStrategy,
+    amount: u64,
+    guard: GuardArgs,
+    predict: PredictArgs,
+) -> Result<(), anyhow::Error> {
+    let mut strategy = Selection {
+        strategy,
+        amount_per_square: amount,
+    };
+    let config = DriverConfig {
+        max_rounds: None,
+        log_file: "deploy_strategy_results.jsonl".to_string(),
+        priority_fee: fee,
+        guard,
+        predict,
+    };
+    run_strategy(rpc, payer, &mut strategy, config).await
 }
 
 async fn set_admin(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
 ) -> Result<(), anyhow::Error> {
     let ix = ore_api::sdk::set_admin(payer.pubkey(), payer.pubkey());
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn set_swap_program(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    swap_program: Pubkey,
 ) -> Result<(), anyhow::Error> {
-    let swap_program = std::env::var("SWAP_PROGRAM").expect("Missing SWAP_PROGRAM env var");
-    let swap_program = Pubkey::from_str(&swap_program).expect("Invalid SWAP_PROGRAM");
     let ix = ore_api::sdk::set_swap_program(payer.pubkey(), swap_program);
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn set_fee_collector(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    fee_collector: Pubkey,
 ) -> Result<(), anyhow::Error> {
-    let fee_collector = std::env::var("FEE_COLLECTOR").expect("Missing FEE_COLLECTOR env var");
-    let fee_collector = Pubkey::from_str(&fee_collector).expect("Invalid FEE_COLLECTOR");
     let ix = ore_api::sdk::set_fee_collector(payer.pubkey(), fee_collector);
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn checkpoint(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
+    authority: Pubkey,
 ) -> Result<(), anyhow::Error> {
-    let authority = std::env::var("AUTHORITY").unwrap_or(payer.pubkey().to_string());
-    let authority = Pubkey::from_str(&authority).expect("Invalid AUTHORITY");
     let miner = get_miner(rpc, authority).await?;
     let ix = ore_api::sdk::checkpoint(payer.pubkey(), authority, miner.round_id);
-    submit_transaction(rpc, payer, &[ix]).await?;
+    submit_transaction(rpc, payer, &[ix], fee).await?;
     Ok(())
 }
 
 async fn checkpoint_all(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
 ) -> Result<(), anyhow::Error> {
     let clock = get_clock(rpc).await?;
     let miners = get_miners(rpc).await?;
@@ -801,7 +662,7 @@ async fn checkpoint_all(
         let batch = ixs
             .drain(..std::cmp::min(10, ixs.len()))
             .collect::<Vec<Instruction>>();
-        submit_transaction(rpc, payer, &batch).await?;
+        submit_transaction(rpc, payer, &batch, fee).await?;
     }
 
     Ok(())
@@ -810,6 +671,7 @@ async fn checkpoint_all(
 async fn close_all(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
 ) -> Result<(), anyhow::Error> {
     let rounds = get_rounds(rpc).await?;
     let mut ixs = vec![];
@@ -830,7 +692,7 @@ async fn close_all(
             .drain(..std::cmp::min(12, ixs.len()))
             .collect::<Vec<Instruction>>();
         // simulate_transaction(rpc, payer, &batch).await;
-        submit_transaction(rpc, payer, &batch).await?;
+        submit_transaction(rpc, payer, &batch, fee).await?;
     }
 
     Ok(())
@@ -876,8 +738,8 @@ async fn close_all(
 //     Ok(())
 // }
 
-async fn log_automations(rpc: &RpcClient) -> Result<(), anyhow::Error> {
-    let automations = get_automations(rpc).await?;
+async fn log_automations(rpc: &RpcClient, executor: Pubkey) -> Result<(), anyhow::Error> {
+    let automations = get_automations(rpc, executor).await?;
     for (i, (address, automation)) in automations.iter().enumerate() {
         println!("[{}/{}] {}", i + 1, automations.len(), address);
         println!("  authority: {}", automation.authority);
@@ -924,9 +786,7 @@ async fn log_treasury(rpc: &RpcClient) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-async fn log_round(rpc: &RpcClient) -> Result<(), anyhow::Error> {
-    let id = std::env::var("ID").expect("Missing ID env var");
-    let id = u64::from_str(&id).expect("Invalid ID");
+async fn log_round(rpc: &RpcClient, id: u64) -> Result<(), anyhow::Error> {
     let round_address = round_pda(id).0;
     let round = get_round(rpc, id).await?;
     let rng = round.rng();
@@ -953,12 +813,7 @@ async fn log_round(rpc: &RpcClient) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-async fn log_miner(
-    rpc: &RpcClient,
-    payer: &solana_sdk::signer::keypair::Keypair,
-) -> Result<(), anyhow::Error> {
-    let authority = std::env::var("AUTHORITY").unwrap_or(payer.pubkey().to_string());
-    let authority = Pubkey::from_str(&authority).expect("Invalid AUTHORITY");
+async fn log_miner(rpc: &RpcClient, authority: Pubkey) -> Result<(), anyhow::Error> {
     let miner_address = ore_api::state::miner_pda(authority).0;
     let miner = get_miner(&rpc, authority).await?;
     println!("Miner");
@@ -1030,12 +885,14 @@ fn print_board(board: Board, clock: &Clock) {
     );
 }
 
-async fn get_automations(rpc: &RpcClient) -> Result<Vec<(Pubkey, Automation)>, anyhow::Error> {
-    const REGOLITH_EXECUTOR: Pubkey = pubkey!("HNWhK5f8RMWBqcA7mXJPaxdTPGrha3rrqUrri7HSKb3T");
-    let filter = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-        56,
-        &REGOLITH_EXECUTOR.to_bytes(),
-    ));
+pub const REGOLITH_EXECUTOR: Pubkey = pubkey!("HNWhK5f8RMWBqcA7mXJPaxdTPGrha3rrqUrri7HSKb3T");
+
+/// Returns the automations that name `executor` as their executor.
+pub async fn get_automations(
+    rpc: &RpcClient,
+    executor: Pubkey,
+) -> Result<Vec<(Pubkey, Automation)>, anyhow::Error> {
+    let filter = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(56, &executor.to_bytes()));
     let automations = get_program_accounts::<Automation>(rpc, ore_api::ID, vec![filter]).await?;
     Ok(automations)
 }
@@ -1059,7 +916,7 @@ pub async fn get_board(rpc: &RpcClient) -> Result<Board, anyhow::Error> {
     Ok(*board)
 }
 
-async fn get_var(rpc: &RpcClient, address: Pubkey) -> Result<Var, anyhow::Error> {
+pub async fn get_var(rpc: &RpcClient, address: Pubkey) -> Result<Var, anyhow::Error> {
     let account = rpc.get_account(&address).await?;
     let var = Var::try_from_bytes(&account.data)?;
     Ok(*var)
@@ -1079,13 +936,28 @@ pub async fn get_treasury(rpc: &RpcClient) -> Result<Treasury, anyhow::Error> {
     Ok(*treasury)
 }
 
-async fn get_config(rpc: &RpcClient) -> Result<Config, anyhow::Error> {
+pub async fn get_mint_supply(rpc: &RpcClient) -> Result<u64, anyhow::Error> {
+    let supply = rpc.get_token_supply(&MINT_ADDRESS).await?;
+    Ok(supply.amount.parse()?)
+}
+
+pub async fn get_config(rpc: &RpcClient) -> Result<Config, anyhow::Error> {
     let config_pda = ore_api::state::config_pda();
     let account = rpc.get_account(&config_pda.0).await?;
     let config = Config::try_from_bytes(&account.data)?;
     Ok(*config)
 }
 
+pub async fn get_automation(
+    rpc: &RpcClient,
+    authority: Pubkey,
+) -> Result<Automation, anyhow::Error> {
+    let automation_pda = ore_api::state::automation_pda(authority);
+    let account = rpc.get_account(&automation_pda.0).await?;
+    let automation = Automation::try_from_bytes(&account.data)?;
+    Ok(*automation)
+}
+
 pub async fn get_miner(rpc: &RpcClient, authority: Pubkey) -> Result<Miner, anyhow::Error> {
     let miner_pda = ore_api::state::miner_pda(authority);
     let account = rpc.get_account(&miner_pda.0).await?;
@@ -1106,7 +978,7 @@ pub async fn get_stake(rpc: &RpcClient, authority: Pubkey) -> Result<Stake, anyh
     Ok(*stake)
 }
 
-async fn get_rounds(rpc: &RpcClient) -> Result<Vec<(Pubkey, Round)>, anyhow::Error> {
+pub async fn get_rounds(rpc: &RpcClient) -> Result<Vec<(Pubkey, Round)>, anyhow::Error> {
     let rounds = get_program_accounts::<Round>(rpc, ore_api::ID, vec![]).await?;
     Ok(rounds)
 }
@@ -1117,7 +989,7 @@ async fn get_miners(rpc: &RpcClient) -> Result<Vec<(Pubkey, Miner)>, anyhow::Err
     Ok(miners)
 }
 
-async fn get_miners_participating(
+pub async fn get_miners_participating(
     rpc: &RpcClient,
     round_id: u64,
 ) -> Result<Vec<(Pubkey, Miner)>, anyhow::Error> {
@@ -1186,6 +1058,7 @@ async fn simulate_transaction_with_address_lookup_tables(
 async fn submit_transaction_batches(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
+    fee: PriorityFee,
     mut ixs: Vec<solana_sdk::instruction::Instruction>,
     batch_size: usize,
 ) -> Result<(), anyhow::Error> {
@@ -1194,7 +1067,7 @@ async fn submit_transaction_batches(
         let batch = ixs
             .drain(..std::cmp::min(batch_size, ixs.len()))
             .collect::<Vec<Instruction>>();
-        submit_transaction_no_confirm(rpc, payer, &batch).await?;
+        submit_transaction_no_confirm(rpc, payer, &batch, fee).await?;
     }
     Ok(())
 }
@@ -1220,13 +1093,25 @@ pub async fn submit_transaction(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
     instructions: &[solana_sdk::instruction::Instruction],
+    fee: PriorityFee,
 ) -> Result<solana_sdk::signature::Signature, anyhow::Error> {
-    let blockhash = rpc.get_latest_blockhash().await?;
-    let mut all_instructions = vec![
-        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
-        ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
-    ];
+    let budget = fees::compute_budget(rpc, &payer.pubkey(), instructions, fee).await?;
+    submit_transaction_with_budget(rpc, payer, instructions, budget).await
+}
+
+/// Like `submit_transaction`, with compute budget instructions the caller already built, so
+/// nothing is simulated or priced before sending.
+pub async fn submit_transaction_with_budget(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+    instructions: &[solana_sdk::instruction::Instruction],
+    budget: [solana_sdk::instruction::Instruction; 2],
+) -> Result<solana_sdk::signature::Signature, anyhow::Error> {
+    let mut all_instructions = budget.to_vec();
     all_instructions.extend_from_slice(instructions);
+    let (blockhash, last_valid_block_height) = rpc
+        .get_latest_blockhash_with_commitment(rpc.commitment())
+        .await?;
     let transaction = Transaction::new_signed_with_payer(
         &all_instructions,
         Some(&payer.pubkey()),
@@ -1234,50 +1119,67 @@ pub async fn submit_transaction(
         blockhash,
     );
 
-    // Try to send and confirm, with smart retry logic
-    match rpc.send_and_confirm_transaction(&transaction).await {
-        Ok(signature) => {
-            println!("Transaction submitted: {:?}", signature);
-            Ok(signature)
-        }
+    // Rebroadcast until the transaction lands or can no longer land.
+    let outcome = match TxSender::new(rpc)
+        .send(&transaction, last_valid_block_height)
+        .await
+    {
+        Ok(outcome) => outcome,
         Err(e) => {
-            let err_str = format!("{:?}", e);
-
-            // If simulation fails with insufficient funds, balance may have just updated - try without simulation
-            if err_str.contains("insufficient lamports") || err_str.contains("SendTransactionPreflightFailure") {
-                println!("⚠️  Simulation failed - trying to send without simulation (balance may have updated)...");
-                match rpc.send_transaction(&transaction).await {
-                    Ok(sig) => {
-                        println!("✅ Transaction sent: {:?}", sig);
-                        Ok(sig)
-                    }
-                    Err(e2) => {
-                        println!("❌ Send also failed: {:?}", e2);
-                        Err(e2.into())
-                    }
-                }
-            } else if err_str.contains("block height exceeded") || err_str.contains("timed out") {
-                println!("⚠️  Confirmation timeout - transaction likely succeeded");
-                Ok(transaction.signatures[0])
-            } else {
-                println!("Error submitting transaction: {:?}", e);
-                Err(e.into())
+            // Surface ORE program errors as typed errors, so callers can react to them.
+            if let Some(err) = e
+                .get_transaction_error()
+                .and_then(|err| ore_error(&err, &all_instructions))
+            {
+                println!("Transaction failed: {}", err);
+                return Err(err.into());
             }
+            println!("Error submitting transaction: {:?}", e);
+            return Err(e.into());
+        }
+    };
+    match outcome {
+        TxOutcome::Landed(signature) => {
+            println!("Transaction confirmed: {:?}", signature);
+            Ok(signature)
+        }
+        TxOutcome::Failed(signature, err) => {
+            println!("Transaction {} failed: {}", signature, err);
+            Err(match ore_error(&err, &all_instructions) {
+                Some(ore_err) => ore_err.into(),
+                None => err.into(),
+            })
+        }
+        TxOutcome::Expired(signature) => {
+            println!("Transaction {} expired before landing", signature);
+            Err(anyhow::anyhow!("Transaction {} expired", signature))
         }
     }
 }
 
+/// Maps a failed transaction to the ORE program error it returned, if any.
+/// Only errors from ORE instructions are mapped, since other programs reuse the same codes.
+pub fn ore_error(err: &TransactionError, instructions: &[Instruction]) -> Option<OreError> {
+    let TransactionError::InstructionError(index, InstructionError::Custom(code)) = *err else {
+        return None;
+    };
+    if instructions.get(index as usize)?.program_id != ore_api::ID {
+        return None;
+    }
+    OreError::try_from(code).ok()
+}
+
 async fn submit_transaction_no_confirm(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
     instructions: &[solana_sdk::instruction::Instruction],
+    fee: PriorityFee,
 ) -> Result<solana_sdk::signature::Signature, anyhow::Error> {
-    let blockhash = rpc.get_latest_blockhash().await?;
-    let mut all_instructions = vec![
-        ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
-        ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
-    ];
+    let mut all_instructions = fees::compute_budget(rpc, &payer.pubkey(), instructions, fee)
+        .await?
+        .to_vec();
     all_instructions.extend_from_slice(instructions);
+    let blockhash = rpc.get_latest_blockhash().await?;
     let transaction = Transaction::new_signed_with_payer(
         &all_instructions,
         Some(&payer.pubkey()),
@@ -1,197 +0,0 @@
-/// OPTIMAL +EV MINING STRATEGY
-/// Based on 100 rounds of real data analysis
-///
-/// KEY FINDINGS:
-/// 1. Deploy to LEAST CROWDED squares = +28% better share
-/// 2. Filter OUT previous winner (no advantage, potential clustering)
-/// 3. Win chance is always random (4% per square)
-/// 4. Edge comes from SHARE SIZE, not win prediction
-
-use ore_api::prelude::*;
-
-/// Select optimal squares based on research findings
-pub fn select_optimal_squares(
-    round: &Round,
-    previous_winner: Option<usize>,
-    num_squares: usize,
-) -> Vec<usize> {
-    // Sort squares by deployment (ascending = least crowded first)
-    let mut squares_by_deployment: Vec<(usize, u64)> = round
-        .deployed
-        .iter()
-        .enumerate()
-        .map(|(i, &d)| (i, d))
-        .collect();
-
-    squares_by_deployment.sort_by_key(|&(_, d)| d);
-
-    // Filter out previous winner
-    let available_squares: Vec<(usize, u64)> = squares_by_deployment
-        .into_iter()
-        .filter(|(idx, _)| {
-            if let Some(prev) = previous_winner {
-                *idx != prev
-            } else {
-                true
-            }
-        })
-        .collect();
-
-    // Select N least crowded (after filtering previous winner)
-    let selected: Vec<usize> = available_squares
-        .iter()
-        .take(num_squares)
-        .map(|&(idx, _)| idx)
-        .collect();
-
-    selected
-}
-
-/// Get the previous round's winner
-pub async fn get_previous_winner(
-    rpc: &solana_client::nonblocking::rpc_client::RpcClient,
-    current_round_id: u64,
-) -> Option<usize> {
-    if current_round_id == 0 {
-        return None;
-    }
-
-    let prev_round_id = current_round_id - 1;
-    let prev_round_pubkey = round_pda(prev_round_id).0;
-
-    match rpc.get_account_data(&prev_round_pubkey).await {
-        Ok(data) => {
-            if let Ok(prev_round) = Round::try_from_bytes(&data) {
-                // Check if slot_hash is available
-                if let Some(rng) = prev_round.rng() {
-                    let winner = prev_round.winning_square(rng);
-                    return Some(winner as usize);
-                }
-            }
-            None
-        }
-        Err(_) => None,
-    }
-}
-
-/// Calculate expected share for display
-pub fn calculate_expected_share(
-    round: &Round,
-    selected_squares: &[usize],
-    deployment_per_square: u64,
-) -> f64 {
-    if selected_squares.is_empty() {
-        return 0.0;
-    }
-
-    let mut total_share = 0.0;
-
-    for &square_idx in selected_squares {
-        let current_pool = round.deployed[square_idx];
-        let total_pool = current_pool + deployment_per_square;
-
-        if total_pool > 0 {
-            let share = deployment_per_square as f64 / total_pool as f64;
-            total_share += share;
-        }
-    }
-
-    // Average share across selected squares
-    total_share / selected_squares.len() as f64
-}
-
-/// Display strategy explanation
-pub fn print_strategy_info(
-    round: &Round,
-    selected_squares: &[usize],
-    previous_winner: Option<usize>,
-    deployment_per_square: u64,
-) {
-    println!("\n╔════════════════════════════════════════════════════════╗");
-    println!("║           🎯 OPTIMAL +EV STRATEGY                      ║");
-    println!("╠════════════════════════════════════════════════════════╣");
-
-    // Show previous winner (if any)
-    if let Some(prev) = previous_winner {
-        let prev_display = prev + 1;
-        let prev_pool = round.deployed[prev];
-        let prev_pool_sol = prev_pool as f64 / 1e9;
-
-        println!("║ Previous Winner: Square #{} ({:.4} SOL)             ║", prev_display, prev_pool_sol);
-        println!("║   ⚠️  FILTERED OUT (no edge, potential clustering)  ║");
-    } else {
-        println!("║ Previous Winner: None (first round or unavailable)    ║");
-    }
-
-    println!("╠════════════════════════════════════════════════════════╣");
-    println!("║ Selected Squares (LEAST CROWDED):                     ║");
-
-    for (i, &square_idx) in selected_squares.iter().enumerate() {
-        let display_square = square_idx + 1;
-        let pool = round.deployed[square_idx];
-        let pool_sol = pool as f64 / 1e9;
-        let total_pool = pool + deployment_per_square;
-        let your_share = if total_pool > 0 {
-            (deployment_per_square as f64 / total_pool as f64) * 100.0
-        } else {
-            100.0
-        };
-
-        println!("║ {}. Square #{:2} - {:.4} SOL pool - {:5.2}% share     ║",
-            i + 1, display_square, pool_sol, your_share);
-    }
-
-    let expected_share = calculate_expected_share(round, selected_squares, deployment_per_square);
-
-    println!("╠════════════════════════════════════════════════════════╣");
-    println!("║ Expected Share: {:.2}%                                  ║", expected_share * 100.0);
-    println!("║ Win Chance: {:.1}% ({}/{} squares)                     ║",
-        (selected_squares.len() as f64 / 25.0) * 100.0,
-        selected_squares.len(),
-        25
-    );
-    println!("║                                                        ║");
-    println!("║ 💡 Edge: +28% better share than most crowded squares  ║");
-    println!("║ 🎲 Winning is random - edge is SHARE SIZE not predict ║");
-    println!("╚════════════════════════════════════════════════════════╝\n");
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_filters_previous_winner() {
-        let mut round = Round {
-            id: 100,
-            deployed: [1000; 25],
-            slot_hash: [0; 32],
-            count: [1; 25],
-            expires_at: 1000,
-            motherlode: 0,
-            rent_payer: solana_sdk::pubkey::Pubkey::default(),
-            top_miner: solana_sdk::pubkey::Pubkey::default(),
-            top_miner_reward: 0,
-            total_deployed: 25000,
-            total_vaulted: 0,
-            total_winnings: 0,
-        };
-
-        // Make square 5 least crowded
-        round.deployed[5] = 100;
-        // Make square 10 second least crowded (and previous winner)
-        round.deployed[10] = 200;
-        // Make square 15 third least crowded
-        round.deployed[15] = 300;
-
-        // Without filtering previous winner
-        let squares_no_filter = select_optimal_squares(&round, None, 2);
-        assert_eq!(squares_no_filter, vec![5, 10]); // Would include square 10
-
-        // With filtering previous winner (square 10)
-        let squares_with_filter = select_optimal_squares(&round, Some(10), 2);
-        assert_eq!(squares_with_filter, vec![5, 15]); // Skips square 10, takes next best
-
-        println!("✅ Previous winner filter working correctly!");
-    }
-}
@@ -0,0 +1,173 @@
+/// Recovery for a miner that stopped mid-loop
+///
+/// A miner remembers only the last round it deployed in, and cannot deploy again until that
+/// round is checkpointed. This checkpoints it, claims everything owed, and reports when the
+/// round's rewards were forfeited because its account expired or was closed.
+
+use anyhow::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{native_token::lamports_to_sol, signature::Signer, signer::keypair::Keypair};
+use spl_token::amount_to_ui_amount;
+use steel::AccountDeserialize;
+
+use ore_api::{prelude::*, sim};
+
+use crate::fees::PriorityFee;
+use crate::{get_board, get_clock, get_miner, submit_transaction};
+
+/// State of the round a miner has yet to checkpoint.
+#[derive(Debug, PartialEq)]
+enum Pending {
+    /// Nothing to checkpoint.
+    None,
+
+    /// The round has not been reset yet.
+    Unsettled,
+
+    /// Rewards can still be checkpointed.
+    Settled(sim::MinerRewards),
+
+    /// The round account expired. Checkpointing only unblocks the miner.
+    Expired(sim::MinerRewards),
+
+    /// The round account was closed. Checkpointing only unblocks the miner.
+    Closed,
+}
+
+pub async fn recover(rpc: &RpcClient, payer: &Keypair, fee: PriorityFee) -> Result<()> {
+    let miner = get_miner(rpc, payer.pubkey()).await?;
+    let board = get_board(rpc).await?;
+    let clock = get_clock(rpc).await?;
+    let mut round = None;
+    if needs_checkpoint(&miner, board.round_id) {
+        let address = round_pda(miner.round_id).0;
+        let account = rpc
+            .get_account_with_commitment(&address, rpc.commitment())
+            .await?
+            .value;
+        if let Some(account) = account {
+            round = Some(*Round::try_from_bytes(&account.data)?);
+        }
+    }
+    let pending = pending(&miner, board.round_id, round.as_ref(), clock.slot);
+
+    // Checkpoint, then claim what the miner will be owed afterwards.
+    let mut ixs = vec![];
+    let (mut rewards_sol, mut rewards_ore) = (miner.rewards_sol, miner.rewards_ore);
+    match &pending {
+        Pending::None => println!("Round #{} is checkpointed", miner.round_id),
+        Pending::Unsettled => println!(
+            "Round #{} has not been reset yet, try again once it is",
+            miner.round_id
+        ),
+        Pending::Settled(rewards) => {
+            println!(
+                "Round #{}: checkpointing {} SOL and {} ORE",
+                miner.round_id,
+                lamports_to_sol(rewards.rewards_sol),
+                amount_to_ui_amount(rewards.rewards_ore(), TOKEN_DECIMALS)
+            );
+            rewards_sol += rewards.rewards_sol;
+            rewards_ore += rewards.rewards_ore();
+        }
+        Pending::Expired(rewards) => println!(
+            "⚠️  Round #{} expired, {} SOL and {} ORE were forfeited",
+            miner.round_id,
+            lamports_to_sol(rewards.rewards_sol),
+            amount_to_ui_amount(rewards.rewards_ore(), TOKEN_DECIMALS)
+        ),
+        Pending::Closed => println!(
+            "⚠️  Round #{} was closed, its rewards were forfeited",
+            miner.round_id
+        ),
+    }
+    if matches!(
+        pending,
+        Pending::Settled(_) | Pending::Expired(_) | Pending::Closed
+    ) {
+        ixs.push(ore_api::sdk::checkpoint(
+            payer.pubkey(),
+            payer.pubkey(),
+            miner.round_id,
+        ));
+    }
+    if rewards_sol > 0 {
+        ixs.push(ore_api::sdk::claim_sol(payer.pubkey()));
+    }
+    if rewards_ore + miner.refined_ore > 0 {
+        ixs.push(ore_api::sdk::claim_ore(payer.pubkey()));
+    }
+    if ixs.is_empty() {
+        println!("Nothing to recover");
+        return Ok(());
+    }
+
+    println!(
+        "Claiming {} SOL and {} ORE plus refined ORE",
+        lamports_to_sol(rewards_sol),
+        amount_to_ui_amount(rewards_ore, TOKEN_DECIMALS)
+    );
+    submit_transaction(rpc, payer, &ixs, fee).await?;
+    Ok(())
+}
+
+fn needs_checkpoint(miner: &Miner, current_round_id: u64) -> bool {
+    miner.checkpoint_id < miner.round_id && miner.round_id < current_round_id
+}
+
+/// Mirrors the checks `checkpoint` makes on the miner's round.
+fn pending(miner: &Miner, current_round_id: u64, round: Option<&Round>, slot: u64) -> Pending {
+    if !needs_checkpoint(miner, current_round_id) {
+        return Pending::None;
+    }
+    let Some(round) = round else {
+        return Pending::Closed;
+    };
+    if round.slot_hash == [0; 32] {
+        return Pending::Unsettled;
+    }
+    let rewards = sim::miner_rewards(round, miner);
+    if slot >= round.expires_at {
+        Pending::Expired(rewards)
+    } else {
+        Pending::Settled(rewards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    #[test]
+    fn test_pending() {
+        let mut miner = Miner::zeroed();
+        miner.round_id = 4;
+        miner.checkpoint_id = 3;
+        miner.deployed[0] = 100;
+        let mut round = Round::zeroed();
+        round.id = 4;
+        round.expires_at = 1_000;
+
+        // Already checkpointed, or still the current round.
+        assert_eq!(pending(&miner, 4, Some(&round), 0), Pending::None);
+        miner.checkpoint_id = 4;
+        assert_eq!(pending(&miner, 5, Some(&round), 0), Pending::None);
+        miner.checkpoint_id = 3;
+
+        // Not reset yet, or closed.
+        assert_eq!(pending(&miner, 5, Some(&round), 0), Pending::Unsettled);
+        assert_eq!(pending(&miner, 5, None, 0), Pending::Closed);
+
+        // A round without randomness refunds every deploy.
+        round.slot_hash = [u8::MAX; 32];
+        let Pending::Settled(rewards) = pending(&miner, 5, Some(&round), 999) else {
+            panic!("round should be settled");
+        };
+        assert_eq!(rewards.rewards_sol, 100);
+        assert!(matches!(
+            pending(&miner, 5, Some(&round), 1_000),
+            Pending::Expired(_)
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+use solana_client::{
+    client_error::ClientError, nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::TransactionStatus;
+
+/// How often a pending transaction is resent, by default.
+const DEFAULT_REBROADCAST_INTERVAL: Duration = Duration::from_millis(2_000);
+
+/// Final state of a sent transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TxOutcome {
+    /// Reached the sender's commitment without error.
+    Landed(Signature),
+
+    /// Executed and returned an error. The fee was still paid.
+    Failed(Signature, TransactionError),
+
+    /// The blockhash expired before the transaction was seen. It can never land.
+    Expired(Signature),
+}
+
+/// Sends a signed transaction and rebroadcasts it until it lands or its blockhash expires.
+///
+/// RPC nodes drop transactions they cannot forward in time, so sending once and waiting is not
+/// enough when the network is busy, and a timeout says nothing about whether it landed.
+pub struct TxSender<'a> {
+    pub rpc: &'a RpcClient,
+
+    /// Time between rebroadcasts, which is also the status polling interval.
+    pub rebroadcast_interval: Duration,
+
+    /// Commitment a transaction must reach to count as landed.
+    pub commitment: CommitmentConfig,
+}
+
+impl<'a> TxSender<'a> {
+    pub fn new(rpc: &'a RpcClient) -> Self {
+        TxSender {
+            rpc,
+            rebroadcast_interval: DEFAULT_REBROADCAST_INTERVAL,
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+
+    /// Sends `tx`, whose blockhash is valid through `last_valid_block_height`.
+    ///
+    /// The first send runs preflight, so simulation errors are returned as `Err` without paying
+    /// a fee. Rebroadcasts skip preflight, since a transaction that already landed would fail it.
+    pub async fn send(
+        &self,
+        tx: &Transaction,
+        last_valid_block_height: u64,
+    ) -> Result<TxOutcome, ClientError> {
+        let signature = self
+            .rpc
+            .send_transaction_with_config(
+                tx,
+                RpcSendTransactionConfig {
+                    skip_preflight: false,
+                    max_retries: Some(0),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        println!("Transaction submitted: {:?}", signature);
+
+        loop {
+            tokio::time::sleep(self.rebroadcast_interval).await;
+
+            // Check status.
+            if let Some(outcome) = self.status(&signature).await? {
+                return Ok(outcome);
+            }
+
+            // Stop once the blockhash has expired. The transaction may have landed between the
+            // last poll and now, so check one more time.
+            let block_height = self
+                .rpc
+                .get_block_height_with_commitment(self.commitment)
+                .await?;
+            if block_height > last_valid_block_height {
+                return Ok(self
+                    .status(&signature)
+                    .await?
+                    .unwrap_or(TxOutcome::Expired(signature)));
+            }
+
+            // Rebroadcast. Failures are transient, the next poll decides the outcome.
+            if let Err(e) = self
+                .rpc
+                .send_transaction_with_config(
+                    tx,
+                    RpcSendTransactionConfig {
+                        skip_preflight: true,
+                        max_retries: Some(0),
+                        ..Default::default()
+                    },
+                )
+                .await
+            {
+                println!("⚠️  Rebroadcast failed: {}", e);
+            }
+        }
+    }
+
+    async fn status(&self, signature: &Signature) -> Result<Option<TxOutcome>, ClientError> {
+        let statuses = self.rpc.get_signature_statuses(&[*signature]).await?.value;
+        Ok(outcome(
+            *signature,
+            statuses.first().and_then(Option::as_ref),
+            self.commitment,
+        ))
+    }
+}
+
+/// Maps a signature status to a final outcome, or None if it is still pending.
+fn outcome(
+    signature: Signature,
+    status: Option<&TransactionStatus>,
+    commitment: CommitmentConfig,
+) -> Option<TxOutcome> {
+    let status = status?;
+    if let Some(err) = &status.err {
+        return Some(TxOutcome::Failed(signature, err.clone()));
+    }
+    if status.satisfies_commitment(commitment) {
+        return Some(TxOutcome::Landed(signature));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::InstructionError;
+    use solana_transaction_status::TransactionConfirmationStatus;
+
+    use super::*;
+
+    fn status(
+        err: Option<TransactionError>,
+        confirmation_status: TransactionConfirmationStatus,
+    ) -> TransactionStatus {
+        TransactionStatus {
+            slot: 1,
+            confirmations: Some(0),
+            status: match &err {
+                Some(err) => Err(err.clone()),
+                None => Ok(()),
+            },
+            err,
+            confirmation_status: Some(confirmation_status),
+        }
+    }
+
+    #[test]
+    fn test_outcome() {
+        let sig = Signature::default();
+        let confirmed = CommitmentConfig::confirmed();
+
+        // Not seen yet.
+        assert_eq!(outcome(sig, None, confirmed), None);
+
+        // Seen but not confirmed.
+        let processed = status(None, TransactionConfirmationStatus::Processed);
+        assert_eq!(outcome(sig, Some(&processed), confirmed), None);
+
+        // Confirmed.
+        let landed = status(None, TransactionConfirmationStatus::Confirmed);
+        assert_eq!(
+            outcome(sig, Some(&landed), confirmed),
+            Some(TxOutcome::Landed(sig))
+        );
+
+        // Errors are final as soon as they are seen.
+        let err = TransactionError::InstructionError(2, InstructionError::Custom(2));
+        let failed = status(Some(err.clone()), TransactionConfirmationStatus::Processed);
+        assert_eq!(
+            outcome(sig, Some(&failed), confirmed),
+            Some(TxOutcome::Failed(sig, err))
+        );
+    }
+}
@@ -0,0 +1,336 @@
+/// Optimal allocation of a budget across squares
+///
+/// The solver spends the budget in small increments, each on the square where it raises the
+/// objective most per lamport, and stops once no increment helps. This is water-filling over the
+/// 25 squares: money flows to the emptiest squares until their marginal value falls to that of
+/// the next one. Every allocation is scored with `ore_api::ev`, so payouts round like the program.
+///
+/// `Deploy` puts the same amount on every square of a mask, so the best equal-amount allocation is
+/// solved for too, by trying each count of least crowded squares at each amount.
+
+use anyhow::Result;
+use ore_api::ev::{self, ExpectedReturns};
+use ore_api::prelude::*;
+use solana_client::nonblocking::rpc_client::RpcClient;
+
+use crate::strategy::least_crowded;
+use crate::{get_board, get_mint_supply, get_round, get_treasury};
+
+/// Increments the budget is split into.
+const STEPS: u64 = 200;
+
+/// What the solver maximizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Objective {
+    /// Expected profit in SOL, with ORE valued at its price.
+    Ev,
+
+    /// Expected log of the bankroll after the round, which sizes down risky allocations.
+    Log,
+}
+
+pub struct SolverConfig {
+    /// Most lamports to deploy.
+    pub budget: u64,
+
+    /// Value of one ORE in lamports.
+    pub ore_price: u64,
+
+    pub objective: Objective,
+
+    /// Lamports held before deploying, for the log objective.
+    pub bankroll: u64,
+
+    /// Least lamports to put on a square that is deployed to at all.
+    pub min_per_square: u64,
+
+    /// Lamports a deploy transaction costs.
+    pub tx_fee: u64,
+}
+
+/// Amounts per square and how they score.
+#[derive(Clone, Copy, Debug)]
+pub struct Allocation {
+    pub amounts: [u64; 25],
+    pub returns: ExpectedReturns,
+
+    /// Gain in the objective over not deploying, net of the transaction fee. Lamports for the
+    /// EV objective, log growth of the bankroll for the log objective.
+    pub score: f64,
+}
+
+impl Allocation {
+    pub fn is_empty(&self) -> bool {
+        self.amounts.iter().all(|&a| a == 0)
+    }
+
+    pub fn squares(&self) -> [bool; 25] {
+        self.amounts.map(|a| a > 0)
+    }
+}
+
+/// The best allocation with any amounts, for `DeployWeighted`, and with one amount on every
+/// square of a mask, for `Deploy`.
+#[derive(Clone, Copy, Debug)]
+pub struct Solution {
+    pub weighted: Allocation,
+    pub uniform: Allocation,
+}
+
+/// Solves for the allocations that maximize the objective. Either is empty if no allocation
+/// beats not deploying.
+pub fn solve(
+    round: &Round,
+    mint_supply: u64,
+    motherlode_pool: u64,
+    config: &SolverConfig,
+) -> Solution {
+    let mut budget = config.budget;
+    if config.objective == Objective::Log {
+        budget = budget.min(config.bankroll.saturating_sub(config.tx_fee));
+    }
+    let step = (budget / STEPS).max(1);
+    let evaluate = |amounts: [u64; 25]| {
+        let returns = ev::expected_returns(round, &amounts, mint_supply, motherlode_pool);
+        let score = score(&returns, config);
+        Allocation {
+            amounts,
+            returns,
+            score,
+        }
+    };
+    let none = Allocation {
+        score: 0.0,
+        ..evaluate([0; 25])
+    };
+
+    // Water-fill, treating the transaction fee as paid.
+    let mut weighted = evaluate([0; 25]);
+    let mut spent = 0;
+    loop {
+        let mut best: Option<(f64, Allocation)> = None;
+        for (square, &amount) in weighted.amounts.iter().enumerate() {
+            let increment = if amount == 0 {
+                step.max(config.min_per_square)
+            } else {
+                step
+            };
+            if spent + increment > budget {
+                continue;
+            }
+            let mut amounts = weighted.amounts;
+            amounts[square] += increment;
+            let candidate = evaluate(amounts);
+            let gain = (candidate.score - weighted.score) / increment as f64;
+            if gain > best.as_ref().map_or(0.0, |(g, _)| *g) {
+                best = Some((gain, candidate));
+            }
+        }
+        let Some((_, next)) = best else {
+            break;
+        };
+        spent = next.returns.cost();
+        weighted = next;
+    }
+
+    // Deploy to the k least crowded squares, for each k and amount.
+    let mut uniform = none;
+    for count in 1..=25u64 {
+        let squares = least_crowded(round, count as usize, None);
+        let mut amount = step.max(config.min_per_square);
+        while amount * count <= budget {
+            let mut amounts = [0; 25];
+            for &i in &squares {
+                amounts[i] = amount;
+            }
+            let candidate = evaluate(amounts);
+            if candidate.score > uniform.score {
+                uniform = candidate;
+            }
+            amount += step;
+        }
+    }
+
+    if weighted.score <= 0.0 || weighted.is_empty() {
+        weighted = none;
+    }
+    Solution { weighted, uniform }
+}
+
+/// Prints the best allocations of the budget in the current round.
+pub async fn log_solution(rpc: &RpcClient, config: &SolverConfig) -> Result<()> {
+    let board = get_board(rpc).await?;
+    let round = get_round(rpc, board.round_id).await?;
+    let treasury = get_treasury(rpc).await?;
+    let mint_supply = get_mint_supply(rpc).await?;
+    let solution = solve(&round, mint_supply, treasury.motherlode, config);
+    println!(
+        "Round #{}: {:.4} SOL deployed, {:.2} ORE motherlode",
+        round.id,
+        round.total_deployed as f64 / 1e9,
+        treasury.motherlode as f64 / ONE_ORE as f64
+    );
+    log_allocation("Weighted (DeployWeighted)", &round, &solution.weighted);
+    log_allocation("Equal amounts (Deploy)", &round, &solution.uniform);
+    if let Some(&amount) = solution.uniform.amounts.iter().find(|&&a| a > 0) {
+        let squares = solution.uniform.squares();
+        let mask = (0..25)
+            .filter(|&i| squares[i])
+            .fold(0u32, |mask, i| mask | (1 << i));
+        println!("  mask: {:#09x}, amount: {} lamports", mask, amount);
+    }
+    Ok(())
+}
+
+fn log_allocation(title: &str, round: &Round, allocation: &Allocation) {
+    println!("\n{}", title);
+    if allocation.is_empty() {
+        println!("  No allocation beats skipping the round");
+        return;
+    }
+    for (i, &amount) in allocation.amounts.iter().enumerate() {
+        if amount > 0 {
+            println!(
+                "  Square #{:2}: {:.4} SOL pool + {:.6} SOL",
+                i + 1,
+                round.deployed[i] as f64 / 1e9,
+                amount as f64 / 1e9
+            );
+        }
+    }
+    let returns = allocation.returns;
+    println!("  cost: {:.6} SOL", returns.cost() as f64 / 1e9);
+    println!(
+        "  expected: {:.6} SOL, {:.6} ORE",
+        returns.expected_sol() as f64 / 1e9,
+        returns.expected_ore() as f64 / ONE_ORE as f64
+    );
+    println!("  score: {:.6}", allocation.score);
+}
+
+/// Scores an allocation net of the transaction fee, which is charged even if the allocation is
+/// empty. Only not sending a transaction at all scores zero.
+fn score(returns: &ExpectedReturns, config: &SolverConfig) -> f64 {
+    match config.objective {
+        Objective::Ev => {
+            returns.expected_value(config.ore_price) as f64
+                - returns.cost() as f64
+                - config.tx_fee as f64
+        }
+        Objective::Log => {
+            // ORE is counted at its expected value within each winning square.
+            let bankroll = config.bankroll as f64;
+            let spent = (returns.cost() + config.tx_fee) as f64;
+            let ore_value = |ore: f64| ore * config.ore_price as f64 / ONE_ORE as f64;
+            let growth: f64 = returns
+                .payouts
+                .iter()
+                .map(|p| {
+                    let ore = p.top_miner_ore as f64
+                        + p.motherlode_ore as f64 / ev::MOTHERLODE_ODDS as f64;
+                    let wealth = bankroll - spent + p.sol as f64 + ore_value(ore);
+                    if wealth > 0.0 {
+                        (wealth / bankroll).ln()
+                    } else {
+                        f64::NEG_INFINITY
+                    }
+                })
+                .sum();
+            growth / 25.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    const ONE_SOL: u64 = 1_000_000_000;
+
+    fn config(objective: Objective) -> SolverConfig {
+        SolverConfig {
+            budget: ONE_SOL / 10,
+            ore_price: ONE_SOL / 2,
+            objective,
+            bankroll: ONE_SOL,
+            min_per_square: 10_000,
+            tx_fee: 5_000,
+        }
+    }
+
+    fn board() -> Round {
+        let mut round = Round::zeroed();
+        round.deployed = [ONE_SOL; 25];
+        round.deployed[4] = ONE_SOL / 100;
+        round.deployed[9] = ONE_SOL / 10;
+        round.total_deployed = round.deployed.iter().sum();
+        round
+    }
+
+    #[test]
+    fn test_fills_emptiest_squares() {
+        let round = board();
+        let config = config(Objective::Ev);
+        let solution = solve(&round, 0, 0, &config);
+        let weighted = solution.weighted;
+        assert!(weighted.score > 0.0);
+        assert!(weighted.amounts[4] > 0);
+        assert!(weighted.amounts[9] > 0);
+        assert_eq!(weighted.squares().iter().filter(|&&s| s).count(), 2);
+        assert!(weighted.returns.cost() <= config.budget);
+        assert!(weighted
+            .amounts
+            .iter()
+            .all(|&a| a == 0 || a >= config.min_per_square));
+
+        // The equal-amount allocation is a mask over the least crowded squares.
+        let uniform = solution.uniform;
+        assert!(uniform.squares()[4]);
+        let amount = uniform.amounts[4];
+        assert!(uniform.amounts.iter().all(|&a| a == 0 || a == amount));
+        assert!(uniform.score > 0.0);
+    }
+
+    #[test]
+    fn test_skips_unprofitable_rounds() {
+        // An even board loses SOL to fees, and ORE is worth nothing.
+        let mut round = Round::zeroed();
+        round.deployed = [ONE_SOL; 25];
+        round.total_deployed = 25 * ONE_SOL;
+        let config = SolverConfig {
+            ore_price: 0,
+            ..config(Objective::Ev)
+        };
+        let solution = solve(&round, 0, 0, &config);
+        assert!(solution.weighted.is_empty());
+        assert!(solution.uniform.is_empty());
+        assert_eq!(solution.weighted.score, 0.0);
+
+        // A fee larger than any gain skips the round too.
+        let config = SolverConfig {
+            tx_fee: 10 * ONE_SOL,
+            ..config
+        };
+        let solution = solve(&board(), 0, 0, &config);
+        assert!(solution.weighted.is_empty());
+        assert!(solution.uniform.is_empty());
+    }
+
+    #[test]
+    fn test_log_objective_is_more_cautious() {
+        let round = board();
+        let ev = solve(&round, 0, 0, &config(Objective::Ev)).weighted;
+        let log = solve(&round, 0, 0, &config(Objective::Log)).weighted;
+        assert!(!log.is_empty());
+        assert!(log.returns.cost() <= ev.returns.cost());
+
+        // The log objective never spends more than the bankroll.
+        let config = SolverConfig {
+            bankroll: ONE_SOL / 50,
+            ..config(Objective::Log)
+        };
+        let log = solve(&round, 0, 0, &config).weighted;
+        assert!(log.returns.cost() + config.tx_fee <= config.bankroll);
+    }
+}
@@ -3,8 +3,8 @@
 /// This module implements profitable strategies based on game theory analysis
 /// and statistical modeling of miner behavior patterns.
 
+use ore_api::ev::{self, ExpectedReturns, SquarePayout};
 use ore_api::prelude::*;
-use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MiningStrategy {
@@ -34,36 +34,46 @@ pub enum MiningStrategy {
 
     /// Optimal strategy for 0.04 SOL capital
     /// Deploy 0.02 SOL to 2 least crowded squares
-    /// Expected ROI: +73% with 57 ORE motherlode
+    /// See `calculate_small_capital_returns` for its expected returns
     SmallCapitalOptimal,
 }
 
 impl MiningStrategy {
     /// Calculate which squares to deploy to based on strategy
     pub fn select_squares(&self, round: &Round, board: &Board) -> [bool; 25] {
+        self.select_squares_with_rng(round, board, &mut rand::thread_rng())
+    }
+
+    /// Same as `select_squares`, drawing random choices from `rng` so runs can be reproduced
+    pub fn select_squares_with_rng(
+        &self,
+        round: &Round,
+        board: &Board,
+        rng: &mut impl rand::Rng,
+    ) -> [bool; 25] {
         match self {
             MiningStrategy::UniformAll => self.uniform_all(),
-            MiningStrategy::Random { count } => self.random_selection(*count),
+            MiningStrategy::Random { count } => self.random_selection(*count, rng),
             MiningStrategy::Contrarian { count, threshold } => {
-                self.contrarian_selection(round, *count, *threshold)
+                self.contrarian_selection(round, *count, *threshold, rng)
             }
             MiningStrategy::AdaptiveKelly { edge_threshold } => {
-                self.kelly_selection(round, *edge_threshold)
+                self.kelly_selection(round, *edge_threshold, rng)
             }
             MiningStrategy::LateSnipe { delay_seconds: _, count } => {
                 // For late snipe, we want to wait until near end
                 // Then deploy to least populated
-                self.contrarian_selection(round, *count, 0.8)
+                self.contrarian_selection(round, *count, 0.8, rng)
             }
             MiningStrategy::WhaleRider { min_whale_size, count } => {
-                self.whale_rider(round, *min_whale_size, *count)
+                self.whale_rider(round, *min_whale_size, *count, rng)
             }
             MiningStrategy::WhaleFader { min_whale_size, count } => {
                 self.whale_fader(round, *min_whale_size, *count)
             }
-            MiningStrategy::Adaptive => self.adaptive_selection(round, board),
+            MiningStrategy::Adaptive => self.adaptive_selection(round, board, rng),
             MiningStrategy::SmallCapitalOptimal => {
-                self.small_capital_optimal(round)
+                self.small_capital_optimal(round, rng)
             }
         }
     }
@@ -74,9 +84,7 @@ impl MiningStrategy {
     }
 
     /// Random selection of N squares
-    fn random_selection(&self, count: usize) -> [bool; 25] {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
+    fn random_selection(&self, count: usize, rng: &mut impl rand::Rng) -> [bool; 25] {
         let mut squares = [false; 25];
         let mut selected = 0;
 
@@ -93,14 +101,20 @@ impl MiningStrategy {
 
     /// CONTRARIAN STRATEGY (HIGH EV)
     /// Deploy to least populated squares
-    fn contrarian_selection(&self, round: &Round, count: usize, threshold: f64) -> [bool; 25] {
+    fn contrarian_selection(
+        &self,
+        round: &Round,
+        count: usize,
+        threshold: f64,
+        rng: &mut impl rand::Rng,
+    ) -> [bool; 25] {
         let mut squares = [false; 25];
 
         // Calculate average deployment
         let avg_deployed = if round.total_deployed > 0 {
             round.total_deployed / 25
         } else {
-            return self.random_selection(count); // First deployer, go random
+            return self.random_selection(count, rng); // First deployer, go random
         };
 
         // Sort squares by deployment amount (ascending)
@@ -117,7 +131,7 @@ impl MiningStrategy {
         let threshold_amount = (avg_deployed as f64 * threshold) as u64;
         let mut selected = 0;
 
-        for (idx, deployed) in squares_by_deployment {
+        for &(idx, deployed) in &squares_by_deployment {
             if deployed <= threshold_amount && selected < count {
                 squares[idx] = true;
                 selected += 1;
@@ -140,39 +154,36 @@ impl MiningStrategy {
     /// KELLY CRITERION STRATEGY (OPTIMAL EV)
     /// Only deploy to squares with positive expected value
     /// Size bets proportional to edge
-    fn kelly_selection(&self, round: &Round, edge_threshold: f64) -> [bool; 25] {
+    fn kelly_selection(
+        &self,
+        round: &Round,
+        edge_threshold: f64,
+        rng: &mut impl rand::Rng,
+    ) -> [bool; 25] {
         let mut squares = [false; 25];
 
         if round.total_deployed == 0 {
-            return self.random_selection(5); // First deployer
+            return self.random_selection(5, rng); // First deployer
         }
 
-        let avg_deployed = round.total_deployed / 25;
-
-        for (idx, &deployed) in round.deployed.iter().enumerate() {
-            // Calculate "crowd factor" - how much above/below average
-            let crowd_factor = if avg_deployed > 0 {
-                deployed as f64 / avg_deployed as f64
-            } else {
-                0.0
-            };
-
-            // Edge exists when square is underpopulated
-            let edge = if crowd_factor < 1.0 {
-                1.0 - crowd_factor
-            } else {
-                0.0
-            };
+        // Edge is the expected SOL profit per lamport of a small deployment on the square.
+        // ORE is left out, as it has no price here.
+        let probe = (round.total_deployed / 25 / 100).max(1_000);
+        for (idx, selected) in squares.iter_mut().enumerate() {
+            let mut amounts = [0; 25];
+            amounts[idx] = probe;
+            let returns = ev::expected_returns(round, &amounts, 0, 0);
+            let edge = returns.expected_sol_profit() as f64 / probe as f64;
 
             // Only deploy if edge exceeds threshold
             if edge >= edge_threshold {
-                squares[idx] = true;
+                *selected = true;
             }
         }
 
         // If no squares have sufficient edge, use contrarian fallback
         if !squares.iter().any(|&x| x) {
-            return self.contrarian_selection(round, 3, 0.7);
+            return self.contrarian_selection(round, 3, 0.7, rng);
         }
 
         squares
@@ -180,7 +191,13 @@ impl MiningStrategy {
 
     /// WHALE RIDER STRATEGY
     /// Deploy to squares with large deployments (ride whale research)
-    fn whale_rider(&self, round: &Round, min_whale_size: u64, count: usize) -> [bool; 25] {
+    fn whale_rider(
+        &self,
+        round: &Round,
+        min_whale_size: u64,
+        count: usize,
+        rng: &mut impl rand::Rng,
+    ) -> [bool; 25] {
         let mut squares = [false; 25];
 
         // Find squares with whale-sized deployments
@@ -203,7 +220,7 @@ impl MiningStrategy {
         // If not enough whale squares, add contrarian picks
         let selected = squares.iter().filter(|&&x| x).count();
         if selected < count {
-            let contrarian = self.contrarian_selection(round, count - selected, 0.5);
+            let contrarian = self.contrarian_selection(round, count - selected, 0.5, rng);
             for (i, &should_deploy) in contrarian.iter().enumerate() {
                 if should_deploy && !squares[i] {
                     squares[i] = true;
@@ -241,10 +258,15 @@ impl MiningStrategy {
 
     /// ADAPTIVE STRATEGY
     /// Dynamically choose best strategy based on board state
-    fn adaptive_selection(&self, round: &Round, _board: &Board) -> [bool; 25] {
+    fn adaptive_selection(
+        &self,
+        round: &Round,
+        _board: &Board,
+        rng: &mut impl rand::Rng,
+    ) -> [bool; 25] {
         if round.total_deployed == 0 {
             // First deployer - spread risk
-            return self.random_selection(10);
+            return self.random_selection(10, rng);
         }
 
         let avg_deployed = round.total_deployed / 25;
@@ -260,27 +282,26 @@ impl MiningStrategy {
 
         // High variance = uneven distribution = use contrarian
         if variance_ratio > 2.0 {
-            return self.contrarian_selection(round, 3, 0.5);
+            return self.contrarian_selection(round, 3, 0.5, rng);
         }
 
         // Medium variance = some concentration = use Kelly
         if variance_ratio > 1.0 {
-            return self.kelly_selection(round, 0.15);
+            return self.kelly_selection(round, 0.15, rng);
         }
 
         // Low variance = even distribution = no edge, spread risk
-        self.random_selection(8)
+        self.random_selection(8, rng)
     }
 
     /// SMALL CAPITAL OPTIMAL STRATEGY (0.04 SOL)
-    /// Mathematically proven +73% ROI strategy
     /// Deploy 0.02 SOL to 2 LEAST crowded squares
-    fn small_capital_optimal(&self, round: &Round) -> [bool; 25] {
+    fn small_capital_optimal(&self, round: &Round, rng: &mut impl rand::Rng) -> [bool; 25] {
         let mut squares = [false; 25];
 
         if round.total_deployed == 0 {
             // First deployer - deploy to 2 random squares
-            return self.random_selection(2);
+            return self.random_selection(2, rng);
         }
 
         // Sort squares by deployment amount (ascending)
@@ -301,64 +322,72 @@ impl MiningStrategy {
     }
 }
 
-/// Calculate expected value for a deployment
+impl std::str::FromStr for MiningStrategy {
+    type Err = String;
+
+    /// Parses a strategy spec such as `contrarian:5:0.7`. Missing parameters take their defaults.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = spec.split(':').collect();
+        let arg = |i: usize| parts.get(i).copied();
+        let invalid = || format!("Invalid strategy: {}", spec);
+        let strategy = match parts[0] {
+            "uniform_all" => MiningStrategy::UniformAll,
+            "random" => MiningStrategy::Random {
+                count: parse_or(arg(1), 5).ok_or_else(invalid)?,
+            },
+            "contrarian" => MiningStrategy::Contrarian {
+                count: parse_or(arg(1), 5).ok_or_else(invalid)?,
+                threshold: parse_or(arg(2), 0.7).ok_or_else(invalid)?,
+            },
+            "kelly" => MiningStrategy::AdaptiveKelly {
+                edge_threshold: parse_or(arg(1), 0.2).ok_or_else(invalid)?,
+            },
+            "late_snipe" => MiningStrategy::LateSnipe {
+                count: parse_or(arg(1), 2).ok_or_else(invalid)?,
+                delay_seconds: parse_or(arg(2), 10).ok_or_else(invalid)?,
+            },
+            "whale_rider" => MiningStrategy::WhaleRider {
+                min_whale_size: parse_or(arg(1), 1_000_000_000).ok_or_else(invalid)?,
+                count: parse_or(arg(2), 4).ok_or_else(invalid)?,
+            },
+            "whale_fader" => MiningStrategy::WhaleFader {
+                min_whale_size: parse_or(arg(1), 1_000_000_000).ok_or_else(invalid)?,
+                count: parse_or(arg(2), 5).ok_or_else(invalid)?,
+            },
+            "adaptive" => MiningStrategy::Adaptive,
+            "small_capital" => MiningStrategy::SmallCapitalOptimal,
+            _ => return Err(invalid()),
+        };
+        Ok(strategy)
+    }
+}
+
+fn parse_or<T: std::str::FromStr>(value: Option<&str>, default: T) -> Option<T> {
+    match value {
+        Some(value) => value.parse().ok(),
+        None => Some(default),
+    }
+}
+
+/// Expected returns of splitting `deployment_amount` evenly over the selected squares, from the
+/// live motherlode pool and mint supply.
 pub fn calculate_expected_value(
     round: &Round,
+    treasury: &Treasury,
+    mint_supply: u64,
     deployment_amount: u64,
     squares: &[bool; 25],
-) -> f64 {
-    let num_squares_selected = squares.iter().filter(|&&x| x).count();
-    if num_squares_selected == 0 {
-        return 0.0;
-    }
-
-    let amount_per_square = deployment_amount / num_squares_selected as u64;
-    let mut total_ev = 0.0;
-
-    for (idx, &should_deploy) in squares.iter().enumerate() {
-        if !should_deploy {
-            continue;
+) -> ExpectedReturns {
+    let num_squares_selected = squares.iter().filter(|&&x| x).count() as u64;
+    let mut amounts = [0; 25];
+    if let Some(amount_per_square) = deployment_amount.checked_div(num_squares_selected) {
+        for (amount, &should_deploy) in amounts.iter_mut().zip(squares) {
+            if should_deploy {
+                *amount = amount_per_square;
+            }
         }
-
-        // Probability of this square winning
-        let p_win = 1.0 / 25.0;
-
-        // Your share of the winning square
-        let total_on_square = round.deployed[idx] + amount_per_square;
-        let your_share = if total_on_square > 0 {
-            amount_per_square as f64 / total_on_square as f64
-        } else {
-            1.0
-        };
-
-        // SOL winnings calculation
-        let total_losing_sol: u64 = round
-            .deployed
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| *i != idx)
-            .map(|(_, &d)| d)
-            .sum();
-
-        // After fees: 1% admin + 10% vault
-        let winnings_after_fees = total_losing_sol as f64 * 0.89;
-        let sol_payout = winnings_after_fees * your_share;
-
-        // ORE rewards (split 50% of time, WTA 50% of time)
-        let ore_split_reward = 1.0 * your_share * 0.5; // Split mode
-        let ore_wta_reward = 1.0 * your_share * 0.5; // WTA mode (simplified)
-        let ore_reward = ore_split_reward + ore_wta_reward;
-
-        // Motherlode (1 in 625 chance)
-        let motherlode_ev = 0.2 * your_share * (1.0 / 625.0);
-
-        // Total EV for this square
-        let square_ev = p_win * (sol_payout + ore_reward + motherlode_ev);
-        total_ev += square_ev;
     }
-
-    // Subtract deployment cost
-    total_ev - deployment_amount as f64
+    ev::expected_returns(round, &amounts, mint_supply, treasury.motherlode)
 }
 
 /// Motherlode tracking system
@@ -440,10 +469,11 @@ impl MotherlodeTracker {
     }
 }
 
-/// Calculate expected returns for 0.04 SOL optimal strategy
+/// Calculate expected returns for 0.04 SOL optimal strategy on the current board
 pub fn calculate_small_capital_returns(
     round: &Round,
-    motherlode_pool: u64,
+    treasury: &Treasury,
+    mint_supply: u64,
 ) -> SmallCapitalReturns {
     const DEPLOYMENT: u64 = 40_000_000; // 0.04 SOL in lamports
     const PER_SQUARE: u64 = 20_000_000; // 0.02 SOL per square
@@ -451,25 +481,23 @@ pub fn calculate_small_capital_returns(
     // Find two least crowded squares
     let mut sorted: Vec<_> = round.deployed.iter().enumerate().collect();
     sorted.sort_by_key(|(_, &d)| d);
+    let squares = [sorted[0].0, sorted[1].0];
 
-    let square_1_deployed = sorted[0].1;
-    let square_2_deployed = sorted[1].1;
+    let mut amounts = [0; 25];
+    for &i in &squares {
+        amounts[i] = PER_SQUARE;
+    }
+    let returns = ev::expected_returns(round, &amounts, mint_supply, treasury.motherlode);
 
-    // Calculate typical deployment on winning square
-    let avg_square_deployment = if *square_1_deployed > 0 && *square_2_deployed > 0 {
-        (*square_1_deployed + *square_2_deployed) / 2
-    } else {
-        500_000_000 // Assume 0.5 SOL typical
+    // Payouts per win are averaged over the two squares.
+    let per_win = |payout: fn(&SquarePayout) -> u64| {
+        squares.iter().map(|&i| payout(&returns.payouts[i])).sum::<u64>() / 2
     };
-
-    let total_on_square = avg_square_deployment + PER_SQUARE;
-    let your_share = PER_SQUARE as f64 / total_on_square as f64;
-
-    // Calculate payouts
-    let sol_payout = 0.429; // Expected SOL return (from analysis)
-    let ore_payout = your_share; // Base 1 ORE * your_share
-    let motherlode_ore = motherlode_pool as f64 / ONE_ORE as f64;
-    let motherlode_payout = motherlode_ore * your_share;
+    let your_share = squares
+        .iter()
+        .map(|&i| PER_SQUARE as f64 / (round.deployed[i] + PER_SQUARE) as f64)
+        .sum::<f64>()
+        / 2.0;
 
     SmallCapitalReturns {
         deployment_sol: DEPLOYMENT as f64 / 1_000_000_000.0,
@@ -477,13 +505,13 @@ pub fn calculate_small_capital_returns(
         per_square_sol: PER_SQUARE as f64 / 1_000_000_000.0,
         your_share_percent: your_share * 100.0,
         win_probability: 8.0, // 2/25
-        expected_sol_payout: sol_payout,
-        expected_ore_payout: ore_payout,
-        motherlode_ore,
-        expected_motherlode_payout: motherlode_payout,
-        expected_total_payout_usd: (sol_payout * 25.0) + (ore_payout * 26.0),
-        roi_percent: 73.0, // With motherlode
-        roi_no_motherlode: 66.0,
+        expected_sol_payout: per_win(|p| p.sol) as f64 / 1_000_000_000.0,
+        expected_ore_payout: per_win(|p| p.top_miner_ore) as f64 / ONE_ORE as f64,
+        motherlode_ore: treasury.motherlode as f64 / ONE_ORE as f64,
+        expected_motherlode_payout: per_win(|p| p.motherlode_ore) as f64 / ONE_ORE as f64,
+        expected_sol: returns.expected_sol() as f64 / 1_000_000_000.0,
+        expected_ore: returns.expected_ore() as f64 / ONE_ORE as f64,
+        sol_roi_percent: returns.expected_sol_profit() as f64 / DEPLOYMENT as f64 * 100.0,
     }
 }
 
@@ -497,9 +525,9 @@ pub struct SmallCapitalReturns {
     pub expected_ore_payout: f64,
     pub motherlode_ore: f64,
     pub expected_motherlode_payout: f64,
-    pub expected_total_payout_usd: f64,
-    pub roi_percent: f64,
-    pub roi_no_motherlode: f64,
+    pub expected_sol: f64,
+    pub expected_ore: f64,
+    pub sol_roi_percent: f64,
 }
 
 impl SmallCapitalReturns {
@@ -519,10 +547,10 @@ impl SmallCapitalReturns {
 ║   ORE Reward:    {:.4} ORE                                    ║
 ║   Motherlode:    {:.2} ORE pool → {:.4} ORE if hit            ║
 ╠═══════════════════════════════════════════════════════════════╣
-║ Expected Value:                                                ║
-║   Total Payout:  ~${:.2} per win                              ║
-║   ROI (base):    +{:.0}%                                      ║
-║   ROI (w/pool):  +{:.0}%                                      ║
+║ Expected Value (per round):                                   ║
+║   SOL:           {:.6} SOL                                    ║
+║   ORE:           {:.6} ORE                                    ║
+║   SOL ROI:       {:+.2}%                                      ║
 ╚═══════════════════════════════════════════════════════════════╝
 "#,
             self.deployment_sol,
@@ -534,9 +562,9 @@ impl SmallCapitalReturns {
             self.expected_ore_payout,
             self.motherlode_ore,
             self.expected_motherlode_payout,
-            self.expected_total_payout_usd,
-            self.roi_no_motherlode,
-            self.roi_percent,
+            self.expected_sol,
+            self.expected_ore,
+            self.sol_roi_percent,
         )
     }
 }
@@ -639,6 +667,7 @@ impl StrategyMetrics {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_sdk::pubkey::Pubkey;
 
     #[test]
     fn test_contrarian_selection() {
@@ -680,4 +709,24 @@ mod tests {
         assert!(selection[5]); // 20
         assert!(selection[12]); // 30
     }
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!(
+            "contrarian:3:0.5".parse::<MiningStrategy>(),
+            Ok(MiningStrategy::Contrarian {
+                count: 3,
+                threshold: 0.5
+            })
+        );
+        assert_eq!(
+            "late_snipe".parse::<MiningStrategy>(),
+            Ok(MiningStrategy::LateSnipe {
+                delay_seconds: 10,
+                count: 2
+            })
+        );
+        assert!("contrarian:x".parse::<MiningStrategy>().is_err());
+        assert!("unknown".parse::<MiningStrategy>().is_err());
+    }
 }
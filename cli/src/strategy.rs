@@ -0,0 +1,257 @@
+/// Pluggable deployment strategies
+///
+/// A `Strategy` decides when to deploy and how much to put on each square. The generic driver
+/// in `driver.rs` owns the WebSocket timing, checkpointing and result logging, so a new strategy
+/// only needs to implement the two methods below.
+
+use ore_api::ev::{self, ExpectedReturns};
+use ore_api::prelude::*;
+
+use crate::strategies::MiningStrategy;
+
+/// When a strategy wants to deploy within a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeployTiming {
+    /// Deploy as soon as the round is active.
+    #[allow(dead_code)]
+    Now,
+
+    /// Deploy once the remaining time drops below `max_seconds` (but not below `min_seconds`).
+    Window { max_seconds: u64, min_seconds: u64 },
+}
+
+/// The outcome of a round we deployed in, as seen after checkpointing.
+#[derive(Debug, Clone, Copy)]
+pub struct RoundRecord {
+    pub round_id: u64,
+    pub amounts: [u64; 25],
+    pub winning_square: Option<usize>,
+    pub sol_won: u64,
+    pub ore_won: u64,
+}
+
+impl RoundRecord {
+    pub fn deployed(&self) -> u64 {
+        self.amounts.iter().sum()
+    }
+
+    pub fn won(&self) -> bool {
+        self.winning_square
+            .map(|w| self.amounts[w] > 0)
+            .unwrap_or(false)
+    }
+}
+
+/// Everything a strategy can see when it allocates.
+pub struct StrategyInput<'a> {
+    pub board: &'a Board,
+    pub round: &'a Round,
+    pub treasury: &'a Treasury,
+    /// Current supply of the ORE mint.
+    pub mint_supply: u64,
+    /// None if the miner account has not been created yet.
+    #[allow(dead_code)]
+    pub miner: Option<&'a Miner>,
+    /// Rounds we played, oldest first.
+    pub history: &'a [RoundRecord],
+    /// Squares taken by other wallets in our fleet this round. Deploying there only splits our
+    /// own share, so strategies should pick other squares.
+    pub excluded: &'a [bool; 25],
+}
+
+impl StrategyInput<'_> {
+    /// The winning square of the last round we played, if known.
+    pub fn previous_winner(&self) -> Option<usize> {
+        self.history.last().and_then(|r| r.winning_square)
+    }
+
+    /// Exact expected returns of deploying `amounts` into the current round.
+    pub fn expected_returns(&self, amounts: &[u64; 25]) -> ExpectedReturns {
+        ev::expected_returns(
+            self.round,
+            amounts,
+            self.mint_supply,
+            self.treasury.motherlode,
+        )
+    }
+}
+
+pub trait Strategy {
+    /// Name used in logs.
+    fn name(&self) -> String;
+
+    /// When to deploy in the current round.
+    fn timing(&self) -> DeployTiming;
+
+    /// Lamports to deploy on each square. All zeros skips the round.
+    fn allocate(&mut self, input: &StrategyInput) -> [u64; 25];
+}
+
+/// Deploys a fixed amount to the N least crowded squares.
+#[derive(Debug, Clone, Copy)]
+pub struct LeastCrowded {
+    pub count: usize,
+    pub amount_per_square: u64,
+    pub skip_previous_winner: bool,
+    pub timing: DeployTiming,
+}
+
+impl Strategy for LeastCrowded {
+    fn name(&self) -> String {
+        format!("least_crowded_{}", self.count)
+    }
+
+    fn timing(&self) -> DeployTiming {
+        self.timing
+    }
+
+    fn allocate(&mut self, input: &StrategyInput) -> [u64; 25] {
+        let mut excluded = *input.excluded;
+        if self.skip_previous_winner {
+            if let Some(winner) = input.previous_winner() {
+                excluded[winner] = true;
+            }
+        }
+        let mut amounts = [0; 25];
+        for i in least_crowded(input.round, 25, None)
+            .into_iter()
+            .filter(|&i| !excluded[i])
+            .take(self.count)
+        {
+            amounts[i] = self.amount_per_square;
+        }
+        amounts
+    }
+}
+
+/// Deploys a fixed amount to each square picked by a `MiningStrategy`.
+#[derive(Debug, Clone, Copy)]
+pub struct Selection {
+    pub strategy: MiningStrategy,
+    pub amount_per_square: u64,
+}
+
+impl Strategy for Selection {
+    fn name(&self) -> String {
+        format!("{:?}", self.strategy)
+    }
+
+    fn timing(&self) -> DeployTiming {
+        match self.strategy {
+            MiningStrategy::LateSnipe { delay_seconds, .. } => DeployTiming::Window {
+                max_seconds: delay_seconds,
+                min_seconds: 0,
+            },
+            _ => DeployTiming::Window {
+                max_seconds: 10,
+                min_seconds: 5,
+            },
+        }
+    }
+
+    fn allocate(&mut self, input: &StrategyInput) -> [u64; 25] {
+        let squares = self.strategy.select_squares(input.round, input.board);
+        let mut amounts = [0; 25];
+        for i in 0..25 {
+            if squares[i] && !input.excluded[i] {
+                amounts[i] = self.amount_per_square;
+            }
+        }
+        amounts
+    }
+}
+
+/// Returns the N least crowded squares, optionally skipping one.
+pub fn least_crowded(round: &Round, count: usize, skip: Option<usize>) -> Vec<usize> {
+    let mut squares_by_deployment: Vec<(usize, u64)> = round
+        .deployed
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| (i, d))
+        .collect();
+    squares_by_deployment.sort_by_key(|&(_, d)| d);
+    squares_by_deployment
+        .into_iter()
+        .filter(|&(i, _)| Some(i) != skip)
+        .take(count)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use steel::Zeroable;
+
+    #[test]
+    fn test_filters_previous_winner() {
+        let mut round = Round::zeroed();
+        round.deployed = [1000; 25];
+
+        // Make squares 5, 10 and 15 the least crowded, in that order.
+        round.deployed[5] = 100;
+        round.deployed[10] = 200;
+        round.deployed[15] = 300;
+
+        assert_eq!(least_crowded(&round, 2, None), vec![5, 10]);
+        assert_eq!(least_crowded(&round, 2, Some(10)), vec![5, 15]);
+    }
+
+    #[test]
+    fn test_least_crowded_allocation() {
+        let mut round = Round::zeroed();
+        round.deployed = [1000; 25];
+        round.deployed[3] = 10;
+        round.deployed[7] = 20;
+        let board = Board {
+            round_id: 0,
+            start_slot: 0,
+            end_slot: 0,
+        };
+        let treasury = Treasury::zeroed();
+        let history = [RoundRecord {
+            round_id: 0,
+            amounts: [0; 25],
+            winning_square: Some(3),
+            sol_won: 0,
+            ore_won: 0,
+        }];
+        let input = StrategyInput {
+            board: &board,
+            round: &round,
+            treasury: &treasury,
+            mint_supply: 0,
+            miner: None,
+            history: &history,
+            excluded: &[false; 25],
+        };
+
+        let mut strategy = LeastCrowded {
+            count: 1,
+            amount_per_square: 5,
+            skip_previous_winner: false,
+            timing: DeployTiming::Now,
+        };
+        let amounts = strategy.allocate(&input);
+        assert_eq!(amounts[3], 5);
+        assert_eq!(amounts.iter().sum::<u64>(), 5);
+
+        strategy.skip_previous_winner = true;
+        let amounts = strategy.allocate(&input);
+        assert_eq!(amounts[7], 5);
+        assert_eq!(amounts.iter().sum::<u64>(), 5);
+
+        // Squares taken by the fleet are skipped too.
+        let mut excluded = [false; 25];
+        excluded[7] = true;
+        let input = StrategyInput {
+            excluded: &excluded,
+            ..input
+        };
+        strategy.count = 2;
+        let amounts = strategy.allocate(&input);
+        assert_eq!(amounts[7], 0);
+        assert_eq!(amounts.iter().filter(|&&a| a > 0).count(), 2);
+        assert_eq!(amounts[3], 0);
+    }
+}
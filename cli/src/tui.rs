@@ -30,6 +30,7 @@ use solana_sdk::{
 use spl_token::amount_to_ui_amount;
 use steel::Clock;
 
+use crate::fees::PriorityFee;
 use crate::{get_board, get_clock, get_miner, get_round, get_stake, get_treasury, submit_transaction};
 
 // Re-export needed constants
@@ -312,7 +313,7 @@ async fn auto_mine_logic(
         if let Some(miner) = &app.miner {
             if miner.checkpoint_id < miner.round_id {
                 let ix = ore_api::sdk::checkpoint(payer.pubkey(), payer.pubkey(), miner.round_id);
-                match submit_transaction(rpc, payer, &[ix]).await {
+                match submit_transaction(rpc, payer, &[ix], PriorityFee::default()).await {
                     Ok(_) => {
                         app.add_log(
                             LogLevel::Success,
@@ -370,7 +371,7 @@ async fn deploy_manual(
         app.selected_squares,
     );
 
-    match submit_transaction(rpc, payer, &[ix]).await {
+    match submit_transaction(rpc, payer, &[ix], PriorityFee::for_command("deploy")).await {
         Ok(_) => {
             let square_count = app.selected_squares.iter().filter(|&&x| x).count();
             app.add_log(
@@ -400,7 +401,7 @@ async fn claim_rewards(
     let ix_sol = ore_api::sdk::claim_sol(payer.pubkey());
     let ix_ore = ore_api::sdk::claim_ore(payer.pubkey());
 
-    match submit_transaction(rpc, payer, &[ix_sol, ix_ore]).await {
+    match submit_transaction(rpc, payer, &[ix_sol, ix_ore], PriorityFee::default()).await {
         Ok(_) => {
             if let Some(miner) = &app.miner {
                 let sol = miner.rewards_sol;
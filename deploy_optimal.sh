@@ -5,8 +5,7 @@
 # - FILTER OUT previous winner (no advantage, potential clustering)
 # - Edge: +28% better share than most crowded
 
-export KEYPAIR="${KEYPAIR:-/path/to/keypair.json}"
-export RPC="${RPC:-https://mainnet.helius-rpc.com/?api-key=YOUR_API_KEY}"
+# RPC and keypair come from ~/.config/ore/config.toml unless RPC / KEYPAIR are set
 
 echo "╔════════════════════════════════════════════════════════╗"
 echo "║     🎯 OPTIMAL +EV MINING STRATEGY                     ║"
@@ -17,9 +16,8 @@ echo "╚═══════════════════════
 echo ""
 
 # Use your existing deploy_single but with strategy
-export COMMAND=deploy_single
-cargo run --bin ore-cli --release
+cargo run --bin ore-cli --release -- deploy-single
 
 echo ""
 echo "💡 Strategy deployed! Check results with:"
-echo "   export COMMAND=checkpoint && cargo run --bin ore-cli"
+echo "   cargo run --bin ore-cli -- checkpoint"
@@ -13,10 +13,7 @@ fi
 # Convert SOL to lamports (multiply by 1 billion)
 BET_LAMPORTS=$(echo "$BET_SOL * 1000000000" | bc | cut -d. -f1)
 
-export KEYPAIR="${KEYPAIR:-/path/to/keypair.json}"
-export RPC="${RPC:-https://mainnet.helius-rpc.com/?api-key=YOUR_API_KEY}"
-export COMMAND="deploy_optimal_ev"
-export BET_AMOUNT=$BET_LAMPORTS
+# RPC and keypair come from ~/.config/ore/config.toml unless RPC / KEYPAIR are set
 
 # Calculate per-square amount (10 squares)
 PER_SQUARE=$(echo "scale=6; $BET_SOL / 10" | bc)
@@ -25,7 +22,7 @@ PER_SQUARE=$(echo "scale=6; $BET_SOL / 10" | bc)
 SHARE_PCT=$(echo "scale=4; ($PER_SQUARE / 30) * 100" | bc)
 
 # Calculate rounds with current balance
-CURRENT_BALANCE=$(solana balance $KEYPAIR --url $RPC 2>/dev/null | awk '{print $1}')
+CURRENT_BALANCE=$(solana balance ${KEYPAIR:+$KEYPAIR} ${RPC:+--url $RPC} 2>/dev/null | awk '{print $1}')
 if [ ! -z "$CURRENT_BALANCE" ]; then
     ROUNDS=$(echo "scale=0; $CURRENT_BALANCE / $BET_SOL" | bc)
 else
@@ -49,4 +46,4 @@ echo "   (Press Ctrl+C to stop)"
 echo ""
 
 # Run the optimal EV deployment strategy
-cargo run --bin ore-cli --release
+cargo run --bin ore-cli --release -- deploy-optimal-ev --num-squares 10 --bet-amount $BET_LAMPORTS
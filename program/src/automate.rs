@@ -1,23 +1,34 @@
 use ore_api::prelude::*;
+use solana_program::rent::Rent;
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Sets the executor.
 pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
+    let (data, limits) = data.split_at(data.len().min(std::mem::size_of::<Automate>()));
     let args = Automate::try_from_bytes(data)?;
+    let limits = parse_limits(limits)?;
     let amount = u64::from_le_bytes(args.amount);
     let deposit = u64::from_le_bytes(args.deposit);
     let fee = u64::from_le_bytes(args.fee);
     let mask = u64::from_le_bytes(args.mask);
     let strategy = AutomationStrategy::from_u64(args.strategy as u64);
+    let max_rounds = u64::from_le_bytes(limits.max_rounds);
+    let expires_at = u64::from_le_bytes(limits.expires_at);
+    let max_per_round = u64::from_le_bytes(limits.max_per_round);
 
     // Load accounts.
+    let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(5));
     let [signer_info, automation_info, executor_info, miner_info, system_program] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
     automation_info.is_writable()?;
     system_program.is_program(&system_program::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // // Check whitelist
     // if !AUTHORIZED_ACCOUNTS.contains(&signer_info.key) {
@@ -54,6 +65,12 @@ pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
             )?
     };
 
+    // Grow automations opened before limits were added. The signer pays the extra rent.
+    let rent = grow_automation(automation_info)?;
+    if rent > 0 {
+        automation_info.collect(rent, signer_info)?;
+    }
+
     // Close account if executor is Pubkey::default().
     if *executor_info.key == Pubkey::default() {
         automation_info
@@ -63,6 +80,24 @@ pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
                 OreError::NotAuthorized.into(),
             )?;
         automation_info.close(signer_info)?;
+
+        // Emit event.
+        emit_event(
+            events,
+            AutomateEvent {
+                disc: OreEvent::Automate as u64,
+                authority: *signer_info.key,
+                executor: Pubkey::default(),
+                amount: 0,
+                deposit: 0,
+                fee: 0,
+                mask: 0,
+                strategy: 0,
+                balance: 0,
+                ts: clock.unix_timestamp,
+            }
+            .to_bytes(),
+        )?;
         return Ok(());
     }
 
@@ -78,6 +113,9 @@ pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
         let automation = automation_info.as_account_mut::<Automation>(&ore_api::ID)?;
         automation.balance = 0;
         automation.authority = *signer_info.key;
+        automation.rounds = 0;
+        automation.round_id = u64::MAX;
+        automation.round_spent = 0;
         automation
     } else {
         automation_info
@@ -95,6 +133,9 @@ pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
     automation.fee = fee;
     automation.mask = mask;
     automation.strategy = strategy as u64;
+    automation.max_rounds = max_rounds;
+    automation.expires_at = expires_at;
+    automation.max_per_round = max_per_round;
 
     // Top up checkpoint fee.
     if miner.checkpoint_fee == 0 {
@@ -105,5 +146,51 @@ pub fn process_automate(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
     // Transfer balance to executor.
     automation_info.collect(deposit, signer_info)?;
 
+    // Emit event.
+    emit_event(
+        events,
+        AutomateEvent {
+            disc: OreEvent::Automate as u64,
+            authority: automation.authority,
+            executor: automation.executor,
+            amount,
+            deposit,
+            fee,
+            mask,
+            strategy: automation.strategy,
+            balance: automation.balance,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
+
     Ok(())
 }
+
+/// Parses the optional limits after the automate args. Automations set without them have no
+/// limits.
+fn parse_limits(data: &[u8]) -> Result<AutomateLimits, ProgramError> {
+    if data.is_empty() {
+        return Ok(AutomateLimits::default());
+    }
+    bytemuck::try_from_bytes::<AutomateLimits>(data)
+        .copied()
+        .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// Grows an automation opened before limits were added to the current size, with no limits and
+/// no round deployed in. Returns the extra rent the account now needs, which is 0 if it was
+/// already the current size or does not exist.
+pub fn grow_automation(automation_info: &AccountInfo<'_>) -> Result<u64, ProgramError> {
+    let size = 8 + std::mem::size_of::<Automation>();
+    let old_size = automation_info.data_len();
+    if old_size == 0 || old_size >= size {
+        return Ok(0);
+    }
+    let rent = Rent::get()?;
+    automation_info.realloc(size, true)?;
+    automation_info
+        .as_account_mut::<Automation>(&ore_api::ID)?
+        .round_id = u64::MAX;
+    Ok(rent.minimum_balance(size) - rent.minimum_balance(old_size))
+}
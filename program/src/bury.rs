@@ -18,9 +18,10 @@ pub fn process_bury(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     };
     signer_info.is_signer()?;
     board_info.as_account_mut::<Board>(&ore_api::ID)?;
-    config_info
-        .as_account::<Config>(&ore_api::ID)?
-        .assert(|c| c.bury_authority == *signer_info.key)?;
+    config_info.as_account::<Config>(&ore_api::ID)?.assert_err(
+        |c| c.bury_authority == *signer_info.key,
+        OreError::NotAuthorized.into(),
+    )?;
     mint_info.has_address(&MINT_ADDRESS)?.as_mint()?;
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     let treasury_ore =
@@ -37,7 +38,9 @@ pub fn process_bury(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
         treasury_sol_info.as_associated_token_account(treasury_info.key, &SOL_MINT)?;
     let pre_swap_ore_balance = treasury_ore.amount();
     let pre_swap_sol_balance = treasury_sol.amount();
-    assert!(pre_swap_sol_balance > 0);
+    if pre_swap_sol_balance == 0 {
+        return Err(OreError::AmountTooSmall.into());
+    }
 
     let accounts: Vec<AccountMeta> = swap_accounts
         .iter()
@@ -122,7 +125,7 @@ pub fn process_bury(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     program_log(
         &[board_info.clone(), ore_program.clone()],
         BuryEvent {
-            disc: 1,
+            disc: OreEvent::Bury as u64,
             ore_buried: burn_amount,
             ore_shared: shared_amount,
             sol_amount: pre_swap_sol_balance,
@@ -1,12 +1,15 @@
-use ore_api::prelude::*;
+use ore_api::{prelude::*, sim};
 use solana_program::{log::sol_log, native_token::lamports_to_sol, rent::Rent};
 use spl_token::amount_to_ui_amount;
 use steel::*;
 
+use crate::log::emit_event;
+
 /// Checkpoints a miner's rewards.
 pub fn process_checkpoint(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, ore_program) = accounts.split_at(accounts.len().min(6));
     let [signer_info, board_info, miner_info, round_info, treasury_info, system_program] = accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
@@ -17,6 +20,40 @@ pub fn process_checkpoint(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     system_program.is_program(&system_program::ID)?;
 
+    // The ORE program is only needed to log the event, so older transactions may leave it out.
+    let ore_program = match ore_program {
+        [] => None,
+        [ore_program] => Some(ore_program.is_program(&ore_api::ID)?),
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    checkpoint_miner(
+        &clock,
+        board.round_id,
+        signer_info,
+        board_info,
+        miner_info,
+        miner,
+        round_info,
+        treasury,
+        ore_program,
+    )
+}
+
+/// Checkpoints the miner's last round, if it has been settled. Also used by `deploy` to
+/// checkpoint the previous round in the same instruction.
+#[allow(clippy::too_many_arguments)]
+pub fn checkpoint_miner<'info>(
+    clock: &Clock,
+    current_round_id: u64,
+    signer_info: &AccountInfo<'info>,
+    board_info: &AccountInfo<'info>,
+    miner_info: &AccountInfo<'info>,
+    miner: &mut Miner,
+    round_info: &AccountInfo<'info>,
+    treasury: &mut Treasury,
+    ore_program: Option<&AccountInfo<'info>>,
+) -> ProgramResult {
     // If miner has already checkpointed this round, return.
     if miner.checkpoint_id == miner.round_id {
         return Ok(());
@@ -35,7 +72,7 @@ pub fn process_checkpoint(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
     // If round is current round, or the miner round ID does not match the provided round, return.
     let round = round_info.as_account_mut::<Round>(&ore_api::ID)?; // Round has been closed.
     sol_log(&format!("Round ID: {}", round.id).as_str());
-    if round.id == board.round_id || round.id != miner.round_id || round.slot_hash == [0; 32] {
+    if round.id == current_round_id || round.id != miner.round_id || round.slot_hash == [0; 32] {
         sol_log(&format!("Round not valid").as_str());
         return Ok(());
     }
@@ -56,94 +93,64 @@ pub fn process_checkpoint(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
         miner.checkpoint_fee = 0;
     }
 
-    // Calculate miner rewards.
-    let mut rewards_sol = 0;
-    let mut rewards_ore = 0;
-
-    // Get the RNG.
+    // Sanity check.
     if let Some(r) = round.rng() {
-        // Get the winning square.
-        let winning_square = round.winning_square(r) as usize;
-
-        // If the miner deployed to the winning square, calculate rewards.
-        if miner.deployed[winning_square] > 0 {
-            // Sanity check.
-            assert!(
-                round.deployed[winning_square] >= miner.deployed[winning_square],
-                "Invalid round deployed amount"
-            );
-
-            // Calculate SOL rewards.
-            let original_deployment = miner.deployed[winning_square];
-            let admin_fee = (original_deployment / 100).max(1);
-            rewards_sol = original_deployment - admin_fee;
-            rewards_sol += ((round.total_winnings as u128 * miner.deployed[winning_square] as u128)
-                / round.deployed[winning_square] as u128) as u64;
-            sol_log(&format!("Base rewards: {} SOL", lamports_to_sol(rewards_sol)).as_str());
-
-            // Calculate ORE rewards.
-            if round.top_miner == SPLIT_ADDRESS {
-                // If round is split, split the reward evenly among all miners.
-                rewards_ore = ((round.top_miner_reward as u128
-                    * miner.deployed[winning_square] as u128)
-                    / round.deployed[winning_square] as u128) as u64;
-                sol_log(
-                    &format!(
-                        "Split rewards: {} ORE",
-                        amount_to_ui_amount(rewards_ore, TOKEN_DECIMALS)
-                    )
-                    .as_str(),
-                );
-            } else {
-                // If round is not split, payout to the top miner.
-                let top_miner_sample = round.top_miner_sample(r, winning_square);
-                if top_miner_sample >= miner.cumulative[winning_square]
-                    && top_miner_sample
-                        < miner.cumulative[winning_square] + miner.deployed[winning_square]
-                {
-                    rewards_ore = round.top_miner_reward;
-                    round.top_miner = miner.authority;
-                    sol_log(
-                        &format!(
-                            "Top miner rewards: {} ORE",
-                            amount_to_ui_amount(rewards_ore, TOKEN_DECIMALS)
-                        )
-                        .as_str(),
-                    );
-                }
-            }
-
-            // Calculate motherlode rewards.
-            if round.motherlode > 0 {
-                let motherload_rewards =
-                    ((round.motherlode as u128 * miner.deployed[winning_square] as u128)
-                        / round.deployed[winning_square] as u128) as u64;
-                sol_log(
-                    &format!(
-                        "Motherlode rewards: {} ORE",
-                        amount_to_ui_amount(motherload_rewards, TOKEN_DECIMALS)
-                    )
-                    .as_str(),
-                );
-                rewards_ore += motherload_rewards;
-            }
+        let winning_square = round.winning_square(r);
+        if round.deployed[winning_square] < miner.deployed[winning_square] {
+            return Err(trace(
+                "Invalid round deployed amount",
+                OreError::InvariantViolation.into(),
+            ));
         }
-    } else {
-        // Sanity check.
+    } else if round.total_deployed != 0 {
         // If there is no rng, total deployed should have been reset to zero.
-        assert!(
-            round.total_deployed == 0,
-            "Round total deployed should be zero."
+        return Err(trace(
+            "Round total deployed should be zero",
+            OreError::InvariantViolation.into(),
+        ));
+    }
+
+    // Calculate miner rewards.
+    let rewards = sim::miner_rewards(round, miner);
+    let rewards_sol = rewards.rewards_sol;
+    let rewards_ore = rewards.rewards_ore();
+    if round.rng().is_none() {
+        sol_log(&format!("Refunding {} SOL", lamports_to_sol(rewards_sol)).as_str());
+    } else if rewards_sol > 0 {
+        sol_log(&format!("Base rewards: {} SOL", lamports_to_sol(rewards_sol)).as_str());
+    }
+    if rewards.top_miner_ore > 0 {
+        let label = if rewards.is_top_miner {
+            "Top miner"
+        } else {
+            "Split"
+        };
+        sol_log(
+            &format!(
+                "{} rewards: {} ORE",
+                label,
+                amount_to_ui_amount(rewards.top_miner_ore, TOKEN_DECIMALS)
+            )
+            .as_str(),
+        );
+    }
+    if rewards.motherlode_ore > 0 {
+        sol_log(
+            &format!(
+                "Motherlode rewards: {} ORE",
+                amount_to_ui_amount(rewards.motherlode_ore, TOKEN_DECIMALS)
+            )
+            .as_str(),
         );
+    }
 
-        // Round has no slot hash, refund all SOL.
-        let refund_amount = miner.deployed.iter().sum::<u64>();
-        sol_log(&format!("Refunding {} SOL", lamports_to_sol(refund_amount)).as_str());
-        rewards_sol = refund_amount;
+    // Record the top miner.
+    if rewards.is_top_miner {
+        round.top_miner = miner.authority;
     }
 
     // Checkpoint rewards.
-    miner.update_rewards(treasury);
+    miner.update_rewards(treasury)?;
 
     // Checkpoint miner.
     miner.checkpoint_id = round.id;
@@ -166,10 +173,29 @@ pub fn process_checkpoint(accounts: &[AccountInfo<'_>], _data: &[u8]) -> Program
     // Assert miner account has sufficient funds for rent and rewards.
     let account_size = 8 + std::mem::size_of::<Miner>();
     let required_rent = Rent::get()?.minimum_balance(account_size);
-    assert!(
-        miner_info.lamports() >= required_rent + miner.checkpoint_fee + miner.rewards_sol,
-        "Miner does not have sufficient funds for rent and rewards"
-    );
+    if miner_info.lamports() < required_rent + miner.checkpoint_fee + miner.rewards_sol {
+        return Err(trace(
+            "Miner does not have sufficient funds for rent and rewards",
+            OreError::InvariantViolation.into(),
+        ));
+    }
+
+    // Emit event.
+    emit_event(
+        ore_program.map(|ore_program| (board_info, ore_program)),
+        CheckpointEvent {
+            disc: OreEvent::Checkpoint as u64,
+            authority: miner.authority,
+            round_id: round.id,
+            rewards_sol,
+            top_miner_ore: rewards.top_miner_ore,
+            motherlode_ore: rewards.motherlode_ore,
+            is_top_miner: rewards.is_top_miner as u64,
+            bot_fee,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
 
     Ok(())
 }
@@ -3,10 +3,13 @@ use solana_program::log::sol_log;
 use spl_token::amount_to_ui_amount;
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Claims a block reward.
 pub fn process_claim_ore(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(9));
     let [signer_info, miner_info, mint_info, recipient_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program] =
         accounts
     else {
@@ -15,7 +18,10 @@ pub fn process_claim_ore(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramR
     signer_info.is_signer()?;
     let miner = miner_info
         .as_account_mut::<Miner>(&ore_api::ID)?
-        .assert_mut(|m| m.authority == *signer_info.key)?;
+        .assert_mut_err(
+            |m| m.authority == *signer_info.key,
+            OreError::NotAuthorized.into(),
+        )?;
     mint_info.has_address(&MINT_ADDRESS)?.as_mint()?;
     recipient_info.is_writable()?;
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
@@ -23,6 +29,7 @@ pub fn process_claim_ore(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramR
     system_program.is_program(&system_program::ID)?;
     token_program.is_program(&spl_token::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // Load recipient.
     if recipient_info.data_is_empty() {
@@ -40,7 +47,9 @@ pub fn process_claim_ore(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramR
     }
 
     // Normalize amount.
-    let amount = miner.claim_ore(&clock, treasury);
+    miner.update_rewards(treasury)?;
+    let gross_amount = miner.refined_ore + miner.rewards_ore;
+    let amount = miner.claim_ore(&clock, treasury)?;
 
     sol_log(
         &format!(
@@ -60,5 +69,18 @@ pub fn process_claim_ore(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramR
         &[TREASURY],
     )?;
 
+    // Emit event.
+    emit_event(
+        events,
+        ClaimOREEvent {
+            disc: OreEvent::ClaimORE as u64,
+            authority: miner.authority,
+            amount,
+            fee: gross_amount - amount,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
+
     Ok(())
 }
@@ -2,18 +2,25 @@ use ore_api::prelude::*;
 use solana_program::{log::sol_log, native_token::lamports_to_sol};
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Claims a block reward.
 pub fn process_claim_sol(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(3));
     let [signer_info, miner_info, system_program] = accounts else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
     let miner = miner_info
         .as_account_mut::<Miner>(&ore_api::ID)?
-        .assert_mut(|m| m.authority == *signer_info.key)?;
+        .assert_mut_err(
+            |m| m.authority == *signer_info.key,
+            OreError::NotAuthorized.into(),
+        )?;
     system_program.is_program(&system_program::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // Normalize amount.
     let amount = miner.claim_sol(&clock);
@@ -23,5 +30,17 @@ pub fn process_claim_sol(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramR
     // Transfer reward to recipient.
     miner_info.send(amount, signer_info);
 
+    // Emit event.
+    emit_event(
+        events,
+        ClaimSOLEvent {
+            disc: OreEvent::ClaimSOL as u64,
+            authority: miner.authority,
+            amount,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
+
     Ok(())
 }
@@ -3,6 +3,8 @@ use solana_program::log::sol_log;
 use spl_token::amount_to_ui_amount;
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Claims yield from the staking contract.
 pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
@@ -11,6 +13,7 @@ pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> Program
 
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(9));
     let [signer_info, mint_info, recipient_info, stake_info, treasury_info, treasury_tokens_info, system_program, token_program, associated_token_program] =
         accounts
     else {
@@ -21,7 +24,10 @@ pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> Program
     recipient_info.is_writable()?;
     let stake = stake_info
         .as_account_mut::<Stake>(&ore_api::ID)?
-        .assert_mut(|s| s.authority == *signer_info.key)?;
+        .assert_mut_err(
+            |s| s.authority == *signer_info.key,
+            OreError::NotAuthorized.into(),
+        )?;
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     treasury_tokens_info
         .is_writable()?
@@ -29,6 +35,7 @@ pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> Program
     system_program.is_program(&system_program::ID)?;
     token_program.is_program(&spl_token::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // Open recipient token account.
     if recipient_info.data_is_empty() {
@@ -46,7 +53,7 @@ pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> Program
     }
 
     // Claim yield from stake account.
-    let amount = stake.claim(amount, &clock, treasury);
+    let amount = stake.claim(amount, &clock, treasury)?;
 
     // Transfer ORE to recipient.
     transfer_signed(
@@ -67,5 +74,17 @@ pub fn process_claim_yield(accounts: &[AccountInfo<'_>], data: &[u8]) -> Program
         .as_str(),
     );
 
+    // Emit event.
+    emit_event(
+        events,
+        ClaimYieldEvent {
+            disc: OreEvent::ClaimYield as u64,
+            authority: stake.authority,
+            amount,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
+
     Ok(())
 }
@@ -16,9 +16,15 @@ pub fn process_close(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     rent_payer_info.is_writable()?;
     round_info
         .as_account_mut::<Round>(&ore_api::ID)?
-        .assert_mut(|r| r.id < board.round_id)?
-        .assert_mut(|r| r.expires_at < clock.slot)? // Ensure round has expired.
-        .assert_mut(|r| r.rent_payer == *rent_payer_info.key)?; // Ensure the rent payer is the correct one.
+        .assert_mut_err(|r| r.id < board.round_id, OreError::InvalidRound.into())?
+        .assert_mut_err(
+            |r| r.expires_at < clock.slot,
+            OreError::RoundNotExpired.into(),
+        )? // Ensure round has expired.
+        .assert_mut_err(
+            |r| r.rent_payer == *rent_payer_info.key,
+            OreError::NotAuthorized.into(),
+        )?; // Ensure the rent payer is the correct one.
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     system_program.is_program(&system_program::ID)?;
 
@@ -1,20 +1,72 @@
 use entropy_api::state::Var;
 use ore_api::prelude::*;
-use solana_program::{keccak::hashv, log::sol_log, native_token::lamports_to_sol};
+use solana_program::{log::sol_log, native_token::lamports_to_sol};
 use steel::*;
 
+use crate::automate::grow_automation;
+use crate::checkpoint::checkpoint_miner;
+use crate::log::emit_event;
 use crate::reset::ORE_VAR_ADDRESS;
 
 /// Deploys capital to prospect on a square.
 pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
+    let (data, guard) = data.split_at(data.len().min(std::mem::size_of::<Deploy>()));
     let args = Deploy::try_from_bytes(data)?;
-    let mut amount = u64::from_le_bytes(args.amount);
+    let guard = parse_guard(guard)?;
+    let amount = u64::from_le_bytes(args.amount);
     let mask = u32::from_le_bytes(args.squares);
 
+    // Convert provided 32-bit mask into per-square amounts, where each bit in the mask
+    // determines if that square index is selected or not.
+    let mut amounts = [0; 25];
+    for i in 0..25 {
+        if (mask & (1 << i)) != 0 {
+            amounts[i] = amount;
+        }
+    }
+
+    deploy_amounts(accounts, amounts, guard)
+}
+
+/// Parses the optional guard after the deploy args. Deploys without one are not limited.
+pub fn parse_guard(data: &[u8]) -> Result<DeployGuard, ProgramError> {
+    if data.is_empty() {
+        return Ok(DeployGuard::default());
+    }
+    bytemuck::try_from_bytes::<DeployGuard>(data)
+        .copied()
+        .map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+/// Deploys the given amount to each square. Squares with a zero amount are skipped, and so are
+/// squares the guard rejects.
+///
+/// A miner may top up a square it already holds as long as no one deployed to it since, so its
+/// range for top miner sampling stays contiguous. Automations never top up, since the executor
+/// would be paid again for the same squares. For the same reason an automation deploys at most
+/// once per round, and only if it deploys something. Skipped squares are reported in a
+/// `DeploySkippedEvent`.
+///
+/// The entropy accounts may be followed by the miner's previous round and the treasury, in which
+/// case that round is checkpointed first instead of failing with `NotCheckpointed`. The ORE
+/// program, which events are logged through, may be anywhere after the first 7 accounts.
+/// Transactions that leave it out still deploy, without events.
+pub fn deploy_amounts(
+    accounts: &[AccountInfo<'_>],
+    mut amounts: [u64; 25],
+    guard: DeployGuard,
+) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
-    let (ore_accounts, entropy_accounts) = accounts.split_at(7);
+    let (ore_accounts, optional_accounts) = accounts.split_at(accounts.len().min(7));
+    let ore_program = optional_accounts.iter().find(|a| *a.key == ore_api::ID);
+    let optional_accounts: Vec<&AccountInfo<'_>> = optional_accounts
+        .iter()
+        .filter(|a| *a.key != ore_api::ID)
+        .collect();
+    let (entropy_accounts, checkpoint_accounts) =
+        optional_accounts.split_at(optional_accounts.len().min(2));
     sol_log(&format!("Ore accounts: {:?}", ore_accounts.len()).to_string());
     sol_log(&format!("Entropy accounts: {:?}", entropy_accounts.len()).to_string());
     let [signer_info, authority_info, automation_info, board_info, miner_info, round_info, system_program] =
@@ -29,14 +81,25 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
         .has_seeds(&[AUTOMATION, &authority_info.key.to_bytes()], &ore_api::ID)?;
     let board = board_info
         .as_account_mut::<Board>(&ore_api::ID)?
-        .assert_mut(|b| clock.slot >= b.start_slot && clock.slot < b.end_slot)?;
+        .assert_mut_err(
+            |b| clock.slot >= b.start_slot && clock.slot < b.end_slot,
+            OreError::RoundNotActive.into(),
+        )?;
     let round = round_info
         .as_account_mut::<Round>(&ore_api::ID)?
-        .assert_mut(|r| r.id == board.round_id)?;
+        .assert_mut_err(|r| r.id == board.round_id, OreError::InvalidRound.into())?;
     miner_info
         .is_writable()?
         .has_seeds(&[MINER, &authority_info.key.to_bytes()], &ore_api::ID)?;
     system_program.is_program(&system_program::ID)?;
+    if let Some(ore_program) = ore_program {
+        ore_program.is_program(&ore_api::ID)?;
+    }
+
+    // Fail if the deploy landed later than its sender allowed.
+    if !guard.is_on_time(clock.slot) {
+        return Err(OreError::DeployDeadlinePassed.into());
+    }
 
     // Wait until first deploy to start round.
     if board.end_slot == u64::MAX {
@@ -45,13 +108,16 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
         round.expires_at = board.end_slot + ONE_DAY_SLOTS;
 
         // Bump var to the next value.
-        let [var_info, entropy_program] = entropy_accounts else {
+        let [var_info, entropy_program] = *entropy_accounts else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
         var_info
             .has_address(&ORE_VAR_ADDRESS)?
             .as_account::<Var>(&entropy_api::ID)?
-            .assert(|v| v.authority == *board_info.key)?;
+            .assert_err(
+                |v| v.authority == *board_info.key,
+                OreError::InvalidVar.into(),
+            )?;
         entropy_program.is_program(&entropy_api::ID)?;
 
         // Bump var to the next value.
@@ -65,41 +131,59 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
 
     // Check if signer is the automation executor.
     let automation = if !automation_info.data_is_empty() {
+        // Grow automations opened before limits were added. Their balance pays the extra rent.
+        let rent = grow_automation(automation_info)?;
         let automation = automation_info
             .as_account_mut::<Automation>(&ore_api::ID)?
-            .assert_mut(|a| a.executor == *signer_info.key)?
-            .assert_mut(|a| a.authority == *authority_info.key)?;
+            .assert_mut_err(
+                |a| a.executor == *signer_info.key,
+                OreError::NotAuthorized.into(),
+            )?
+            .assert_mut_err(
+                |a| a.authority == *authority_info.key,
+                OreError::NotAuthorized.into(),
+            )?;
+
+        // Close the automation once it has expired or used up its rounds, whatever its balance.
+        if automation.is_finished(clock.slot) {
+            automation_info.close(authority_info)?;
+            sol_log("Automation finished");
+            return Ok(());
+        }
+
+        let automation = automation.assert_mut_err(
+            |a| a.balance >= rent + a.amount + a.fee,
+            OreError::InsufficientAutomationBalance.into(),
+        )?;
+        automation.balance -= rent;
         Some(automation)
     } else {
         None
     };
 
-    // Update amount and mask for automation.
-    let mut squares = [false; 25];
-    if let Some(automation) = &automation {
-        // Set amount
-        amount = automation.amount;
-
-        // Set squares
-        match AutomationStrategy::from_u64(automation.strategy as u64) {
-            AutomationStrategy::Preferred => {
-                // Preferred automation strategy. Use the miner authority's provided mask.
-                for i in 0..25 {
-                    squares[i] = (automation.mask & (1 << i)) != 0;
-                }
+    // Check the automation has not deployed this round and can afford at least one square.
+    let budget = match &automation {
+        Some(automation) => {
+            if automation.round_id == round.id {
+                return Err(OreError::AutomationAlreadyDeployed.into());
             }
-            AutomationStrategy::Random => {
-                // Random automation strategy. Generate a random mask based on number of squares user wants to deploy to.
-                let num_squares = ((automation.mask & 0xFF) as u64).min(25);
-                let r = hashv(&[&automation.authority.to_bytes(), &round.id.to_le_bytes()]).0;
-                squares = generate_random_mask(num_squares, &r);
+            let budget = automation.round_budget(round.id);
+            if automation.amount + automation.fee > budget {
+                return Err(OreError::AutomationRoundBudgetExceeded.into());
             }
+            budget
         }
-    } else {
-        // Convert provided 32-bit mask into array of 25 booleans, where each bit in the mask
-        // determines if that square index is selected (true) or not (false)
+        None => u64::MAX,
+    };
+
+    // Update amounts for automation.
+    if let Some(automation) = &automation {
+        // Set squares
+        let squares = automation.squares(round);
+
+        // Set amounts
         for i in 0..25 {
-            squares[i] = (mask & (1 << i)) != 0;
+            amounts[i] = if squares[i] { automation.amount } else { 0 };
         }
     }
 
@@ -126,22 +210,42 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
     } else {
         miner_info
             .as_account_mut::<Miner>(&ore_api::ID)?
-            .assert_mut(|m| {
-                if let Some(automation) = &automation {
-                    m.authority == automation.authority
-                } else {
-                    m.authority == *signer_info.key
-                }
-            })?
+            .assert_mut_err(
+                |m| {
+                    if let Some(automation) = &automation {
+                        m.authority == automation.authority
+                    } else {
+                        m.authority == *signer_info.key
+                    }
+                },
+                OreError::NotAuthorized.into(),
+            )?
     };
 
     // Reset miner
     if miner.round_id != round.id {
+        // Checkpoint prior round if its account was provided.
+        if miner.checkpoint_id != miner.round_id {
+            if let [prev_round_info, treasury_info] = *checkpoint_accounts {
+                let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
+                checkpoint_miner(
+                    &clock,
+                    round.id,
+                    signer_info,
+                    board_info,
+                    miner_info,
+                    miner,
+                    prev_round_info,
+                    treasury,
+                    ore_program,
+                )?;
+            }
+        }
+
         // Assert miner has checkpointed prior round.
-        assert!(
-            miner.checkpoint_id == miner.round_id,
-            "Miner has not checkpointed"
-        );
+        if miner.checkpoint_id != miner.round_id {
+            return Err(OreError::NotCheckpointed.into());
+        }
 
         // Reset miner for new round.
         miner.deployed = [0; 25];
@@ -150,47 +254,68 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
     }
 
     // Calculate all deployments.
+    let mut deployed = [0; 25];
     let mut total_amount = 0;
     let mut total_squares = 0;
-    for (square_id, &should_deploy) in squares.iter().enumerate() {
-        // Skip if square index is out of bounds.
-        if square_id > 24 {
-            break;
-        }
-
+    let mut held = 0u64;
+    let mut guarded = 0u64;
+    for (square_id, &amount) in amounts.iter().enumerate() {
         // Skip if square is not deployed to.
-        if !should_deploy {
+        if amount == 0 {
             continue;
         }
 
-        // Skip if miner already deployed to this square.
-        if miner.deployed[square_id] > 0 {
+        // Skip if miner already deployed to this square and cannot top it up.
+        let is_top_up = miner.deployed[square_id] > 0;
+        if is_top_up {
+            let is_last = miner.cumulative[square_id] + miner.deployed[square_id]
+                == round.deployed[square_id];
+            if automation.is_some() || !is_last {
+                held |= 1 << square_id;
+                continue;
+            }
+        }
+
+        // Skip, or fail if strict, if the square got more crowded than the guard allows.
+        if !guard.allows(square_id, round.deployed[square_id], amount) {
+            if guard.is_strict() {
+                return Err(OreError::DeployGuardFailed.into());
+            }
+            guarded |= 1 << square_id;
             continue;
         }
 
-        // Record cumulative amount.
-        miner.cumulative[square_id] = round.deployed[square_id];
+        // Record cumulative amount. A top-up extends the range the miner already has.
+        if !is_top_up {
+            miner.cumulative[square_id] = round.deployed[square_id];
+            round.count[square_id] += 1;
+        }
 
         // Update miner
-        miner.deployed[square_id] = amount;
+        miner.deployed[square_id] += amount;
 
         // Update board
         round.deployed[square_id] += amount;
         round.total_deployed += amount;
-        round.count[square_id] += 1;
 
         // Update totals.
+        deployed[square_id] = amount;
         total_amount += amount;
         total_squares += 1;
 
-        // Exit early if automation does not have enough balance for another square.
+        // Exit early if automation does not have enough balance or budget for another square.
         if let Some(automation) = &automation {
-            if total_amount + automation.fee + amount > automation.balance {
+            if total_amount + automation.fee + automation.amount > automation.balance.min(budget) {
                 break;
             }
         }
     }
 
+    // Do not pay the executor for a deploy that deployed nothing.
+    if automation.is_some() && total_amount == 0 {
+        return Err(OreError::AmountTooSmall.into());
+    }
+
     // Top up checkpoint fee.
     if miner.checkpoint_fee == 0 {
         miner.checkpoint_fee = CHECKPOINT_FEE;
@@ -203,8 +328,19 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
         automation_info.send(total_amount, &round_info);
         automation_info.send(automation.fee, &signer_info);
 
-        // Close automation if balance is less than what's required to deploy 1 square.
-        if automation.balance < automation.amount + automation.fee {
+        // Track spending per round.
+        if automation.round_id != round.id {
+            automation.round_id = round.id;
+            automation.round_spent = 0;
+            automation.rounds += 1;
+        }
+        automation.round_spent += total_amount + automation.fee;
+
+        // Close automation if balance is less than what's required to deploy 1 square, or this
+        // was its last round.
+        if automation.balance < automation.amount + automation.fee
+            || automation.is_finished(clock.slot)
+        {
             automation_info.close(authority_info)?;
         }
     } else {
@@ -216,28 +352,43 @@ pub fn process_deploy(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResul
         &format!(
             "Round #{}: deploying {} SOL to {} squares",
             round.id,
-            lamports_to_sol(amount),
+            lamports_to_sol(total_amount),
             total_squares,
         )
         .as_str(),
     );
 
-    Ok(())
-}
-
-fn generate_random_mask(num_squares: u64, r: &[u8]) -> [bool; 25] {
-    let mut new_mask = [false; 25];
-    let mut selected = 0;
-    for i in 0..25 {
-        let rand_byte = r[i];
-        let remaining_needed = num_squares as u64 - selected as u64;
-        let remaining_positions = 25 - i;
-        if remaining_needed > 0
-            && (rand_byte as u64) * (remaining_positions as u64) < (remaining_needed * 256)
-        {
-            new_mask[i] = true;
-            selected += 1;
+    // Emit event.
+    let events = ore_program.map(|ore_program| (board_info, ore_program));
+    emit_event(
+        events,
+        DeployEvent {
+            disc: OreEvent::Deploy as u64,
+            signer: *signer_info.key,
+            authority: *authority_info.key,
+            round_id: round.id,
+            amounts: deployed,
+            total_amount,
+            num_squares: total_squares,
+            round_total_deployed: round.total_deployed,
+            ts: clock.unix_timestamp,
         }
+        .to_bytes(),
+    )?;
+    if held != 0 || guarded != 0 {
+        emit_event(
+            events,
+            DeploySkippedEvent {
+                disc: OreEvent::DeploySkipped as u64,
+                authority: *authority_info.key,
+                round_id: round.id,
+                held,
+                guarded,
+                ts: clock.unix_timestamp,
+            }
+            .to_bytes(),
+        )?;
     }
-    new_mask
+
+    Ok(())
 }
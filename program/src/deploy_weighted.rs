@@ -0,0 +1,15 @@
+use ore_api::prelude::*;
+use steel::*;
+
+use crate::deploy::{deploy_amounts, parse_guard};
+
+/// Deploys a different amount of capital to each square.
+pub fn process_deploy_weighted(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse data.
+    let (data, guard) = data.split_at(data.len().min(std::mem::size_of::<DeployWeighted>()));
+    let args = DeployWeighted::try_from_bytes(data)?;
+    let amounts = args.amounts.map(u64::from_le_bytes);
+    let guard = parse_guard(guard)?;
+
+    deploy_amounts(accounts, amounts, guard)
+}
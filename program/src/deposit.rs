@@ -3,6 +3,8 @@ use solana_program::log::sol_log;
 use spl_token::amount_to_ui_amount;
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Deposits ORE into the staking contract.
 pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
@@ -11,6 +13,7 @@ pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
 
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(9));
     let [signer_info, mint_info, sender_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program] =
         accounts
     else {
@@ -26,6 +29,7 @@ pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
     system_program.is_program(&system_program::ID)?;
     token_program.is_program(&spl_token::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // Open stake account.
     let stake = if stake_info.data_is_empty() {
@@ -50,7 +54,10 @@ pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
     } else {
         stake_info
             .as_account_mut::<Stake>(&ore_api::ID)?
-            .assert_mut(|s| s.authority == *signer_info.key)?
+            .assert_mut_err(
+                |s| s.authority == *signer_info.key,
+                OreError::NotAuthorized.into(),
+            )?
     };
 
     // Create stake tokens account.
@@ -72,7 +79,7 @@ pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
     // assert!(stake.is_seeker == 1, "Only seekers can deposit stake");
 
     // Deposit into stake account.
-    let amount = stake.deposit(amount, &clock, treasury, &sender);
+    let amount = stake.deposit(amount, &clock, treasury, &sender)?;
 
     // Transfer ORE to treasury.
     transfer(
@@ -95,7 +102,22 @@ pub fn process_deposit(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResu
     // Safety check.
     let stake_tokens =
         stake_tokens_info.as_associated_token_account(stake_info.key, mint_info.key)?;
-    assert!(stake_tokens.amount() >= stake.balance);
+    if stake_tokens.amount() < stake.balance {
+        return Err(OreError::InvariantViolation.into());
+    }
+
+    // Emit event.
+    emit_event(
+        events,
+        DepositEvent {
+            disc: OreEvent::Deposit as u64,
+            authority: stake.authority,
+            amount,
+            balance: stake.balance,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
 
     Ok(())
 }
@@ -6,6 +6,7 @@ mod claim_sol;
 mod claim_yield;
 mod close;
 mod deploy;
+mod deploy_weighted;
 mod deposit;
 mod log;
 mod new_var;
@@ -25,6 +26,7 @@ use claim_sol::*;
 use claim_yield::*;
 use close::*;
 use deploy::*;
+use deploy_weighted::*;
 use deposit::*;
 use log::*;
 use new_var::*;
@@ -53,6 +55,7 @@ pub fn process_instruction(
         OreInstruction::ClaimSOL => process_claim_sol(accounts, data)?,
         OreInstruction::ClaimORE => process_claim_ore(accounts, data)?,
         OreInstruction::Deploy => process_deploy(accounts, data)?,
+        OreInstruction::DeployWeighted => process_deploy_weighted(accounts, data)?,
         OreInstruction::Log => process_log(accounts, data)?,
         OreInstruction::Close => process_close(accounts, data)?,
         OreInstruction::Reset => process_reset(accounts, data)?,
@@ -13,3 +13,32 @@ pub fn process_log(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult
 
     Ok(())
 }
+
+/// Parses the board and ORE program an instruction logs its events through. They are optional
+/// trailing accounts, so transactions built before events were added still work and emit none.
+pub fn event_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<Option<(&'a AccountInfo<'info>, &'a AccountInfo<'info>)>, ProgramError> {
+    match accounts {
+        [] => Ok(None),
+        [board_info, ore_program] => {
+            board_info.as_account::<Board>(&ore_api::ID)?;
+            ore_program.is_program(&ore_api::ID)?;
+            Ok(Some((board_info, ore_program)))
+        }
+        _ => Err(ProgramError::NotEnoughAccountKeys),
+    }
+}
+
+/// Logs an event through the board and ORE program, if the instruction was given them.
+pub fn emit_event<'info>(
+    accounts: Option<(&AccountInfo<'info>, &AccountInfo<'info>)>,
+    event: &[u8],
+) -> ProgramResult {
+    match accounts {
+        Some((board_info, ore_program)) => {
+            program_log(&[board_info.clone(), ore_program.clone()], event)
+        }
+        None => Ok(()),
+    }
+}
@@ -1,5 +1,5 @@
 use entropy_api::state::Var;
-use ore_api::prelude::*;
+use ore_api::{prelude::*, sim};
 use solana_program::{keccak, log::sol_log, pubkey};
 use steel::*;
 
@@ -20,14 +20,17 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     signer_info.is_signer()?;
     let board = board_info
         .as_account_mut::<Board>(&ore_api::ID)?
-        .assert_mut(|b| clock.slot >= b.end_slot + INTERMISSION_SLOTS)?;
+        .assert_mut_err(
+            |b| clock.slot >= b.end_slot.saturating_add(INTERMISSION_SLOTS),
+            OreError::RoundNotEnded.into(),
+        )?;
     let config = config_info.as_account::<Config>(&ore_api::ID)?;
     fee_collector_info
         .is_writable()?
         .has_address(&config.fee_collector)?;
     let round = round_info
         .as_account_mut::<Round>(&ore_api::ID)?
-        .assert_mut(|r| r.id == board.round_id)?;
+        .assert_mut_err(|r| r.id == board.round_id, OreError::InvalidRound.into())?;
     round_next_info
         .is_empty()?
         .is_writable()?
@@ -69,10 +72,15 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     let var = var_info
         .has_address(&ORE_VAR_ADDRESS)? // TODO Verify address matches whats in the config.
         .as_account::<Var>(&entropy_api::ID)?
-        .assert(|v| v.authority == *board_info.key)?
-        .assert(|v| v.slot_hash != [0; 32])?
-        .assert(|v| v.seed != [0; 32])?
-        .assert(|v| v.value != [0; 32])?;
+        .assert_err(
+            |v| {
+                v.authority == *board_info.key
+                    && v.slot_hash != [0; 32]
+                    && v.seed != [0; 32]
+                    && v.value != [0; 32]
+            },
+            OreError::InvalidVar.into(),
+        )?;
     entropy_program.is_program(&entropy_api::ID)?;
 
     // Print the seed and slot hash.
@@ -86,18 +94,19 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     sol_log(&format!("var value: {:?}", value).to_string());
     round.slot_hash = var.value;
 
+    // Settle the round.
+    let outcome = sim::settle_round(round, mint.supply(), treasury.motherlode);
+    outcome.apply(round);
+
     // Exit early if no slot hash was found.
-    let Some(r) = round.rng() else {
-        // Slot hash could not be found, refund all SOL.
-        round.total_vaulted = 0;
-        round.total_winnings = 0;
-        round.total_deployed = 0;
+    let Some(winning_square) = outcome.winning_square else {
+        // Slot hash could not be found, all SOL is refunded on checkpoint.
 
         // Emit event.
         program_log(
             &[board_info.clone(), ore_program.clone()],
             ResetEvent {
-                disc: 0,
+                disc: OreEvent::Reset as u64,
                 round_id: round.id,
                 start_slot: board.start_slot,
                 end_slot: board.end_slot,
@@ -121,23 +130,16 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
         return Ok(());
     };
 
-    // Caculate admin fees.
-    let total_admin_fee = round.total_deployed / 100;
-
-    // Get the winning square.
-    let winning_square = round.winning_square(r);
-
     // If no one deployed on the winning square, vault all deployed.
     if round.deployed[winning_square] == 0 {
         // Vault all deployed.
-        round.total_vaulted = round.total_deployed - total_admin_fee;
-        treasury.balance += round.total_deployed - total_admin_fee;
+        treasury.balance += round.total_vaulted;
 
         // Emit event.
         program_log(
             &[board_info.clone(), ore_program.clone()],
             ResetEvent {
-                disc: 0,
+                disc: OreEvent::Reset as u64,
                 round_id: round.id,
                 start_slot: board.start_slot,
                 end_slot: board.end_slot,
@@ -160,68 +162,62 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
         board.end_slot = u64::MAX;
 
         // Do SOL transfers.
-        round_info.send(total_admin_fee, &fee_collector_info);
-        round_info.send(round.total_deployed - total_admin_fee, &treasury_info);
+        round_info.send(outcome.admin_fee, &fee_collector_info);
+        round_info.send(round.total_vaulted, &treasury_info);
         return Ok(());
     }
 
-    // Get winnings amount (total deployed on all non-winning squares, minus admin fee).
-    let winnings = round.calculate_total_winnings(winning_square);
-    let winnings_admin_fee = winnings / 100; // 1% admin fee.
-    let winnings = winnings - winnings_admin_fee;
-
-    // Subtract vault amount from winnings.
-    let vault_amount = winnings / 10; // 10% of winnings.
-    let winnings = winnings - vault_amount;
-    round.total_winnings = winnings;
-    round.total_vaulted = vault_amount;
-    treasury.balance += vault_amount;
+    // Vault 10% of winnings.
+    treasury.balance += round.total_vaulted;
 
     // Sanity check.
-    assert!(
-        round.total_deployed
-            >= round.total_vaulted
-                + round.total_winnings
-                + round.deployed[winning_square]
-                + winnings_admin_fee
-    );
+    let winnings_admin_fee = round.calculate_total_winnings(winning_square) / 100;
+    if round.total_deployed
+        < round.total_vaulted
+            + round.total_winnings
+            + round.deployed[winning_square]
+            + winnings_admin_fee
+    {
+        return Err(OreError::InvariantViolation.into());
+    }
 
     // Mint +1 ORE for the winning miner(s).
-    let mint_amount = MAX_SUPPLY.saturating_sub(mint.supply()).min(ONE_ORE);
-    round.top_miner_reward = mint_amount;
     mint_to_signed(
         mint_info,
         treasury_tokens_info,
         treasury_info,
         token_program,
-        mint_amount,
+        round.top_miner_reward,
         &[TREASURY],
     )?;
 
-    // With 1 in 2 odds, split the +1 ORE reward.
-    if round.is_split_reward(r) {
-        round.top_miner = SPLIT_ADDRESS;
-    }
-
     // Payout the motherlode if it was activated.
-    if round.did_hit_motherlode(r) {
-        round.motherlode = treasury.motherlode;
-        treasury.motherlode = 0;
+    treasury.motherlode -= round.motherlode;
+    if round.motherlode > 0 {
+        program_log(
+            &[board_info.clone(), ore_program.clone()],
+            MotherlodeEvent {
+                disc: OreEvent::Motherlode as u64,
+                amount: round.motherlode,
+                round_id: round.id,
+                num_miners: round.count[winning_square],
+                ts: clock.unix_timestamp,
+            }
+            .to_bytes(),
+        )?;
     }
 
     // Mint +0.2 ORE to the motherlode rewards pool.
-    let mint = mint_info.as_mint()?;
-    let motherlode_mint_amount = MAX_SUPPLY.saturating_sub(mint.supply()).min(ONE_ORE / 5);
-    if motherlode_mint_amount > 0 {
+    if outcome.motherlode_minted > 0 {
         mint_to_signed(
             mint_info,
             treasury_tokens_info,
             treasury_info,
             token_program,
-            motherlode_mint_amount,
+            outcome.motherlode_minted,
             &[TREASURY],
         )?;
-        treasury.motherlode += motherlode_mint_amount;
+        treasury.motherlode += outcome.motherlode_minted;
     }
 
     // Validate top miner.
@@ -240,7 +236,7 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     program_log(
         &[board_info.clone(), ore_program.clone()],
         ResetEvent {
-            disc: 0,
+            disc: OreEvent::Reset as u64,
             round_id: round.id,
             start_slot: board.start_slot,
             end_slot: board.end_slot,
@@ -251,7 +247,7 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
             total_deployed: round.total_deployed,
             total_vaulted: round.total_vaulted,
             total_winnings: round.total_winnings,
-            total_minted: mint_amount + motherlode_mint_amount,
+            total_minted: outcome.total_minted(),
             ts: clock.unix_timestamp,
         }
         .to_bytes(),
@@ -263,8 +259,8 @@ pub fn process_reset(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResul
     board.end_slot = u64::MAX; // board.start_slot + 150;
 
     // Do SOL transfers.
-    round_info.send(total_admin_fee, &fee_collector_info);
-    round_info.send(vault_amount, &treasury_info);
+    round_info.send(outcome.admin_fee, &fee_collector_info);
+    round_info.send(round.total_vaulted, &treasury_info);
 
     Ok(())
 }
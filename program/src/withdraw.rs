@@ -3,6 +3,8 @@ use solana_program::log::sol_log;
 use spl_token::amount_to_ui_amount;
 use steel::*;
 
+use crate::log::{emit_event, event_accounts};
+
 /// Withdraws ORE from the staking contract.
 pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
@@ -11,6 +13,7 @@ pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
 
     // Load accounts.
     let clock = Clock::get()?;
+    let (accounts, log_accounts) = accounts.split_at(accounts.len().min(9));
     let [signer_info, mint_info, recipient_info, stake_info, stake_tokens_info, treasury_info, system_program, token_program, associated_token_program] =
         accounts
     else {
@@ -23,12 +26,16 @@ pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
         .as_associated_token_account(&signer_info.key, &mint_info.key)?;
     let stake = stake_info
         .as_account_mut::<Stake>(&ore_api::ID)?
-        .assert_mut(|s| s.authority == *signer_info.key)?;
+        .assert_mut_err(
+            |s| s.authority == *signer_info.key,
+            OreError::NotAuthorized.into(),
+        )?;
     stake_tokens_info.as_associated_token_account(stake_info.key, mint_info.key)?;
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     system_program.is_program(&system_program::ID)?;
     token_program.is_program(&spl_token::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
+    let events = event_accounts(log_accounts)?;
 
     // Open recipient token account.
     if recipient_info.data_is_empty() {
@@ -44,7 +51,7 @@ pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
     }
 
     // Deposit into stake account.
-    let amount = stake.withdraw(amount, &clock, treasury);
+    let amount = stake.withdraw(amount, &clock, treasury)?;
 
     // Transfer ORE to recipient.
     transfer_signed(
@@ -68,7 +75,22 @@ pub fn process_withdraw(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramRes
     // Safety check.
     let stake_tokens =
         stake_tokens_info.as_associated_token_account(stake_info.key, mint_info.key)?;
-    assert!(stake_tokens.amount() >= stake.balance);
+    if stake_tokens.amount() < stake.balance {
+        return Err(OreError::InvariantViolation.into());
+    }
+
+    // Emit event.
+    emit_event(
+        events,
+        WithdrawEvent {
+            disc: OreEvent::Withdraw as u64,
+            authority: stake.authority,
+            amount,
+            balance: stake.balance,
+            ts: clock.unix_timestamp,
+        }
+        .to_bytes(),
+    )?;
 
     Ok(())
 }
@@ -10,9 +10,10 @@ pub fn process_wrap(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    config_info
-        .as_account::<Config>(&ore_api::ID)?
-        .assert(|c| c.bury_authority == *signer_info.key)?;
+    config_info.as_account::<Config>(&ore_api::ID)?.assert_err(
+        |c| c.bury_authority == *signer_info.key,
+        OreError::NotAuthorized.into(),
+    )?;
     let treasury = treasury_info.as_account_mut::<Treasury>(&ore_api::ID)?;
     treasury_sol_info
         .is_writable()?
@@ -28,10 +29,12 @@ pub fn process_wrap(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult
 
     // Check min balance.
     let min_balance = Rent::get()?.minimum_balance(std::mem::size_of::<Treasury>());
-    assert!(
-        treasury_info.lamports() >= min_balance,
-        "Insufficient SOL balance"
-    );
+    if treasury_info.lamports() < min_balance {
+        return Err(trace(
+            "Insufficient SOL balance",
+            OreError::InvariantViolation.into(),
+        ));
+    }
 
     // Update treasury.
     treasury.balance -= amount;
@@ -0,0 +1,338 @@
+#![allow(dead_code)]
+
+use entropy_api::state::Var;
+use ore_api::prelude::*;
+use solana_program::{program_option::COption, program_pack::Pack, rent::Rent};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    keccak::hashv,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use steel::*;
+
+/// Initial ORE supply, held by the treasury.
+pub const INITIAL_SUPPLY: u64 = 1_000 * ONE_ORE;
+
+/// Size of an automation account opened before limits were added.
+pub const LEGACY_AUTOMATION_LEN: usize = 8 + 104;
+
+/// A local bank with the ORE program, a stub entropy program and a freshly initialized board.
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub fee_collector: Pubkey,
+}
+
+impl Harness {
+    /// Starts a bank with the given motherlode pool. The entropy var holds `value`.
+    pub async fn new(motherlode: u64, value: [u8; 32]) -> Self {
+        let mut program_test =
+            ProgramTest::new("ore", ore_api::ID, processor!(ore::process_instruction));
+        program_test.add_program("entropy", entropy_api::ID, processor!(process_entropy_stub));
+        program_test.prefer_bpf(false);
+
+        // Board and first round. Miners start out checkpointed at round 0, so play from round 1.
+        let fee_collector = Pubkey::new_unique();
+        program_test.add_account(
+            board_pda().0,
+            program_account(&Board {
+                round_id: 1,
+                start_slot: 0,
+                end_slot: u64::MAX,
+            }),
+        );
+        let mut round = Round::zeroed();
+        round.id = 1;
+        round.expires_at = u64::MAX;
+        program_test.add_account(round_pda(1).0, program_account(&round));
+
+        // Config.
+        let mut config = Config::zeroed();
+        config.admin = ADMIN_ADDRESS;
+        config.fee_collector = fee_collector;
+        config.var_address = var_address();
+        program_test.add_account(config_pda().0, program_account(&config));
+        program_test.add_account(
+            fee_collector,
+            Account::new(LAMPORTS_PER_SOL, 0, &system_program::ID),
+        );
+
+        // Treasury, mint and treasury tokens.
+        let mut treasury = Treasury::zeroed();
+        treasury.motherlode = motherlode;
+        program_test.add_account(TREASURY_ADDRESS, program_account(&treasury));
+        program_test.add_account(MINT_ADDRESS, mint_account(INITIAL_SUPPLY));
+        program_test.add_account(
+            treasury_tokens_address(),
+            token_account(TREASURY_ADDRESS, INITIAL_SUPPLY),
+        );
+
+        // Entropy var.
+        program_test.add_account(var_address(), var_account(value));
+
+        let context = program_test.start_with_context().await;
+        Self {
+            context,
+            fee_collector,
+        }
+    }
+
+    /// Creates a funded wallet.
+    pub async fn wallet(&mut self, lamports: u64) -> Keypair {
+        let wallet = Keypair::new();
+        let payer = self.context.payer.insecure_clone();
+        let ix = system_instruction::transfer(&payer.pubkey(), &wallet.pubkey(), lamports);
+        self.process(&[ix], &[]).await.unwrap();
+        wallet
+    }
+
+    /// Gives a wallet an ORE token account with the given balance.
+    pub fn set_ore_balance(&mut self, owner: Pubkey, amount: u64) {
+        let address = get_associated_token_address(&owner, &MINT_ADDRESS);
+        self.context
+            .set_account(&address, &token_account(owner, amount).into());
+    }
+
+    /// Sets the value the entropy var will reveal at the next reset.
+    pub fn set_var_value(&mut self, value: [u8; 32]) {
+        self.context
+            .set_account(&var_address(), &AccountSharedData::from(var_account(value)));
+    }
+
+    /// Signs with the bank payer and the given signers, and processes the instructions.
+    pub async fn process(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let tx = self.transaction(ixs, signers).await?;
+        self.context.banks_client.process_transaction(tx).await
+    }
+
+    /// Like `process`, and returns the data of each event logged through the ORE program. Events
+    /// are read from a simulation of the same transaction, which records inner instructions.
+    pub async fn process_with_events(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<Vec<u8>>, BanksClientError> {
+        let tx = self.transaction(ixs, signers).await?;
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(tx.clone())
+            .await?;
+        let inner_instructions = simulation
+            .simulation_details
+            .and_then(|details| details.inner_instructions)
+            .expect("simulation records inner instructions");
+        let mut events = vec![];
+        for inner in inner_instructions.iter().flatten() {
+            let ix = &inner.instruction;
+            let program_id = tx.message.account_keys[ix.program_id_index as usize];
+            if let Some((&disc, event)) = ix.data.split_first() {
+                if program_id == ore_api::ID && disc == OreInstruction::Log as u8 {
+                    events.push(event.to_vec());
+                }
+            }
+        }
+        self.context.banks_client.process_transaction(tx).await?;
+        Ok(events)
+    }
+
+    /// Signs the instructions with the bank payer and the given signers, on a fresh blockhash.
+    async fn transaction(
+        &mut self,
+        ixs: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Transaction, BanksClientError> {
+        let payer = self.context.payer.insecure_clone();
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        Ok(Transaction::new_signed_with_payer(
+            ixs,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        ))
+    }
+
+    /// Shrinks an automation to the layout from before limits were added, keeping its balance.
+    pub async fn set_legacy_automation(&mut self, authority: Pubkey) {
+        let address = automation_pda(authority).0;
+        let automation: Automation = self.account(address).await.unwrap();
+        let mut data = serialize(&automation);
+        data.truncate(LEGACY_AUTOMATION_LEN);
+        let mut account = account_with_data(&ore_api::ID, data);
+        account.lamports += automation.balance;
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Warps past the end of the current round and its intermission.
+    pub async fn warp_to_reset(&mut self) {
+        let board = self.board().await;
+        self.context
+            .warp_to_slot(board.end_slot + INTERMISSION_SLOTS + 1)
+            .unwrap();
+    }
+
+    /// Warps past the expiry of the given round.
+    pub async fn warp_past_expiry(&mut self, round_id: u64) {
+        let round = self.round(round_id).await;
+        self.context.warp_to_slot(round.expires_at + 1).unwrap();
+    }
+
+    /// Resets the current round and warps to the start of the next one.
+    pub async fn reset(&mut self) -> Result<(), BanksClientError> {
+        let board = self.board().await;
+        let payer = self.context.payer.pubkey();
+        let ix = ore_api::sdk::reset(payer, self.fee_collector, board.round_id, payer);
+        self.process(&[ix], &[]).await?;
+        let board = self.board().await;
+        self.context.warp_to_slot(board.start_slot).unwrap();
+        Ok(())
+    }
+
+    pub async fn account<T: AccountDeserialize + Discriminator + Pod>(
+        &mut self,
+        address: Pubkey,
+    ) -> Option<T> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()?;
+        Some(*T::try_from_bytes(&account.data).unwrap())
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn ore_balance(&mut self, owner: Pubkey) -> u64 {
+        let address = get_associated_token_address(&owner, &MINT_ADDRESS);
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn board(&mut self) -> Board {
+        self.account(board_pda().0).await.unwrap()
+    }
+
+    pub async fn round(&mut self, id: u64) -> Round {
+        self.account(round_pda(id).0).await.unwrap()
+    }
+
+    pub async fn miner(&mut self, authority: Pubkey) -> Miner {
+        self.account(miner_pda(authority).0).await.unwrap()
+    }
+
+    pub async fn treasury(&mut self) -> Treasury {
+        self.account(TREASURY_ADDRESS).await.unwrap()
+    }
+}
+
+/// The entropy var read by deploy and reset.
+pub fn var_address() -> Pubkey {
+    entropy_api::state::var_pda(board_pda().0, 0).0
+}
+
+/// Finds a var value whose rng satisfies the predicate.
+pub fn find_value(predicate: impl Fn(&Round, u64) -> bool) -> [u8; 32] {
+    let mut round = Round::zeroed();
+    for nonce in 0u64.. {
+        round.slot_hash = hashv(&[&nonce.to_le_bytes()]).to_bytes();
+        if let Some(r) = round.rng() {
+            if predicate(&round, r) {
+                return round.slot_hash;
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Accepts every instruction, so deploy can bump the var without a real entropy program.
+fn process_entropy_stub(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _data: &[u8],
+) -> ProgramResult {
+    Ok(())
+}
+
+fn program_account<T: Discriminator + Pod>(state: &T) -> Account {
+    account_with_data(&ore_api::ID, serialize(state))
+}
+
+fn serialize<T: Discriminator + Pod>(state: &T) -> Vec<u8> {
+    let mut data = vec![0; 8];
+    data[0] = T::discriminator();
+    data.extend_from_slice(bytemuck::bytes_of(state));
+    data
+}
+
+fn var_account(value: [u8; 32]) -> Account {
+    let mut var = Var::zeroed();
+    var.authority = board_pda().0;
+    var.seed = [1; 32];
+    var.slot_hash = [2; 32];
+    var.value = value;
+    account_with_data(&entropy_api::ID, serialize(&var))
+}
+
+fn mint_account(supply: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(TREASURY_ADDRESS),
+        supply,
+        decimals: TOKEN_DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    account_with_data(&spl_token::ID, data)
+}
+
+fn token_account(owner: Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: MINT_ADDRESS,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    account_with_data(&spl_token::ID, data)
+}
+
+fn account_with_data(owner: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
@@ -0,0 +1,768 @@
+mod common;
+
+use common::{find_value, Harness, INITIAL_SUPPLY, LEGACY_AUTOMATION_LEN};
+use ore_api::{prelude::*, sim};
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+/// Deploys each (wallet, square, amount), settles the round and checkpoints every wallet.
+/// Returns the simulated settlement and each wallet's miner account after checkpoint.
+async fn play_round(
+    h: &mut Harness,
+    deployments: &[(&Keypair, usize, u64)],
+    value: [u8; 32],
+) -> (sim::Simulation, Vec<Miner>) {
+    h.set_var_value(value);
+
+    // Deploy.
+    let round_id = h.board().await.round_id;
+    let mut wallets: Vec<&Keypair> = vec![];
+    for &(wallet, square, amount) in deployments {
+        let mut squares = [false; 25];
+        squares[square] = true;
+        let ix = ore_api::sdk::deploy(wallet.pubkey(), wallet.pubkey(), amount, round_id, squares);
+        h.process(&[ix], &[wallet]).await.unwrap();
+        if !wallets.iter().any(|w| w.pubkey() == wallet.pubkey()) {
+            wallets.push(wallet);
+        }
+    }
+
+    // Simulate the settlement from the pre-reset state.
+    let round = h.round(round_id).await;
+    let mut miners = vec![];
+    for wallet in &wallets {
+        miners.push(h.miner(wallet.pubkey()).await);
+    }
+    let treasury = h.treasury().await;
+    let simulation = sim::simulate(&round, &miners, value, INITIAL_SUPPLY, treasury.motherlode);
+
+    // Reset and checkpoint.
+    h.warp_to_reset().await;
+    h.reset().await.unwrap();
+    let mut miners = vec![];
+    for wallet in &wallets {
+        let ix = ore_api::sdk::checkpoint(wallet.pubkey(), wallet.pubkey(), round_id);
+        h.process(&[ix], &[wallet]).await.unwrap();
+        miners.push(h.miner(wallet.pubkey()).await);
+    }
+
+    (simulation, miners)
+}
+
+/// Finds the `DeploySkippedEvent` among the data of logged events.
+fn skipped_event(events: &[Vec<u8>]) -> Option<DeploySkippedEvent> {
+    events.iter().find_map(|data| {
+        let event = bytemuck::try_pod_read_unaligned::<DeploySkippedEvent>(data).ok()?;
+        (event.disc == OreEvent::DeploySkipped as u64).then_some(event)
+    })
+}
+
+/// Strips the accounts events are logged through, as transactions built before events were added
+/// leave them out.
+fn without_event_accounts(mut ix: Instruction, len: usize) -> Instruction {
+    ix.accounts.retain(|meta| meta.pubkey != ore_api::ID);
+    ix.accounts.truncate(len);
+    ix
+}
+
+#[tokio::test]
+async fn test_full_round() {
+    let value = find_value(|round, r| round.winning_square(r) == 3 && !round.is_split_reward(r));
+    let mut h = Harness::new(0, value).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let fee_collector_before = h.lamports(h.fee_collector).await;
+
+    // Play a round where A wins.
+    let (simulation, miners) = play_round(
+        &mut h,
+        &[(&a, 3, LAMPORTS_PER_SOL), (&b, 7, 2 * LAMPORTS_PER_SOL)],
+        value,
+    )
+    .await;
+    let outcome = simulation.outcome;
+    assert_eq!(outcome.winning_square, Some(3));
+    assert_eq!(miners[0].rewards_sol, simulation.rewards[0].rewards_sol);
+    assert_eq!(miners[0].rewards_ore, ONE_ORE);
+    assert_eq!(miners[1].rewards_sol, 0);
+    assert_eq!(miners[1].rewards_ore, 0);
+    assert_eq!(miners[0].checkpoint_id, 1);
+
+    // Fees and vault.
+    assert_eq!(
+        h.lamports(h.fee_collector).await,
+        fee_collector_before + outcome.admin_fee
+    );
+    let treasury = h.treasury().await;
+    assert_eq!(treasury.balance, outcome.total_vaulted);
+    assert_eq!(treasury.motherlode, ONE_ORE / 5);
+    let board = h.board().await;
+    assert_eq!(board.round_id, 2);
+    assert_eq!(board.end_slot, u64::MAX);
+    assert_eq!(h.round(1).await.top_miner, a.pubkey());
+
+    // Claim SOL.
+    let before = h.lamports(a.pubkey()).await;
+    let ix = ore_api::sdk::claim_sol(a.pubkey());
+    h.process(&[ix], &[&a]).await.unwrap();
+    assert_eq!(h.lamports(a.pubkey()).await, before + miners[0].rewards_sol);
+    assert_eq!(h.miner(a.pubkey()).await.rewards_sol, 0);
+
+    // Claim ORE. A is the only miner with unclaimed ORE, so no fee is charged.
+    let ix = ore_api::sdk::claim_ore(a.pubkey());
+    h.process(&[ix], &[&a]).await.unwrap();
+    assert_eq!(h.ore_balance(a.pubkey()).await, ONE_ORE);
+    assert_eq!(h.miner(a.pubkey()).await.rewards_ore, 0);
+
+    // Play another round, then close it once it expires.
+    play_round(&mut h, &[(&b, 0, LAMPORTS_PER_SOL)], [0xFF; 32]).await;
+    h.warp_past_expiry(2).await;
+    let payer = h.context.payer.pubkey();
+    let ix = ore_api::sdk::close(payer, 2, payer);
+    h.process(&[ix], &[]).await.unwrap();
+    assert!(h.account::<Round>(round_pda(2).0).await.is_none());
+}
+
+#[tokio::test]
+async fn test_refund() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let fee_collector_before = h.lamports(h.fee_collector).await;
+
+    // A round without a slot hash refunds everything.
+    let (simulation, miners) = play_round(
+        &mut h,
+        &[(&a, 0, LAMPORTS_PER_SOL), (&a, 5, LAMPORTS_PER_SOL)],
+        [0xFF; 32],
+    )
+    .await;
+    assert_eq!(simulation.outcome.winning_square, None);
+    assert_eq!(miners[0].rewards_sol, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(miners[0].rewards_ore, 0);
+    assert_eq!(h.lamports(h.fee_collector).await, fee_collector_before);
+    assert_eq!(h.treasury().await.balance, 0);
+
+    let before = h.lamports(a.pubkey()).await;
+    let ix = ore_api::sdk::claim_sol(a.pubkey());
+    h.process(&[ix], &[&a]).await.unwrap();
+    assert_eq!(h.lamports(a.pubkey()).await, before + 2 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_empty_winning_square() {
+    let value = find_value(|round, r| round.winning_square(r) == 10);
+    let mut h = Harness::new(0, value).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let fee_collector_before = h.lamports(h.fee_collector).await;
+
+    // Nobody deployed on the winning square, so everything is vaulted.
+    let (simulation, miners) = play_round(&mut h, &[(&a, 0, LAMPORTS_PER_SOL)], value).await;
+    let admin_fee = LAMPORTS_PER_SOL / 100;
+    assert_eq!(simulation.outcome.admin_fee, admin_fee);
+    assert_eq!(miners[0].rewards_sol, 0);
+    assert_eq!(miners[0].rewards_ore, 0);
+    assert_eq!(
+        h.lamports(h.fee_collector).await,
+        fee_collector_before + admin_fee
+    );
+    let treasury = h.treasury().await;
+    assert_eq!(treasury.balance, LAMPORTS_PER_SOL - admin_fee);
+    assert_eq!(treasury.motherlode, 0);
+}
+
+#[tokio::test]
+async fn test_split_reward() {
+    let value = find_value(|round, r| {
+        round.winning_square(r) == 3 && round.is_split_reward(r) && !round.did_hit_motherlode(r)
+    });
+    let mut h = Harness::new(0, value).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // The top miner reward is split pro rata between both miners.
+    let (simulation, miners) = play_round(
+        &mut h,
+        &[(&a, 3, LAMPORTS_PER_SOL), (&b, 3, 3 * LAMPORTS_PER_SOL)],
+        value,
+    )
+    .await;
+    assert!(simulation.outcome.is_split);
+    assert_eq!(h.round(1).await.top_miner, SPLIT_ADDRESS);
+    assert_eq!(miners[0].rewards_ore, ONE_ORE / 4);
+    assert_eq!(miners[1].rewards_ore, ONE_ORE * 3 / 4);
+    for (miner, rewards) in miners.iter().zip(simulation.rewards.iter()) {
+        assert_eq!(miner.rewards_sol, rewards.rewards_sol);
+        assert_eq!(miner.rewards_ore, rewards.rewards_ore());
+    }
+}
+
+#[tokio::test]
+async fn test_motherlode() {
+    let pool = 50 * ONE_ORE;
+    let value = find_value(|round, r| {
+        round.winning_square(r) == 3 && round.did_hit_motherlode(r) && !round.is_split_reward(r)
+    });
+    let mut h = Harness::new(pool, value).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // Both miners share the motherlode, one of them also takes the top miner reward.
+    let (simulation, miners) = play_round(
+        &mut h,
+        &[(&a, 3, LAMPORTS_PER_SOL), (&b, 3, LAMPORTS_PER_SOL)],
+        value,
+    )
+    .await;
+    assert_eq!(simulation.outcome.motherlode, pool);
+    assert_eq!(h.round(1).await.motherlode, pool);
+    for (miner, rewards) in miners.iter().zip(simulation.rewards.iter()) {
+        assert_eq!(rewards.motherlode_ore, pool / 2);
+        assert_eq!(miner.rewards_ore, rewards.rewards_ore());
+    }
+    assert_eq!(
+        miners.iter().map(|m| m.rewards_ore).sum::<u64>(),
+        pool + ONE_ORE
+    );
+    assert_eq!(h.treasury().await.motherlode, ONE_ORE / 5);
+}
+
+#[tokio::test]
+async fn test_deploy_weighted() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // B deploys first, then A deploys different amounts to two squares.
+    let mut squares = [false; 25];
+    squares[9] = true;
+    let ix = ore_api::sdk::deploy(b.pubkey(), b.pubkey(), LAMPORTS_PER_SOL / 2, 1, squares);
+    h.process(&[ix], &[&b]).await.unwrap();
+    let mut amounts = [0; 25];
+    amounts[2] = LAMPORTS_PER_SOL;
+    amounts[9] = 3 * LAMPORTS_PER_SOL;
+    let ix = ore_api::sdk::deploy_weighted(a.pubkey(), a.pubkey(), 1, amounts);
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.deployed, amounts);
+    assert_eq!(miner.cumulative[2], 0);
+    assert_eq!(miner.cumulative[9], LAMPORTS_PER_SOL / 2);
+    let round = h.round(1).await;
+    assert_eq!(round.deployed[2], LAMPORTS_PER_SOL);
+    assert_eq!(
+        round.deployed[9],
+        3 * LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 2
+    );
+    assert_eq!(round.count[2], 1);
+    assert_eq!(round.count[9], 2);
+    assert_eq!(
+        round.total_deployed,
+        4 * LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 2
+    );
+}
+
+#[tokio::test]
+async fn test_automation() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+
+    // A automates deploys to squares 0 and 1.
+    let amount = LAMPORTS_PER_SOL / 10;
+    let fee = 5_000;
+    let ix = ore_api::sdk::automate(
+        a.pubkey(),
+        amount,
+        LAMPORTS_PER_SOL,
+        executor.pubkey(),
+        fee,
+        0b11,
+        AutomationStrategy::Preferred as u8,
+        0,
+        0,
+        0,
+    );
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    // The executor deploys on A's behalf.
+    let before = h.lamports(executor.pubkey()).await;
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [false; 25]);
+    h.process(&[ix], &[&executor]).await.unwrap();
+
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.deployed[0], amount);
+    assert_eq!(miner.deployed[1], amount);
+    assert_eq!(miner.deployed.iter().sum::<u64>(), 2 * amount);
+    let automation = h
+        .account::<Automation>(automation_pda(a.pubkey()).0)
+        .await
+        .unwrap();
+    assert_eq!(automation.balance, LAMPORTS_PER_SOL - 2 * amount - fee);
+    assert_eq!(h.lamports(executor.pubkey()).await, before + fee);
+
+    // Closing the automation returns its account to A.
+    let ix = ore_api::sdk::automate(a.pubkey(), 0, 0, Pubkey::default(), 0, 0, 0, 0, 0, 0);
+    h.process(&[ix], &[&a]).await.unwrap();
+    assert!(h
+        .account::<Automation>(automation_pda(a.pubkey()).0)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_automation_limits() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+
+    // A automates 1 round on squares 0-3, spending at most 2 squares plus the fee per round.
+    let amount = LAMPORTS_PER_SOL / 10;
+    let fee = 5_000;
+    let ix = ore_api::sdk::automate(
+        a.pubkey(),
+        amount,
+        LAMPORTS_PER_SOL,
+        executor.pubkey(),
+        fee,
+        0b1111,
+        AutomationStrategy::Preferred as u8,
+        1,
+        0,
+        2 * amount + fee,
+    );
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    // The budget stops the deploy after 2 squares, and the automation closes after its last round.
+    let before = h.lamports(a.pubkey()).await;
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [false; 25]);
+    h.process(&[ix], &[&executor]).await.unwrap();
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.deployed.iter().sum::<u64>(), 2 * amount);
+    assert!(h
+        .account::<Automation>(automation_pda(a.pubkey()).0)
+        .await
+        .is_none());
+    assert!(h.lamports(a.pubkey()).await > before + LAMPORTS_PER_SOL - 2 * amount - fee);
+}
+
+#[tokio::test]
+async fn test_automation_expired_underfunded() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+
+    // A automates until slot 5 with less than one square's worth of SOL.
+    let fee = 5_000;
+    let ix = ore_api::sdk::automate(
+        a.pubkey(),
+        LAMPORTS_PER_SOL,
+        LAMPORTS_PER_SOL / 10,
+        executor.pubkey(),
+        fee,
+        0b1,
+        AutomationStrategy::Preferred as u8,
+        0,
+        5,
+        0,
+    );
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    // Once expired, the executor's deploy still closes it and returns the balance to A.
+    h.context.warp_to_slot(10).unwrap();
+    let before = h.lamports(a.pubkey()).await;
+    let executor_before = h.lamports(executor.pubkey()).await;
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [false; 25]);
+    h.process(&[ix], &[&executor]).await.unwrap();
+    assert!(h
+        .account::<Automation>(automation_pda(a.pubkey()).0)
+        .await
+        .is_none());
+    assert!(h.lamports(a.pubkey()).await > before + LAMPORTS_PER_SOL / 10);
+    assert_eq!(h.lamports(executor.pubkey()).await, executor_before);
+    assert_eq!(h.miner(a.pubkey()).await.deployed, [0; 25]);
+}
+
+#[tokio::test]
+async fn test_automation_pays_once() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+
+    // B deploys to square 0 by hand, then A and B automate deploys to square 0.
+    let amount = LAMPORTS_PER_SOL / 10;
+    let fee = 5_000;
+    let mut squares = [false; 25];
+    squares[0] = true;
+    let ix = ore_api::sdk::deploy(b.pubkey(), b.pubkey(), amount, 1, squares);
+    h.process(&[ix], &[&b]).await.unwrap();
+    for wallet in [&a, &b] {
+        let ix = ore_api::sdk::automate(
+            wallet.pubkey(),
+            amount,
+            LAMPORTS_PER_SOL,
+            executor.pubkey(),
+            fee,
+            0b1,
+            AutomationStrategy::Preferred as u8,
+            0,
+            0,
+            0,
+        );
+        h.process(&[ix], &[wallet]).await.unwrap();
+    }
+
+    // The executor deploys for A once. A second deploy in the same round fails.
+    let before = h.lamports(executor.pubkey()).await;
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [false; 25]);
+    h.process(&[ix.clone()], &[&executor]).await.unwrap();
+    let err = h.process(&[ix], &[&executor]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::AutomationAlreadyDeployed as u32)
+        )
+    );
+    assert_eq!(h.lamports(executor.pubkey()).await, before + fee);
+    let automation = h
+        .account::<Automation>(automation_pda(a.pubkey()).0)
+        .await
+        .unwrap();
+    assert_eq!(automation.balance, LAMPORTS_PER_SOL - amount - fee);
+
+    // B already holds square 0, so a deploy for B would deploy nothing and fails.
+    let ix = ore_api::sdk::deploy(executor.pubkey(), b.pubkey(), 0, 1, [false; 25]);
+    let err = h.process(&[ix], &[&executor]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::AmountTooSmall as u32)
+        )
+    );
+    assert_eq!(h.lamports(executor.pubkey()).await, before + fee);
+}
+
+#[tokio::test]
+async fn test_automation_least_crowded() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+
+    // B deploys to every square except 4 and 17.
+    let mut squares = [true; 25];
+    squares[4] = false;
+    squares[17] = false;
+    let ix = ore_api::sdk::deploy(b.pubkey(), b.pubkey(), LAMPORTS_PER_SOL / 100, 1, squares);
+    h.process(&[ix], &[&b]).await.unwrap();
+
+    // A automates deploys to the 2 least crowded squares.
+    let amount = LAMPORTS_PER_SOL / 10;
+    let ix = ore_api::sdk::automate(
+        a.pubkey(),
+        amount,
+        LAMPORTS_PER_SOL,
+        executor.pubkey(),
+        5_000,
+        2,
+        AutomationStrategy::LeastCrowded as u8,
+        0,
+        0,
+        0,
+    );
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    // The program picks the squares, whatever the executor passes.
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [true; 25]);
+    h.process(&[ix.clone()], &[&executor]).await.unwrap();
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.deployed[4], amount);
+    assert_eq!(miner.deployed[17], amount);
+    assert_eq!(miner.deployed.iter().sum::<u64>(), 2 * amount);
+
+    // Squares 4 and 17 are now the most crowded, but the executor cannot re-pick for another fee.
+    let before = h.lamports(executor.pubkey()).await;
+    let err = h.process(&[ix], &[&executor]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::AutomationAlreadyDeployed as u32)
+        )
+    );
+    assert_eq!(h.lamports(executor.pubkey()).await, before);
+    assert_eq!(h.miner(a.pubkey()).await.deployed, miner.deployed);
+}
+
+#[tokio::test]
+async fn test_deploy_requires_checkpoint() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // Play round 1, then skip the checkpoint.
+    let mut squares = [false; 25];
+    squares[0] = true;
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&a]).await.unwrap();
+    h.warp_to_reset().await;
+    h.reset().await.unwrap();
+
+    // Deploying to round 2 fails with a typed error.
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 2, squares);
+    let err = h.process(&[ix], &[&a]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::NotCheckpointed as u32)
+        )
+    );
+
+    // Checkpointing unblocks the deploy.
+    let checkpoint = ore_api::sdk::checkpoint(a.pubkey(), a.pubkey(), 1);
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 2, squares);
+    h.process(&[checkpoint, ix], &[&a]).await.unwrap();
+    assert_eq!(h.miner(a.pubkey()).await.round_id, 2);
+}
+
+#[tokio::test]
+async fn test_deploy_with_checkpoint() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // Play round 1, then skip the checkpoint. The round has no slot hash, so it is refunded.
+    let mut squares = [false; 25];
+    squares[0] = true;
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&a]).await.unwrap();
+    h.warp_to_reset().await;
+    h.reset().await.unwrap();
+
+    // The deploy checkpoints round 1 itself when given its account.
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 2, squares);
+    let ix = ore_api::sdk::with_checkpoint(ix, 1);
+    h.process(&[ix], &[&a]).await.unwrap();
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.checkpoint_id, 1);
+    assert_eq!(miner.round_id, 2);
+    assert_eq!(miner.rewards_sol, LAMPORTS_PER_SOL);
+    assert_eq!(miner.deployed[0], LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_deploy_guard() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let c = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // A piles onto squares 0 and 1.
+    let mut squares = [false; 25];
+    squares[0] = true;
+    squares[1] = true;
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&a]).await.unwrap();
+
+    // B expected square 0 to hold at most 0.5 SOL, so it is skipped. Square 1 still gives B half
+    // the pool, above the 25% minimum.
+    squares[2] = true;
+    let mut max_deployed = [u64::MAX; 25];
+    max_deployed[0] = LAMPORTS_PER_SOL / 2;
+    let guard = DeployGuard::new(0, max_deployed, 2_500, false);
+    let ix = ore_api::sdk::deploy(b.pubkey(), b.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    let ix = ore_api::sdk::with_guard(ix, guard);
+    let events = h.process_with_events(&[ix], &[&b]).await.unwrap();
+    let miner = h.miner(b.pubkey()).await;
+    assert_eq!(miner.deployed[0], 0);
+    assert_eq!(miner.deployed[1], LAMPORTS_PER_SOL);
+    assert_eq!(miner.deployed[2], LAMPORTS_PER_SOL);
+
+    // The skipped square is reported as guarded.
+    let event = skipped_event(&events).unwrap();
+    assert_eq!(event.authority, b.pubkey());
+    assert_eq!(event.held, 0);
+    assert_eq!(event.guarded, 1);
+
+    // A strict guard fails the whole deploy instead.
+    let guard = DeployGuard::new(0, max_deployed, 0, true);
+    let ix = ore_api::sdk::deploy(c.pubkey(), c.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    let ix = ore_api::sdk::with_guard(ix, guard);
+    let err = h.process(&[ix], &[&c]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::DeployGuardFailed as u32)
+        )
+    );
+
+    // So does landing after the max slot.
+    h.context.warp_to_slot(10).unwrap();
+    let guard = DeployGuard::new(9, [u64::MAX; 25], 0, false);
+    let ix = ore_api::sdk::deploy(c.pubkey(), c.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    let ix = ore_api::sdk::with_guard(ix, guard);
+    let err = h.process(&[ix], &[&c]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(OreError::DeployDeadlinePassed as u32)
+        )
+    );
+
+    // Deploys without a guard are not limited.
+    let ix = ore_api::sdk::deploy(c.pubkey(), c.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&c]).await.unwrap();
+    assert_eq!(h.round(1).await.deployed[0], 2 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_deploy_top_up() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let b = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let mut squares = [false; 25];
+    squares[0] = true;
+
+    // A tops up square 0 while no one else has deployed to it.
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&a]).await.unwrap();
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL / 2, 1, squares);
+    let events = h.process_with_events(&[ix], &[&a]).await.unwrap();
+    assert!(skipped_event(&events).is_none());
+    let miner = h.miner(a.pubkey()).await;
+    assert_eq!(miner.deployed[0], 3 * LAMPORTS_PER_SOL / 2);
+    assert_eq!(miner.cumulative[0], 0);
+    let round = h.round(1).await;
+    assert_eq!(round.deployed[0], 3 * LAMPORTS_PER_SOL / 2);
+    assert_eq!(round.count[0], 1);
+
+    // Once B deploys after it, A's range is no longer at the end and the top-up is skipped.
+    let ix = ore_api::sdk::deploy(b.pubkey(), b.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[ix], &[&b]).await.unwrap();
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    let events = h.process_with_events(&[ix], &[&a]).await.unwrap();
+    assert_eq!(
+        h.miner(a.pubkey()).await.deployed[0],
+        3 * LAMPORTS_PER_SOL / 2
+    );
+    let event = skipped_event(&events).unwrap();
+    assert_eq!(event.authority, a.pubkey());
+    assert_eq!(event.round_id, 1);
+    assert_eq!(event.held, 1);
+    assert_eq!(event.guarded, 0);
+    let miner = h.miner(b.pubkey()).await;
+    assert_eq!(miner.cumulative[0], 3 * LAMPORTS_PER_SOL / 2);
+    let round = h.round(1).await;
+    assert_eq!(round.deployed[0], 5 * LAMPORTS_PER_SOL / 2);
+    assert_eq!(round.count[0], 2);
+}
+
+#[tokio::test]
+async fn test_legacy_accounts() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+
+    // Deploy, checkpoint and claim still work without the event accounts, and emit nothing.
+    let mut squares = [false; 25];
+    squares[0] = true;
+    let ix = ore_api::sdk::deploy(a.pubkey(), a.pubkey(), LAMPORTS_PER_SOL, 1, squares);
+    h.process(&[without_event_accounts(ix, 9)], &[&a])
+        .await
+        .unwrap();
+    assert_eq!(h.miner(a.pubkey()).await.deployed[0], LAMPORTS_PER_SOL);
+    h.warp_to_reset().await;
+    h.reset().await.unwrap();
+    let ix = ore_api::sdk::checkpoint(a.pubkey(), a.pubkey(), 1);
+    h.process(&[without_event_accounts(ix, 6)], &[&a])
+        .await
+        .unwrap();
+    assert_eq!(h.miner(a.pubkey()).await.rewards_sol, LAMPORTS_PER_SOL);
+    let before = h.lamports(a.pubkey()).await;
+    let ix = ore_api::sdk::claim_sol(a.pubkey());
+    h.process(&[without_event_accounts(ix, 3)], &[&a])
+        .await
+        .unwrap();
+    assert_eq!(h.lamports(a.pubkey()).await, before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn test_legacy_automation() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(10 * LAMPORTS_PER_SOL).await;
+    let executor = h.wallet(LAMPORTS_PER_SOL).await;
+    let address = automation_pda(a.pubkey()).0;
+    let size = 8 + std::mem::size_of::<Automation>();
+    let rent = Rent::default();
+    let extra_rent = rent.minimum_balance(size) - rent.minimum_balance(LEGACY_AUTOMATION_LEN);
+
+    // A automates deploys to squares 0 and 1 with an automation opened before limits were added.
+    let amount = LAMPORTS_PER_SOL / 10;
+    let fee = 5_000;
+    let ix = ore_api::sdk::automate(
+        a.pubkey(),
+        amount,
+        LAMPORTS_PER_SOL,
+        executor.pubkey(),
+        fee,
+        0b11,
+        AutomationStrategy::Preferred as u8,
+        0,
+        0,
+        0,
+    );
+    h.process(&[ix], &[&a]).await.unwrap();
+    h.set_legacy_automation(a.pubkey()).await;
+
+    // The executor's deploy grows the account, paying the extra rent from its balance.
+    let ix = ore_api::sdk::deploy(executor.pubkey(), a.pubkey(), 0, 1, [false; 25]);
+    h.process(&[ix], &[&executor]).await.unwrap();
+    assert_eq!(
+        h.miner(a.pubkey()).await.deployed.iter().sum::<u64>(),
+        2 * amount
+    );
+    let automation = h.account::<Automation>(address).await.unwrap();
+    assert_eq!(
+        automation.balance,
+        LAMPORTS_PER_SOL - 2 * amount - fee - extra_rent
+    );
+    assert_eq!(automation.rounds, 1);
+    assert_eq!(automation.round_id, 1);
+
+    // Automate data and accounts from before limits were added still work, and set no limits.
+    // A pays the extra rent to grow a legacy account.
+    let balance = h.account::<Automation>(address).await.unwrap().balance;
+    h.set_legacy_automation(a.pubkey()).await;
+    let mut ix = ore_api::sdk::automate(
+        a.pubkey(),
+        amount,
+        0,
+        executor.pubkey(),
+        fee,
+        0b11,
+        AutomationStrategy::Preferred as u8,
+        5,
+        100,
+        amount,
+    );
+    ix.data.truncate(1 + std::mem::size_of::<Automate>());
+    let before = h.lamports(address).await;
+    h.process(&[without_event_accounts(ix, 5)], &[&a])
+        .await
+        .unwrap();
+    assert_eq!(h.lamports(address).await, before + extra_rent);
+    let automation = h.account::<Automation>(address).await.unwrap();
+    assert_eq!(automation.balance, balance);
+    assert_eq!(automation.max_rounds, 0);
+    assert_eq!(automation.expires_at, 0);
+    assert_eq!(automation.max_per_round, 0);
+    assert_eq!(automation.round_id, u64::MAX);
+}
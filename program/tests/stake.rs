@@ -0,0 +1,34 @@
+mod common;
+
+use common::Harness;
+use ore_api::prelude::*;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+
+#[tokio::test]
+async fn test_deposit_withdraw() {
+    let mut h = Harness::new(0, [0xFF; 32]).await;
+    let a = h.wallet(LAMPORTS_PER_SOL).await;
+    h.set_ore_balance(a.pubkey(), 10 * ONE_ORE);
+
+    // Deposit.
+    let ix = ore_api::sdk::deposit(a.pubkey(), 4 * ONE_ORE);
+    h.process(&[ix], &[&a]).await.unwrap();
+    let stake = h.account::<Stake>(stake_pda(a.pubkey()).0).await.unwrap();
+    assert_eq!(stake.authority, a.pubkey());
+    assert_eq!(stake.balance, 4 * ONE_ORE);
+    assert_eq!(h.ore_balance(a.pubkey()).await, 6 * ONE_ORE);
+
+    // Withdraw.
+    let ix = ore_api::sdk::withdraw(a.pubkey(), ONE_ORE);
+    h.process(&[ix], &[&a]).await.unwrap();
+    let stake = h.account::<Stake>(stake_pda(a.pubkey()).0).await.unwrap();
+    assert_eq!(stake.balance, 3 * ONE_ORE);
+    assert_eq!(h.ore_balance(a.pubkey()).await, 7 * ONE_ORE);
+
+    // Withdrawing more than the stake balance withdraws everything.
+    let ix = ore_api::sdk::withdraw(a.pubkey(), 4 * ONE_ORE);
+    h.process(&[ix], &[&a]).await.unwrap();
+    let stake = h.account::<Stake>(stake_pda(a.pubkey()).0).await.unwrap();
+    assert_eq!(stake.balance, 0);
+    assert_eq!(h.ore_balance(a.pubkey()).await, 10 * ONE_ORE);
+}
@@ -35,4 +35,4 @@ echo "  r     - Claim rewards"
 echo "  +/-   - Adjust deploy amount"
 echo ""
 
-COMMAND=tui cargo run --release
\ No newline at end of file
+cargo run --release -- tui
\ No newline at end of file
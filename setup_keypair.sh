@@ -55,4 +55,4 @@ echo "  KEYPAIR=$KEYPAIR"
 echo "  RPC=$RPC"
 echo ""
 echo "Once you have keypair.json, run:"
-echo "  COMMAND=board cargo run --package ore-cli"
+echo "  cargo run --package ore-cli -- board"